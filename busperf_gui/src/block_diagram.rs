@@ -0,0 +1,187 @@
+//! Overview of all buses as a block diagram of modules in which they are described.
+
+use std::collections::BTreeMap;
+
+use eframe::{
+    egui::{self, Color32, RichText, Ui},
+    epaint::Hsva,
+};
+use libbusperf::bus_usage::{BusData, Statistic};
+
+/// Statistic by which buses are colored.
+#[derive(PartialEq, Clone, Copy)]
+pub enum Coloring {
+    Utilization,
+    Backpressure,
+}
+
+impl Coloring {
+    fn label(&self) -> &'static str {
+        match self {
+            Coloring::Utilization => "Utilization [%]",
+            Coloring::Backpressure => "Backpressure rate [%]",
+        }
+    }
+}
+
+// Module with indices of buses described directly in it and its submodules
+#[derive(Default)]
+struct Module {
+    buses: Vec<usize>,
+    submodules: BTreeMap<String, Module>,
+}
+
+impl Module {
+    fn build(usages: &[BusData]) -> Self {
+        let mut root = Module::default();
+        for (i, u) in usages.iter().enumerate() {
+            let mut module = &mut root;
+            for name in u.scope.iter() {
+                module = module.submodules.entry(name.clone()).or_default();
+            }
+            module.buses.push(i);
+        }
+        root
+    }
+}
+
+// Bus as drawn in the diagram, with percentages by which it is colored. [None] if the bus has no
+// such statistic, e.g. backpressure of multichannel buses.
+struct Bus {
+    name: String,
+    utilization: Option<f64>,
+    backpressure: Option<f64>,
+}
+
+impl Bus {
+    fn new(bus: &BusData) -> Self {
+        let timelines = bus
+            .get_statistics(&[])
+            .into_iter()
+            .filter_map(|s| match s {
+                Statistic::Timeline(t) => Some((t.name, t.value)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let value = |name: &str| {
+            timelines
+                .iter()
+                .find(|(n, _)| *n == name)
+                .map(|(_, v)| *v)
+                .filter(|v| v.is_finite())
+        };
+        Bus {
+            name: bus.display_name(),
+            // Multichannel buses transfer at most about one transaction per clock cycle
+            utilization: value("Utilization [%]").or(value("Bandwidth [t/clk]").map(|b| b * 100.0)),
+            backpressure: value("Backpressure rate [%]"),
+        }
+    }
+
+    fn value(&self, coloring: Coloring) -> Option<f64> {
+        match coloring {
+            Coloring::Utilization => self.utilization,
+            Coloring::Backpressure => self.backpressure,
+        }
+    }
+}
+
+// Green for 0%, through yellow, to red for 100%
+fn color(percentage: Option<f64>) -> Color32 {
+    match percentage {
+        Some(p) => {
+            let h = (1.0 - p.clamp(0.0, 100.0) as f32 / 100.0) / 3.0;
+            Hsva::new(h, 0.85, 0.6, 1.0).into()
+        }
+        None => Color32::GRAY,
+    }
+}
+
+fn draw_module(
+    ui: &mut Ui,
+    module: &Module,
+    buses: &[Bus],
+    coloring: Coloring,
+    clicked: &mut Option<usize>,
+) {
+    if !module.buses.is_empty() {
+        ui.horizontal_wrapped(|ui| {
+            for &i in module.buses.iter() {
+                let bus = &buses[i];
+                let value = bus.value(coloring);
+                let button = egui::Button::new(RichText::new(&bus.name).color(Color32::BLACK))
+                    .fill(color(value));
+                let hover = match value {
+                    Some(v) => format!("{}: {v:.2}", coloring.label()),
+                    None => format!("{}: not available", coloring.label()),
+                };
+                if ui.add(button).on_hover_text(hover).clicked() {
+                    *clicked = Some(i);
+                }
+            }
+        });
+    }
+    ui.horizontal_wrapped(|ui| {
+        for (name, submodule) in module.submodules.iter() {
+            // Modules with no buses and a single submodule are joined into one box
+            let mut name = name.to_string();
+            let mut submodule = submodule;
+            while submodule.buses.is_empty() && submodule.submodules.len() == 1 {
+                let (child, module) = submodule.submodules.iter().next().unwrap();
+                name = format!("{name}.{child}");
+                submodule = module;
+            }
+            egui::Frame::group(ui.style()).show(ui, |ui| {
+                ui.vertical(|ui| {
+                    ui.strong(name);
+                    draw_module(ui, submodule, buses, coloring, clicked);
+                });
+            });
+        }
+    });
+}
+
+/// Modules in which buses are described, drawn as nested boxes with buses colored by a statistic.
+/// Values of statistics are computed once, when the buses are set.
+pub struct BlockDiagram {
+    root: Module,
+    buses: Vec<Bus>,
+    coloring: Coloring,
+}
+
+impl BlockDiagram {
+    pub fn new(usages: &[BusData]) -> Self {
+        BlockDiagram {
+            root: Module::build(usages),
+            buses: usages.iter().map(Bus::new).collect(),
+            coloring: Coloring::Utilization,
+        }
+    }
+
+    /// Replaces drawn buses, keeping the statistic by which they are colored.
+    pub fn set_usages(&mut self, usages: &[BusData]) {
+        self.root = Module::build(usages);
+        self.buses = usages.iter().map(Bus::new).collect();
+    }
+
+    /// Draws the diagram. Returns index of the clicked bus.
+    pub fn draw(&mut self, ui: &mut Ui) -> Option<usize> {
+        ui.horizontal(|ui| {
+            ui.label("Color by: ");
+            ui.radio_value(&mut self.coloring, Coloring::Utilization, "utilization")
+                .on_hover_text("Bandwidth in transactions per clock cycle for multichannel buses");
+            ui.radio_value(&mut self.coloring, Coloring::Backpressure, "backpressure");
+            ui.separator();
+            for (label, percentage) in [("0%", 0.0), ("50%", 50.0), ("100%", 100.0)] {
+                ui.colored_label(color(Some(percentage)), label);
+            }
+            ui.colored_label(color(None), "not available");
+        });
+        ui.separator();
+        let mut clicked = None;
+        egui::ScrollArea::both().show(ui, |ui| {
+            draw_module(ui, &self.root, &self.buses, self.coloring, &mut clicked);
+        });
+        clicked
+    }
+}
@@ -1,4 +1,5 @@
 use std::error::Error;
+use std::sync::mpsc::{Receiver, TryRecvError};
 use std::{cell::RefCell, collections::HashMap, io::Read};
 
 use eframe::{
@@ -23,22 +24,34 @@ impl TimescaleUnit {
     }
 }
 
+impl From<Timescale> for TimescaleUnit {
+    fn from(value: Timescale) -> Self {
+        // Trace timescale factors are powers of 10 (1, 10 or 100)
+        TimescaleUnit(value.exponent as i32 + value.factor.max(1).ilog10() as i32)
+    }
+}
+
 impl From<&TimescaleUnit> for i32 {
     fn from(value: &TimescaleUnit) -> Self {
         value.0
     }
 }
 
+use crate::block_diagram::BlockDiagram;
 use crate::surfer_egui::{self, SurferData, surfer_ui_buckets};
 
 use libbusperf::{
-    CyclesNum,
+    CyclesNum, Timescale,
+    binning::Binning,
     bus_usage::{BusData, BusUsage, Statistic},
+    diagnostics::{Diagnostics, Severity},
 };
 
 #[derive(PartialEq)]
 pub enum PlotScale {
-    Log,
+    /// Buckets of the binning of the statistic.
+    Bins,
+    /// Every duration separately.
     Lin,
 }
 
@@ -75,7 +88,8 @@ pub struct TimelinePlot {
 impl TimelinePlot {
     pub fn new(timescale_unit: TimescaleUnit, pointer: Option<PlotPoint>) -> Self {
         Self {
-            timescale_unit,
+            // Plot can only be shown in s, ms, us, ns or ps
+            timescale_unit: TimescaleUnit(timescale_unit.0.div_euclid(3) * 3),
             pointer,
             period_start: 0.0,
             period_end: 0.0,
@@ -93,13 +107,24 @@ impl std::fmt::Display for PlotType {
     }
 }
 
+#[derive(PartialEq, Clone, Copy)]
+enum View {
+    /// Block diagram of all buses
+    Overview,
+    /// Statistics of the selected bus
+    Details,
+}
+
 pub struct BusperfApp {
     usages: Vec<BusData>,
     selected: usize,
+    view: View,
+    overview: BlockDiagram,
     waveform_time_unit: TimescaleUnit,
     left: PlotType,
     right: PlotType,
     surfer: SurferData,
+    updates: Option<Receiver<(Vec<BusData>, Timescale)>>,
 }
 
 impl BusperfApp {
@@ -109,13 +134,9 @@ impl BusperfApp {
         decoder
             .read_to_end(&mut buf)
             .map_err(|_| "invalid file: failed decompression")?;
-        let config = bincode::config::standard();
-        let data: (String, String, Vec<BusData>) = bincode::decode_from_slice(&buf, config)
-            .map_err(|_| "invalid file data")?
-            .0;
-        let (waveform_path, hash, usages) = data;
+        let (waveform_path, hash, timescale, usages) = libbusperf::saved::decode(&buf)?;
         let surfer_data = SurferData::new(waveform_path, Some(hash))?;
-        Ok(BusperfApp::new(usages, surfer_data, TimescaleUnit(-9)))
+        Ok(BusperfApp::new(usages, surfer_data, timescale.into()))
     }
 
     pub fn new(usages: Vec<BusData>, surfer: SurferData, time_unit: TimescaleUnit) -> Self {
@@ -125,22 +146,68 @@ impl BusperfApp {
             PlotType::Timeline(TimelinePlot::new(time_unit, None))
         };
         Self {
+            overview: BlockDiagram::new(&usages),
             usages,
             selected: 0,
+            view: View::Details,
             waveform_time_unit: time_unit,
-            left: PlotType::Buckets(BucketsPlot::new(PlotScale::Log)),
+            left: PlotType::Buckets(BucketsPlot::new(PlotScale::Bins)),
             right,
             surfer,
+            updates: None,
+        }
+    }
+
+    /// Replaces shown results with ones received from `updates`, e.g. during analysis of a trace
+    /// that is still being written.
+    pub fn with_updates(mut self, updates: Receiver<(Vec<BusData>, Timescale)>) -> Self {
+        self.updates = Some(updates);
+        self
+    }
+
+    fn receive_updates(&mut self, ctx: &egui::Context) {
+        let Some(updates) = &self.updates else {
+            return;
+        };
+        let mut latest = None;
+        loop {
+            match updates.try_recv() {
+                Ok(update) => latest = Some(update),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.updates = None;
+                    break;
+                }
+            }
+        }
+        if let Some((usages, timescale)) = latest
+            && !usages.is_empty()
+        {
+            self.selected = self.selected.min(usages.len() - 1);
+            self.overview.set_usages(&usages);
+            self.usages = usages;
+            self.waveform_time_unit = timescale.into();
+        }
+        if self.updates.is_some() {
+            ctx.request_repaint_after(std::time::Duration::from_millis(200));
         }
     }
 
     fn draw_statistics(&mut self, ui: &mut Ui, skipped_stats: &[String]) {
-        let BusData { usage, signals } = &self.usages[self.selected];
-        let statistics = usage.get_statistics(skipped_stats);
+        let bus = &self.usages[self.selected];
+        let BusData {
+            usage,
+            signals,
+            diagnostics,
+            ..
+        } = bus;
+        let statistics = bus.get_statistics(skipped_stats);
         let signals = signals.iter().map(|s| format!("{s}")).collect();
         self.surfer.set_signals_and_name(signals, usage.get_name());
 
         draw_values(ui, &statistics);
+        draw_phases(ui, &self.usages, usage.get_name(), skipped_stats);
+        draw_diagnostics(ui, diagnostics, &self.waveform_time_unit);
         let size = ui.available_size();
         let id = self.selected;
         ui.horizontal(|ui| {
@@ -172,12 +239,19 @@ impl BusperfApp {
 
 impl eframe::App for BusperfApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        self.receive_updates(ctx);
+        egui::TopBottomPanel::top("view_selector").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.selectable_value(&mut self.view, View::Overview, "Overview");
+                ui.selectable_value(&mut self.view, View::Details, "Details");
+            });
+        });
         egui::SidePanel::new(egui::panel::Side::Left, "bus_selector").show(ctx, |ui| {
             ui.heading("Bus");
             ui.separator();
             for (i, u) in self.usages.iter().enumerate() {
                 ui.with_layout(egui::Layout::default().with_cross_justify(true), |ui| {
-                    let name = u.usage.get_name();
+                    let name = u.display_name();
                     let mut job = LayoutJob::simple_singleline(
                         name.to_string(),
                         FontId::proportional(12.0),
@@ -190,8 +264,13 @@ impl eframe::App for BusperfApp {
                         ..Default::default()
                     };
                     let text = ui.fonts(|f| f.layout_job(job));
-                    ui.selectable_value(&mut self.selected, i, text)
-                        .on_hover_text(name);
+                    if ui
+                        .selectable_value(&mut self.selected, i, text)
+                        .on_hover_text(name)
+                        .clicked()
+                    {
+                        self.view = View::Details;
+                    }
                 });
             }
             if ctx.input(|i| i.key_pressed(egui::Key::ArrowDown)) {
@@ -205,11 +284,19 @@ impl eframe::App for BusperfApp {
                 }
             }
         });
-        egui::CentralPanel::default().show(ctx, |ui| {
-            let result = &self.usages[self.selected];
-            ui.heading(RichText::new(result.usage.get_name()).color(Color32::WHITE));
-            ui.separator();
-            self.draw_statistics(ui, &[]);
+        egui::CentralPanel::default().show(ctx, |ui| match self.view {
+            View::Overview => {
+                if let Some(clicked) = self.overview.draw(ui) {
+                    self.selected = clicked;
+                    self.view = View::Details;
+                }
+            }
+            View::Details => {
+                let result = &self.usages[self.selected];
+                ui.heading(RichText::new(result.display_name()).color(Color32::WHITE));
+                ui.separator();
+                self.draw_statistics(ui, &[]);
+            }
         });
     }
 }
@@ -256,13 +343,73 @@ fn estimate_size(statistic: &Statistic) -> f32 {
                 .sum::<usize>() as f32
                 * 20.0
         }
-        Statistic::Bucket(buckets_statistic) => buckets_statistic.display().len() as f32 * 8.0,
+        Statistic::Bucket(buckets_statistic) => {
+            let summary = buckets_statistic
+                .summary()
+                .map(|s| s.to_string().len())
+                .unwrap_or_default();
+            (buckets_statistic.display().len() as f32 * 8.0).max(summary as f32 * 6.0)
+        }
         Statistic::Timeline(timeline_statistic) => {
             (timeline_statistic.name.len() + timeline_statistic.display.len()) as f32 * 10.0
         }
     }
 }
 
+// Shows summaries of the statistics of a bus in every phase next to each other
+fn draw_phases(ui: &mut Ui, usages: &[BusData], bus_name: &str, skipped_stats: &[String]) {
+    let phases = usages
+        .iter()
+        .filter(|u| u.phase.is_some() && u.usage.get_name() == bus_name)
+        .collect::<Vec<_>>();
+    if phases.len() < 2 {
+        return;
+    }
+    ui.collapsing("Phases", |ui| {
+        egui::Grid::new("phases").striped(true).show(ui, |ui| {
+            ui.label("");
+            for u in phases.iter() {
+                ui.strong(u.phase.as_deref().unwrap_or_default());
+            }
+            ui.end_row();
+            let statistics = phases
+                .iter()
+                .map(|u| u.get_statistics(skipped_stats))
+                .collect::<Vec<_>>();
+            for (i, statistic) in statistics[0].iter().enumerate() {
+                ui.label(statistic.name());
+                for phase_statistics in statistics.iter() {
+                    ui.label(phase_statistics[i].display());
+                }
+                ui.end_row();
+            }
+        });
+    });
+}
+
+fn draw_diagnostics(ui: &mut Ui, diagnostics: &Diagnostics, time_unit: &TimescaleUnit) {
+    if diagnostics.is_empty() {
+        return;
+    }
+    let timescale = Timescale::new(1, time_unit.0 as i8);
+    let count = diagnostics.iter().map(|d| d.count).sum::<u32>();
+    ui.collapsing(format!("Diagnostics ({count})"), |ui| {
+        egui::Grid::new("diagnostics").striped(true).show(ui, |ui| {
+            for d in diagnostics.iter() {
+                let color = match d.severity {
+                    Severity::Warning => Color32::YELLOW,
+                    Severity::Error => Color32::RED,
+                };
+                ui.colored_label(color, d.severity.to_string());
+                ui.label(&d.message).on_hover_text(&d.kind);
+                ui.label(format!("{}x", d.count));
+                ui.label(format!("first at {}", timescale.format_time(d.time)));
+                ui.end_row();
+            }
+        });
+    });
+}
+
 fn draw_values(ui: &mut Ui, statistics: &[Statistic]) {
     ui.allocate_ui(vec2(ui.available_size_before_wrap().x, 20.0), |ui| {
         ui.with_layout(
@@ -318,24 +465,33 @@ fn draw_values(ui: &mut Ui, statistics: &[Statistic]) {
                                             percentage_statistic.description
                                         }
                                         Statistic::Bucket(buckets_statistic) => {
-                                            ui.add_sized(
-                                                vec2(10.0, 30.0),
-                                                Label::new(
-                                                    egui::RichText::new(
-                                                        buckets_statistic.display(),
-                                                    )
-                                                    .font(egui::FontId::proportional(16.0))
-                                                    .color(get_color(stat_id)),
-                                                ),
-                                            )
-                                            .context_menu(|ui| {
-                                                let mut color = get_color(stat_id);
-                                                egui::widgets::color_picker::color_picker_color32(
-                                                    ui,
-                                                    &mut color,
-                                                    egui::color_picker::Alpha::Opaque,
-                                                );
-                                                set_color(stat_id, color);
+                                            ui.vertical(|ui| {
+                                                ui.add_sized(
+                                                    vec2(10.0, 30.0),
+                                                    Label::new(
+                                                        egui::RichText::new(
+                                                            buckets_statistic.display(),
+                                                        )
+                                                        .font(egui::FontId::proportional(16.0))
+                                                        .color(get_color(stat_id)),
+                                                    ),
+                                                )
+                                                .context_menu(|ui| {
+                                                    let mut color = get_color(stat_id);
+                                                    egui::widgets::color_picker::color_picker_color32(
+                                                        ui,
+                                                        &mut color,
+                                                        egui::color_picker::Alpha::Opaque,
+                                                    );
+                                                    set_color(stat_id, color);
+                                                });
+                                                if let Some(summary) = buckets_statistic.summary() {
+                                                    ui.label(
+                                                        RichText::new(summary.to_string())
+                                                            .font(FontId::proportional(12.0))
+                                                            .color(get_color(stat_id)),
+                                                    );
+                                                }
                                             });
                                             buckets_statistic.description
                                         }
@@ -405,7 +561,7 @@ fn draw_plot(
                 let bucket_button: ItemCreation = Box::new(|ui: &mut Ui, active, type_| {
                     ui.selectable_value(
                         type_,
-                        PlotType::Buckets(BucketsPlot::new(PlotScale::Log)),
+                        PlotType::Buckets(BucketsPlot::new(PlotScale::Bins)),
                         if active {
                             "Buckets"
                         } else {
@@ -488,6 +644,14 @@ fn format_bucket_label(i: f64) -> String {
     }
 }
 
+// Label of the bucket at `i` on the plot, log2 buckets are labeled also between bars
+fn bucket_label(binning: &Binning, i: f64) -> String {
+    match binning {
+        Binning::Log2 => format_bucket_label(i),
+        binning => binning.label(i.round() as i32),
+    }
+}
+
 fn draw_buckets(
     ui: &mut Ui,
     statistics: &[Statistic],
@@ -502,6 +666,12 @@ fn draw_buckets(
         Statistic::Timeline(_) => None,
     });
     let statistics_num = statistics.clone().count();
+    // Axis is labeled only if all statistics are binned in the same way
+    let binning = statistics
+        .clone()
+        .next()
+        .map(|(_, s)| s.binning.clone())
+        .filter(|b| statistics.clone().all(|(_, s)| s.binning == *b));
     if statistics_num == 0 {
         ui.label(
             egui::RichText::new("There are no statistics to display on a barchart for this bus.")
@@ -524,13 +694,13 @@ fn draw_buckets(
         let BucketsPlot { scale, selected } = buckets;
         ui.horizontal(|ui| {
             ui.label("Scale: ");
-            ui.radio_value(scale, PlotScale::Log, "log");
+            ui.radio_value(scale, PlotScale::Bins, "bins");
             ui.radio_value(scale, PlotScale::Lin, "lin");
         });
-        let response = if *scale == PlotScale::Log {
-            Plot::new(("buckets", id)).x_axis_formatter(|marker, _| {
-                let i = marker.value;
-                format_bucket_label(i)
+        let response = if *scale == PlotScale::Bins {
+            Plot::new(("buckets", id)).x_axis_formatter(move |marker, _| match &binning {
+                Some(binning) => bucket_label(binning, marker.value),
+                None => format!("{}", marker.value),
             })
         } else {
             Plot::new(("buckets", id))
@@ -548,7 +718,7 @@ fn draw_buckets(
             for (i, (stat_id, buckets_statistic)) in statistics.into_iter().enumerate() {
                 barcharts.insert(Id::new(buckets_statistic.name), buckets_statistic);
                 plot_ui.bar_chart(
-                    if *scale == PlotScale::Log {
+                    if *scale == PlotScale::Bins {
                         BarChart::new(
                             buckets_statistic.name,
                             buckets_statistic
@@ -563,13 +733,16 @@ fn draw_buckets(
                                 })
                                 .collect::<Vec<_>>(),
                         )
-                        .element_formatter(Box::new(|bar, _| {
-                            format!(
-                                "{}: {}",
-                                format_bucket_label(bar.argument.round()),
-                                bar.value
-                            )
-                        }))
+                        .element_formatter({
+                            let binning = buckets_statistic.binning.clone();
+                            Box::new(move |bar, _| {
+                                format!(
+                                    "{}: {}",
+                                    bucket_label(&binning, bar.argument.round()),
+                                    bar.value
+                                )
+                            })
+                        })
                     } else {
                         BarChart::new(
                             buckets_statistic.name,
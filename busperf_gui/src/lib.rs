@@ -1,3 +1,4 @@
+mod block_diagram;
 pub mod egui_visualization;
 mod surfer_egui;
 
@@ -9,7 +10,10 @@ pub fn run_egui(
     usages: Vec<libbusperf::bus_usage::BusData>,
     trace_path: String,
     hash: Option<String>,
-    time_unit: egui_visualization::TimescaleUnit,
+    timescale: libbusperf::Timescale,
+    updates: Option<
+        std::sync::mpsc::Receiver<(Vec<libbusperf::bus_usage::BusData>, libbusperf::Timescale)>,
+    >,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let options = eframe::NativeOptions::default();
     let surfer_data = surfer_egui::SurferData::new(trace_path, hash)?;
@@ -17,11 +21,11 @@ pub fn run_egui(
         "busperf",
         options,
         Box::new(|_| {
-            Ok(Box::new(egui_visualization::BusperfApp::new(
-                usages,
-                surfer_data,
-                time_unit,
-            )))
+            let app = egui_visualization::BusperfApp::new(usages, surfer_data, timescale.into());
+            Ok(Box::new(match updates {
+                Some(updates) => app.with_updates(updates),
+                None => app,
+            }))
         }),
     )
     .map_err(|e| format!("[Error] failed to run egui {}", e))?;
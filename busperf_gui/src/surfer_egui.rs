@@ -355,7 +355,7 @@ cfg_if! {
                             if ui.button("open in surfer").clicked() {
                                 let buckets_statistic = barcharts[id];
                                 let data = match scale {
-                                    PlotScale::Log => buckets_statistic.get_data_for_bucket(*selected),
+                                    PlotScale::Bins => buckets_statistic.get_data_for_bucket(*selected),
                                     PlotScale::Lin => buckets_statistic.get_data_of_value(*selected),
                                 };
                                 let signals = surfer.signals.clone();
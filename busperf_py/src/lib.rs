@@ -0,0 +1,360 @@
+//! Python bindings for Busperf.
+//!
+//! Builds the `busperf` Python extension module, which allows analyzing a trace directly from a
+//! Python script, e.g. at the end of a cocotb test:
+//!
+//! ```python
+//! import busperf
+//!
+//! trace = busperf.load_simulation_trace("dump.vcd")
+//! for analyzer in busperf.load_bus_analyzers("buses.yaml"):
+//!     analyzer.analyze(trace)
+//!     assert analyzer.results["Cycles"].values["Busy"] > 0
+//! ```
+
+use busperf::analyze::{self, SimulationData, analyzer::Analyzer};
+use libbusperf::{
+    bus_usage::{BusUsage, Statistic},
+    diagnostics::{Diagnostic, Diagnostics},
+};
+use pyo3::{
+    create_exception,
+    exceptions::{PyException, PyKeyError},
+    prelude::*,
+    types::PyDict,
+};
+
+create_exception!(busperf, BusperfError, PyException);
+
+fn to_py_err(e: Box<dyn std::error::Error>) -> PyErr {
+    BusperfError::new_err(e.to_string())
+}
+
+/// Simulation trace loaded into memory.
+#[pyclass(name = "SimulationTrace", unsendable)]
+struct PySimulationTrace {
+    data: SimulationData,
+}
+
+#[pymethods]
+impl PySimulationTrace {
+    /// Time of the last time step in the trace.
+    #[getter]
+    fn end_time(&self) -> u64 {
+        self.data.end_time()
+    }
+}
+
+/// Analyzer of a single bus, in a single phase if phases are defined.
+#[pyclass(name = "Analyzer", unsendable)]
+struct PyAnalyzer {
+    analyzer: Box<dyn Analyzer>,
+}
+
+#[pymethods]
+impl PyAnalyzer {
+    #[getter]
+    fn name(&self) -> &str {
+        self.analyzer.name()
+    }
+
+    #[getter]
+    fn phase(&self) -> Option<&str> {
+        self.analyzer.phase()
+    }
+
+    #[getter]
+    fn finished(&self) -> bool {
+        self.analyzer.finished_analysis()
+    }
+
+    /// Analyzes the bus in `trace`.
+    #[pyo3(signature = (trace, verbose = false))]
+    fn analyze(&mut self, trace: &mut PySimulationTrace, verbose: bool) -> PyResult<()> {
+        self.analyzer
+            .analyze(&mut trace.data, verbose)
+            .map_err(to_py_err)
+    }
+
+    /// Statistics of the bus, `None` if it was not analyzed yet.
+    #[getter]
+    fn results(&self, py: Python<'_>) -> PyResult<Option<BusStatistics>> {
+        self.analyzer
+            .get_results()
+            .map(|usage| {
+                BusStatistics::new(
+                    py,
+                    usage,
+                    self.analyzer.phase(),
+                    self.analyzer.diagnostics(),
+                )
+            })
+            .transpose()
+    }
+
+    fn __repr__(&self) -> String {
+        match self.analyzer.phase() {
+            Some(phase) => format!("Analyzer({} [{phase}])", self.analyzer.name()),
+            None => format!("Analyzer({})", self.analyzer.name()),
+        }
+    }
+}
+
+/// Results of the analysis of a bus.
+#[pyclass(frozen)]
+struct BusStatistics {
+    #[pyo3(get)]
+    name: String,
+    #[pyo3(get)]
+    phase: Option<String>,
+    /// `single_channel` or `multi_channel`
+    #[pyo3(get)]
+    kind: &'static str,
+    statistics: Vec<Py<PyStatistic>>,
+    /// Problems found during the analysis
+    #[pyo3(get)]
+    diagnostics: Vec<PyDiagnostic>,
+}
+
+impl BusStatistics {
+    fn new(
+        py: Python<'_>,
+        usage: &BusUsage,
+        phase: Option<&str>,
+        diagnostics: &Diagnostics,
+    ) -> PyResult<Self> {
+        let statistics = usage
+            .get_statistics(&[])
+            .iter()
+            .map(|s| Py::new(py, PyStatistic::from(s)))
+            .collect::<PyResult<_>>()?;
+        Ok(BusStatistics {
+            name: usage.get_name().to_owned(),
+            phase: phase.map(|p| p.to_owned()),
+            kind: match usage {
+                BusUsage::SingleChannel(_) => "single_channel",
+                BusUsage::MultiChannel(_) => "multi_channel",
+            },
+            statistics,
+            diagnostics: diagnostics.iter().map(PyDiagnostic::from).collect(),
+        })
+    }
+}
+
+#[pymethods]
+impl BusStatistics {
+    #[getter]
+    fn statistics(&self, py: Python<'_>) -> Vec<Py<PyStatistic>> {
+        self.statistics.iter().map(|s| s.clone_ref(py)).collect()
+    }
+
+    /// Returns statistic with given name, e.g. `Cycles` or `Bandwidth [t/clk]`.
+    fn __getitem__(&self, py: Python<'_>, name: &str) -> PyResult<Py<PyStatistic>> {
+        self.statistics
+            .iter()
+            .find(|s| s.get().name == name)
+            .map(|s| s.clone_ref(py))
+            .ok_or_else(|| PyKeyError::new_err(name.to_owned()))
+    }
+
+    fn __repr__(&self) -> String {
+        match &self.phase {
+            Some(phase) => format!("BusStatistics({} [{phase}])", self.name),
+            None => format!("BusStatistics({})", self.name),
+        }
+    }
+}
+
+/// Problem found during the analysis of a bus, e.g. a transaction that did not finish.
+#[pyclass(name = "Diagnostic", frozen)]
+#[derive(Clone)]
+struct PyDiagnostic {
+    /// `WARN` or `ERROR`
+    #[pyo3(get)]
+    severity: String,
+    #[pyo3(get)]
+    kind: String,
+    /// Description of the first occurrence
+    #[pyo3(get)]
+    message: String,
+    /// Waveform time of the first occurrence
+    #[pyo3(get)]
+    time: u64,
+    /// Number of occurrences
+    #[pyo3(get)]
+    count: u32,
+}
+
+impl From<&Diagnostic> for PyDiagnostic {
+    fn from(d: &Diagnostic) -> Self {
+        PyDiagnostic {
+            severity: d.severity.to_string(),
+            kind: d.kind.clone(),
+            message: d.message.clone(),
+            time: d.time,
+            count: d.count,
+        }
+    }
+}
+
+#[pymethods]
+impl PyDiagnostic {
+    fn __repr__(&self) -> String {
+        format!(
+            "Diagnostic([{}] {}: {}, {} times)",
+            self.severity, self.kind, self.message, self.count
+        )
+    }
+}
+
+enum Values {
+    Percentage(Vec<(&'static str, f32)>),
+    // Start, end and duration in clock cycles of each period
+    Buckets(Vec<(u64, u64, i32)>),
+    Timeline(Vec<(f64, f64)>),
+}
+
+/// Single statistic of a bus.
+#[pyclass(name = "Statistic", frozen)]
+struct PyStatistic {
+    #[pyo3(get)]
+    name: &'static str,
+    #[pyo3(get)]
+    description: &'static str,
+    /// Short text summary, as printed by `busperf analyze`
+    #[pyo3(get)]
+    summary: String,
+    values: Values,
+}
+
+impl From<&Statistic<'_>> for PyStatistic {
+    fn from(statistic: &Statistic) -> Self {
+        let (description, values) = match statistic {
+            Statistic::Percentage(s) => (
+                s.description,
+                Values::Percentage(s.data_labels.iter().map(|(v, l)| (*l, *v)).collect()),
+            ),
+            Statistic::Bucket(s) => (
+                s.description,
+                Values::Buckets(
+                    s.data
+                        .iter()
+                        .map(|p| (p.start(), p.end(), p.duration()))
+                        .collect(),
+                ),
+            ),
+            Statistic::Timeline(s) => (
+                s.description,
+                Values::Timeline(s.values.iter().map(|[x, y]| (*x, *y)).collect()),
+            ),
+        };
+        PyStatistic {
+            name: statistic.name(),
+            description,
+            summary: statistic.display(),
+            values,
+        }
+    }
+}
+
+#[pymethods]
+impl PyStatistic {
+    /// `percentage`, `buckets` or `timeline`
+    #[getter]
+    fn kind(&self) -> &'static str {
+        match self.values {
+            Values::Percentage(_) => "percentage",
+            Values::Buckets(_) => "buckets",
+            Values::Timeline(_) => "timeline",
+        }
+    }
+
+    /// Values of the statistic: a dict of values by label for percentage statistics, durations of
+    /// periods in clock cycles for bucket statistics and (time, value) points for timelines.
+    #[getter]
+    fn values<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        Ok(match &self.values {
+            Values::Percentage(values) => {
+                let dict = PyDict::new(py);
+                for (label, value) in values {
+                    dict.set_item(label, value)?;
+                }
+                dict.into_any()
+            }
+            Values::Buckets(periods) => periods
+                .iter()
+                .map(|(_, _, duration)| *duration)
+                .collect::<Vec<_>>()
+                .into_pyobject(py)?,
+            Values::Timeline(points) => points.into_pyobject(py)?,
+        })
+    }
+
+    /// (start, end, duration) of each period of a bucket statistic, empty for other statistics.
+    #[getter]
+    fn periods(&self) -> Vec<(u64, u64, i32)> {
+        match &self.values {
+            Values::Buckets(periods) => periods.clone(),
+            _ => vec![],
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Statistic({}: {})", self.name, self.summary)
+    }
+}
+
+/// Loads VCD, FST or GHW trace, possibly gzip compressed.
+#[pyfunction]
+#[pyo3(signature = (filename, verbose = false))]
+fn load_simulation_trace(filename: &str, verbose: bool) -> PyResult<PySimulationTrace> {
+    let data = analyze::load_simulation_trace(filename, verbose).map_err(to_py_err)?;
+    Ok(PySimulationTrace { data })
+}
+
+/// Loads analyzers of buses described in a yaml file.
+#[pyfunction]
+#[pyo3(signature = (
+    filename,
+    max_burst_delay = 0,
+    window_length = 10000,
+    x_rate = 0.0001,
+    y_rate = 0.00001,
+    plugins_path = "./plugins/python"
+))]
+fn load_bus_analyzers(
+    filename: &str,
+    max_burst_delay: i32,
+    window_length: u32,
+    x_rate: f32,
+    y_rate: f32,
+    plugins_path: &str,
+) -> PyResult<Vec<PyAnalyzer>> {
+    let analyzers = analyze::load_bus_analyzers(
+        filename,
+        max_burst_delay,
+        window_length,
+        x_rate,
+        y_rate,
+        plugins_path,
+    )
+    .map_err(to_py_err)?;
+    Ok(analyzers
+        .into_iter()
+        .map(|analyzer| PyAnalyzer { analyzer })
+        .collect())
+}
+
+#[pymodule]
+#[pyo3(name = "busperf")]
+fn busperf_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("BusperfError", m.py().get_type::<BusperfError>())?;
+    m.add_class::<PySimulationTrace>()?;
+    m.add_class::<PyAnalyzer>()?;
+    m.add_class::<BusStatistics>()?;
+    m.add_class::<PyStatistic>()?;
+    m.add_class::<PyDiagnostic>()?;
+    m.add_function(wrap_pyfunction!(load_simulation_trace, m)?)?;
+    m.add_function(wrap_pyfunction!(load_bus_analyzers, m)?)?;
+    Ok(())
+}
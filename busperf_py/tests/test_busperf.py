@@ -0,0 +1,61 @@
+import unittest
+from pathlib import Path
+
+import busperf
+
+DUMPS = Path(__file__).parents[2] / "tests" / "test_dumps"
+PLUGINS = Path(__file__).parents[2] / "tests" / "dummy_plugins"
+
+
+def analyze(trace, yaml, **kwargs):
+    trace = busperf.load_simulation_trace(str(DUMPS / trace))
+    analyzers = busperf.load_bus_analyzers(str(DUMPS / yaml), **kwargs)
+    for analyzer in analyzers:
+        analyzer.analyze(trace)
+    return [analyzer.results for analyzer in analyzers]
+
+
+class BusperfTest(unittest.TestCase):
+    def test_single_channel(self):
+        a, b = analyze("dump.vcd", "dump.yaml")
+        self.assertEqual((a.name, b.name), ("a_", "b_"))
+        self.assertEqual(a.kind, "single_channel")
+        cycles = a["Cycles"].values
+        self.assertEqual(cycles["No data"], 15)
+        self.assertEqual(cycles["Reset"], 15)
+        self.assertEqual(a["Transaction delays"].values, [30])
+        self.assertEqual(a["Transaction delays"].periods, [(0, 58000, 30)])
+        self.assertEqual(a["Burst lengths"].values, [])
+
+    def test_multi_channel(self):
+        results = analyze("axi.vcd", "../taxi_descriptions/axi_ram.yaml")
+        bandwidth = results[0]["Bandwidth [t/clk]"]
+        self.assertEqual(results[0].kind, "multi_channel")
+        self.assertEqual(bandwidth.kind, "timeline")
+        self.assertGreater(float(bandwidth.summary), 0)
+
+    def test_python_plugin(self):
+        a, _ = analyze("dump.vcd", "python_dump.yaml", plugins_path=str(PLUGINS))
+        self.assertEqual(a["Cycles"].values["No data"], 15)
+
+    def test_diagnostics(self):
+        (result,) = analyze("credit_valid.vcd", "credit_valid.yaml")
+        (diagnostic,) = result.diagnostics
+        self.assertEqual(diagnostic.severity, "WARN")
+        self.assertEqual(diagnostic.kind, "valid_without_credit")
+        self.assertEqual((diagnostic.time, diagnostic.count), (28, 5))
+
+    def test_not_analyzed(self):
+        analyzers = busperf.load_bus_analyzers(str(DUMPS / "dump.yaml"))
+        self.assertFalse(analyzers[0].finished)
+        self.assertIsNone(analyzers[0].results)
+
+    def test_errors(self):
+        with self.assertRaises(busperf.BusperfError):
+            busperf.load_simulation_trace(str(DUMPS / "missing.vcd"))
+        with self.assertRaises(KeyError):
+            analyze("dump.vcd", "dump.yaml")[0]["Bandwidth"]
+
+
+if __name__ == "__main__":
+    unittest.main()
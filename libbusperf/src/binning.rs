@@ -0,0 +1,198 @@
+//! Schemes of grouping durations of periods into histogram bins.
+
+use std::str::FromStr;
+
+use crate::CyclesNum;
+
+/// How durations of a [BucketsStatistic](crate::bus_usage::BucketsStatistic) are grouped into
+/// bins.
+#[derive(PartialEq, Debug, Clone, Default, bincode::Encode, bincode::Decode)]
+pub enum Binning {
+    /// Bins of logarithmic size: 0, 1, 2-3, 4-7 and so on.
+    #[default]
+    Log2,
+    /// Bins of given width in clock cycles, starting at 0.
+    Linear(CyclesNum),
+    /// Bins between strictly increasing edges. Durations below the first edge and from the last
+    /// edge up are counted in two open-ended bins.
+    Edges(Vec<CyclesNum>),
+}
+
+impl Binning {
+    /// Returns bins between `edges`, which have to be strictly increasing.
+    pub fn edges(edges: Vec<CyclesNum>) -> Result<Binning, String> {
+        if edges.is_empty() {
+            Err("at least one bin edge is required")?
+        }
+        if edges.windows(2).any(|w| w[0] >= w[1]) {
+            Err("bin edges should be strictly increasing")?
+        }
+        Ok(Binning::Edges(edges))
+    }
+
+    /// Returns number of the bin in which `duration` is counted.
+    pub fn bin(&self, duration: CyclesNum) -> i32 {
+        match self {
+            Binning::Log2 => match duration {
+                0 => 0,
+                v if v > 0 => (v.ilog2() + 1) as i32,
+                v => -(v.unsigned_abs().ilog2() as i32 + 1),
+            },
+            Binning::Linear(width) => duration.div_euclid(*width),
+            Binning::Edges(edges) => edges.partition_point(|e| *e <= duration) as i32,
+        }
+    }
+
+    /// Returns the smallest and the largest duration counted in the bin.
+    pub fn bounds(&self, bin: i32) -> (i64, i64) {
+        match self {
+            Binning::Log2 => match bin {
+                0 => (0, 0),
+                b if b > 0 => (1 << (b - 1), (1 << b) - 1),
+                b => (-((1 << -b) - 1), -(1 << (-b - 1))),
+            },
+            Binning::Linear(width) => {
+                let start = bin as i64 * *width as i64;
+                (start, start + *width as i64 - 1)
+            }
+            Binning::Edges(edges) => {
+                let bin = bin.clamp(0, edges.len() as i32) as usize;
+                let min = match bin {
+                    0 => CyclesNum::MIN as i64,
+                    b => edges[b - 1] as i64,
+                };
+                let max = match edges.get(bin) {
+                    Some(e) => *e as i64 - 1,
+                    None => CyclesNum::MAX as i64,
+                };
+                (min, max)
+            }
+        }
+    }
+
+    /// Returns range of durations in the bin, e.g. `4-7`, `2-4k` or `40+`.
+    pub fn label(&self, bin: i32) -> String {
+        match self {
+            Binning::Log2 => {
+                let i = bin;
+                if i < 2 {
+                    format!("{i}")
+                } else if i >= 41 {
+                    format!("2^{i}+")
+                } else if i >= 21 {
+                    let i = i as u32 - 20;
+                    format!("{}-{}M", 1 << (i - 1), 1 << i)
+                } else if i >= 11 {
+                    let i = i as u32 - 10;
+                    format!("{}-{}k", 1 << (i - 1), 1 << i)
+                } else {
+                    format!("{}-{}", 1 << (i as u64 - 1), (1 << i as u64) - 1)
+                }
+            }
+            Binning::Linear(_) => match self.bounds(bin) {
+                (min, max) if min == max => format!("{min}"),
+                (min, max) => format!("{min}-{max}"),
+            },
+            Binning::Edges(edges) => match self.bounds(bin) {
+                _ if edges.is_empty() => String::from("all"),
+                (_, max) if bin <= 0 => format!("<{}", max + 1),
+                (min, _) if bin as usize >= edges.len() => format!("{min}+"),
+                (min, max) if min == max => format!("{min}"),
+                (min, max) => format!("{min}-{max}"),
+            },
+        }
+    }
+}
+
+impl FromStr for Binning {
+    type Err = String;
+
+    /// Parses `log2`, `linear:WIDTH` or `edges:EDGE,EDGE,...`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (scheme, args) = s.split_once(':').unwrap_or((s, ""));
+        match scheme.trim() {
+            "log2" if args.is_empty() => Ok(Binning::Log2),
+            "linear" => match args.trim().parse::<CyclesNum>() {
+                Ok(width) if width > 0 => Ok(Binning::Linear(width)),
+                _ => Err(format!("invalid width of linear bins: `{args}`")),
+            },
+            "edges" => args
+                .split(',')
+                .map(|e| {
+                    e.trim()
+                        .parse::<CyclesNum>()
+                        .map_err(|_| format!("invalid bin edge: `{e}`"))
+                })
+                .collect::<Result<Vec<_>, _>>()
+                .and_then(Binning::edges),
+            _ => Err(format!(
+                "unknown binning `{s}`, expected log2, linear:WIDTH or edges:EDGE,EDGE,..."
+            )),
+        }
+    }
+}
+
+/// Binning of statistics of a bus: the default one and binning of chosen statistics.
+///
+/// Statistics are identified by keys returned by [statistic_key], e.g. `cmd_to_completion`.
+#[derive(PartialEq, Debug, Clone, Default, bincode::Encode, bincode::Decode)]
+pub struct Bins {
+    default: Option<Binning>,
+    statistics: Vec<(String, Binning)>,
+}
+
+impl Bins {
+    /// Sets binning of statistics which have none set.
+    pub fn set_default(&mut self, binning: Binning) {
+        self.default = Some(binning);
+    }
+
+    /// Sets binning of the statistic with given name or key.
+    pub fn set(&mut self, statistic: &str, binning: Binning) {
+        let key = statistic_key(statistic);
+        self.statistics.retain(|(k, _)| *k != key);
+        self.statistics.push((key, binning));
+    }
+
+    /// Returns binning of the statistic with given name.
+    pub fn get(&self, statistic: &str) -> Binning {
+        let key = statistic_key(statistic);
+        self.statistics
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map(|(_, b)| b)
+            .or(self.default.as_ref())
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Uses binning from `defaults` for everything that is not set.
+    pub fn with_defaults(mut self, defaults: &Bins) -> Bins {
+        if self.default.is_none() {
+            self.default = defaults.default.clone();
+        }
+        for (key, binning) in defaults.statistics.iter() {
+            if !self.statistics.iter().any(|(k, _)| k == key) {
+                self.statistics.push((key.clone(), binning.clone()));
+            }
+        }
+        self
+    }
+
+    /// Parses binning set with `KEY=BINNING` for a statistic or `BINNING` for all of them, see
+    /// [Binning::from_str].
+    pub fn parse_setting(&mut self, setting: &str) -> Result<(), String> {
+        match setting.split_once('=') {
+            Some((key, binning)) => self.set(key.trim(), binning.parse()?),
+            None => self.set_default(setting.parse()?),
+        }
+        Ok(())
+    }
+}
+
+/// Returns identifier of a statistic: lowercase name without unit and with `_` instead of
+/// spaces, e.g. `bandwidth` for `Bandwidth [t/clk]`.
+pub fn statistic_key(name: &str) -> String {
+    let name = name.split('[').next().unwrap_or_default();
+    name.trim().to_lowercase().replace(' ', "_")
+}
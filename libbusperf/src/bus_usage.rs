@@ -1,16 +1,68 @@
 use crate::CycleType;
+use crate::binning::{Binning, Bins};
+use crate::diagnostics::Diagnostics;
 use crate::{CyclesNum, SignalPath};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+pub(crate) mod legacy;
 
 #[derive(bincode::Encode, bincode::Decode)]
 pub struct BusData {
     pub usage: BusUsage,
     pub signals: Vec<SignalPath>,
+    /// Phase of the trace to which the analysis was limited.
+    pub phase: Option<String>,
+    pub diagnostics: Diagnostics,
+    /// Binning of bucket statistics.
+    pub bins: Bins,
+    /// Names of buses to which this bus passes data, e.g. the next stage of a pipeline.
+    pub feeds: Vec<String>,
+    /// Scope of the module in which the bus is described, e.g. `["top", "dma"]`.
+    pub scope: Vec<String>,
 }
 
 impl BusData {
+    /// Creates data of a bus analyzed in the whole trace, with no diagnostics, default binning and
+    /// no chained buses. Its scope is the scope shared by all `signals`. Other fields can be set
+    /// directly.
     pub fn new(usage: BusUsage, signals: Vec<SignalPath>) -> Self {
-        Self { usage, signals }
+        let mut scope = signals.first().map(|s| s.scope.clone()).unwrap_or_default();
+        for signal in signals.iter() {
+            let common = scope
+                .iter()
+                .zip(signal.scope.iter())
+                .take_while(|(a, b)| a == b)
+                .count();
+            scope.truncate(common);
+        }
+        Self {
+            usage,
+            signals,
+            phase: None,
+            diagnostics: Diagnostics::default(),
+            bins: Bins::default(),
+            feeds: vec![],
+            scope,
+        }
+    }
+
+    /// Returns statistics of the bus with bucket statistics binned according to [BusData::bins].
+    pub fn get_statistics(&self, skipped_stats: &[String]) -> Vec<Statistic<'_>> {
+        let mut statistics = self.usage.get_statistics(skipped_stats);
+        for statistic in statistics.iter_mut() {
+            if let Statistic::Bucket(s) = statistic {
+                s.binning = self.bins.get(s.name);
+            }
+        }
+        statistics
+    }
+
+    /// Returns bus name followed by the phase, if there is one.
+    pub fn display_name(&self) -> String {
+        match &self.phase {
+            Some(phase) => format!("{} [{phase}]", self.usage.get_name()),
+            None => self.usage.get_name().to_owned(),
+        }
     }
 }
 
@@ -38,6 +90,42 @@ impl BusUsage {
             }
         }
     }
+
+    /// Combines statistics of the same bus from several simulations into one.
+    ///
+    /// Cycles are summed and periods of all usages are kept. Name and parameters of the analysis
+    /// are taken from the first usage.
+    pub fn merge(usages: &[&BusUsage]) -> Result<BusUsage, String> {
+        let name = usages.first().ok_or("nothing to merge")?.get_name();
+        if let Some(u) = usages.iter().find(|u| u.get_name() != name) {
+            Err(format!("cannot merge buses {name} and {}", u.get_name()))?
+        }
+        let single = usages
+            .iter()
+            .filter_map(|u| match u {
+                BusUsage::SingleChannel(u) => Some(u),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let multi = usages
+            .iter()
+            .filter_map(|u| match u {
+                BusUsage::MultiChannel(u) => Some(u),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        match (&single[..], &multi[..]) {
+            ([first, rest @ ..], []) => {
+                let mut merged = (*first).clone();
+                rest.iter().for_each(|u| merged.merge(u));
+                Ok(BusUsage::SingleChannel(merged))
+            }
+            ([], [_, ..]) => Ok(BusUsage::MultiChannel(MultiChannelBusUsage::merge(&multi))),
+            _ => Err(format!(
+                "cannot merge single and multi channel statistics of bus {name}"
+            )),
+        }
+    }
 }
 
 /// Enum that contains all statistic types.
@@ -55,6 +143,15 @@ impl<'a> Statistic<'a> {
             Statistic::Timeline(timeline_statistic) => timeline_statistic.name,
         }
     }
+
+    /// Returns short text summary of the statistic.
+    pub fn display(&self) -> String {
+        match self {
+            Statistic::Percentage(percentage_statistic) => percentage_statistic.display(),
+            Statistic::Bucket(buckets_statistic) => buckets_statistic.display(),
+            Statistic::Timeline(timeline_statistic) => timeline_statistic.display.clone(),
+        }
+    }
 }
 
 /// Statistic that compares given values based on their proportions.
@@ -91,6 +188,8 @@ pub struct TimelineStatistic {
     pub name: &'static str,
     pub values: Vec<[f64; 2]>,
     pub vertical_lines: Vec<f64>,
+    /// Value summarizing the whole timeline, e.g. average bandwidth.
+    pub value: f64,
     pub display: String,
     pub description: &'static str,
 }
@@ -129,6 +228,40 @@ pub struct SingleChannelBusUsage {
 
     max_burst_delay: CyclesNum,
     clk_period: RealTime,
+    window_length: u32,
+    windows: Vec<CycleCounts>,
+    // Recorded only if enabled with [SingleChannelBusUsage::record_runs]
+    runs: Option<Vec<CycleRun>>,
+}
+
+/// Consecutive clock cycles of a single channel bus in the same state, from `start` up to, but not
+/// including, `end`. Times are in waveform time.
+#[derive(PartialEq, Debug, Clone, bincode::Encode, bincode::Decode)]
+pub struct CycleRun {
+    pub state: CycleType,
+    pub start: RealTime,
+    pub end: RealTime,
+}
+
+/// Numbers of clock cycles of each kind in half of a rolling window, see
+/// [SingleChannelBusUsage::windows].
+#[derive(PartialEq, Debug, Clone, Default, bincode::Encode, bincode::Decode)]
+pub struct CycleCounts {
+    /// Time of the first cycle.
+    pub start: RealTime,
+    pub cycles: CyclesNum,
+    pub busy: CyclesNum,
+    pub backpressure: CyclesNum,
+    pub reset: CyclesNum,
+}
+
+impl CycleCounts {
+    fn add(&mut self, other: &CycleCounts) {
+        self.cycles += other.cycles;
+        self.busy += other.busy;
+        self.backpressure += other.backpressure;
+        self.reset += other.reset;
+    }
 }
 
 impl SingleChannelBusUsage {
@@ -141,6 +274,7 @@ impl SingleChannelBusUsage {
                 clk_to_time: self.clk_period,
                 color: "Red",
                 description: "Delays between transaction in clock cycles",
+                binning: Binning::Log2,
             }),
             Statistic::Bucket(BucketsStatistic {
                 name: "Burst lengths",
@@ -148,9 +282,62 @@ impl SingleChannelBusUsage {
                 clk_to_time: self.clk_period,
                 color: "Blue",
                 description: "Burst lengths in clock cycles",
+                binning: Binning::Log2,
             }),
+            self.timeline(
+                "Utilization [%]",
+                "Percentage of clock cycles outside reset with a transfer or backpressure, in rolling windows.",
+                |c| (c.busy + c.backpressure) as f64 * 100.0,
+            ),
+            self.timeline(
+                "Backpressure rate [%]",
+                "Percentage of clock cycles outside reset with backpressure, in rolling windows.",
+                |c| c.backpressure as f64 * 100.0,
+            ),
+            self.timeline(
+                "Throughput [t/clk]",
+                "Transfers per clock cycle outside reset, in rolling windows.",
+                |c| c.busy as f64,
+            ),
         ])
     }
+    // Timeline of `value` per clock cycle outside reset in windows of window_length cycles, moved
+    // by half of the window like the bandwidth of multichannel buses
+    fn timeline(
+        &self,
+        name: &'static str,
+        description: &'static str,
+        value: impl Fn(&CycleCounts) -> f64,
+    ) -> Statistic<'_> {
+        let rate = |c: &CycleCounts| match c.cycles - c.reset {
+            0 => 0.0,
+            cycles => value(c) / cycles as f64,
+        };
+        let values = (0..self.windows.len())
+            .map(|i| {
+                let mut window = self.windows[i].clone();
+                if let Some(next) = self.windows.get(i + 1) {
+                    window.add(next);
+                }
+                [window.start as f64, rate(&window)]
+            })
+            .collect();
+        let mut total = CycleCounts::default();
+        self.windows.iter().for_each(|w| total.add(w));
+        let total = rate(&total);
+        Statistic::Timeline(TimelineStatistic {
+            name,
+            values,
+            vertical_lines: vec![],
+            value: total,
+            display: if name.ends_with("[%]") {
+                format!("{total:.2}")
+            } else {
+                format!("{total:.4}")
+            },
+            description,
+        })
+    }
     fn get_cycles(&self) -> PercentageStatistic {
         PercentageStatistic {
             data_labels: vec![
@@ -167,7 +354,15 @@ impl SingleChannelBusUsage {
     }
     /// Creates SingleChannelBusUsage with all statistics initialized to 0.
     /// To fill it with data use add_cycle() method for every cycle in the simulation. Later call end() to finish calculations.
-    pub fn new(name: &str, max_burst_delay: CyclesNum, clk_to_time: u64) -> SingleChannelBusUsage {
+    ///
+    /// Utilization, backpressure and throughput timelines are averaged over windows of
+    /// `window_length` clock cycles.
+    pub fn new(
+        name: &str,
+        max_burst_delay: CyclesNum,
+        clk_to_time: u64,
+        window_length: u32,
+    ) -> SingleChannelBusUsage {
         SingleChannelBusUsage {
             bus_name: name.to_owned(),
             busy: 0,
@@ -181,11 +376,46 @@ impl SingleChannelBusUsage {
             current: CurrentlyCalculating::None,
             max_burst_delay,
             clk_period: clk_to_time,
+            window_length,
+            windows: vec![],
+            runs: None,
         }
     }
 
-    /// Updates statistics by adding a cycle of given type
-    pub fn add_cycle(&mut self, t: CycleType) {
+    /// Enables recording of states of the bus, see [SingleChannelBusUsage::runs]. Should be
+    /// called before any cycle is added.
+    pub fn record_runs(&mut self) {
+        self.runs.get_or_insert_default();
+    }
+
+    /// Updates statistics by adding a cycle of given type, which started at `time`
+    pub fn add_cycle(&mut self, time: RealTime, t: CycleType) {
+        let half_window = (self.window_length as CyclesNum / 2).max(1);
+        match self.windows.last_mut() {
+            Some(window) if window.cycles < half_window => window.cycles += 1,
+            _ => self.windows.push(CycleCounts {
+                start: time,
+                cycles: 1,
+                ..Default::default()
+            }),
+        }
+        let window = self.windows.last_mut().expect("Should have at least one");
+        match t {
+            CycleType::Busy => window.busy += 1,
+            CycleType::Backpressure => window.backpressure += 1,
+            CycleType::Reset => window.reset += 1,
+            _ => (),
+        }
+        if let Some(runs) = &mut self.runs {
+            match runs.last_mut() {
+                Some(run) if run.state == t => run.end = time + self.clk_period,
+                _ => runs.push(CycleRun {
+                    state: t,
+                    start: time,
+                    end: time + self.clk_period,
+                }),
+            }
+        }
         if let CycleType::Busy = t {
             self.add_busy_cycle();
         } else {
@@ -288,6 +518,60 @@ impl SingleChannelBusUsage {
         }
     }
 
+    /// Adds cycles and periods of another simulation of the same bus.
+    pub fn merge(&mut self, other: &SingleChannelBusUsage) {
+        self.busy += other.busy;
+        self.backpressure += other.backpressure;
+        self.no_data += other.no_data;
+        self.no_transaction += other.no_transaction;
+        self.free += other.free;
+        self.reset += other.reset;
+        self.transaction_delays
+            .extend_from_slice(&other.transaction_delays);
+        self.burst_lengths.extend_from_slice(&other.burst_lengths);
+        // Windows start at the same times in simulations with the same intervals
+        let mut windows: BTreeMap<RealTime, CycleCounts> = Default::default();
+        for window in self.windows.iter().chain(other.windows.iter()) {
+            windows
+                .entry(window.start)
+                .or_insert(CycleCounts {
+                    start: window.start,
+                    ..Default::default()
+                })
+                .add(window);
+        }
+        self.windows = windows.into_values().collect();
+        // States of separate simulations do not form a single timeline
+        self.runs = None;
+    }
+
+    /// Returns states of the bus in order of occurrence, with consecutive cycles in the same state
+    /// joined into a single run. Runs are recorded only after [SingleChannelBusUsage::record_runs]
+    /// and merged statistics of several simulations have none.
+    pub fn runs(&self) -> &[CycleRun] {
+        self.runs.as_deref().unwrap_or_default()
+    }
+
+    /// Returns duration of a clock cycle in waveform time.
+    pub fn clock_period(&self) -> RealTime {
+        self.clk_period
+    }
+
+    /// Returns numbers of cycles of each kind in consecutive halves of rolling windows.
+    pub fn windows(&self) -> &[CycleCounts] {
+        &self.windows
+    }
+
+    /// Returns delays between transactions, in order of occurrence.
+    pub fn transaction_delays(&self) -> &[Period] {
+        &self.transaction_delays
+    }
+
+    /// Returns bursts, in order of occurrence.
+    pub fn burst_lengths(&self) -> &[Period] {
+        &self.burst_lengths
+    }
+
     /// Creates SingleChannelBusUsage with given values - for tests purposes
     #[allow(clippy::too_many_arguments)]
     pub fn literal(
@@ -303,6 +587,9 @@ impl SingleChannelBusUsage {
         max_burst_delay: CyclesNum,
         current: CurrentlyCalculating,
         clk_to_time: u64,
+        window_length: u32,
+        windows: Vec<CycleCounts>,
+        runs: Option<Vec<CycleRun>>,
     ) -> SingleChannelBusUsage {
         SingleChannelBusUsage {
             bus_name: bus_name.to_owned(),
@@ -317,6 +604,9 @@ impl SingleChannelBusUsage {
             max_burst_delay,
             current,
             clk_period: clk_to_time,
+            window_length,
+            windows,
+            runs,
         }
     }
 }
@@ -330,6 +620,8 @@ pub struct BucketsStatistic<'a> {
     pub clk_to_time: u64,
     pub color: &'static str,
     pub description: &'static str,
+    /// Grouping of periods into buckets by their duration.
+    pub binning: Binning,
 }
 
 impl<'a> BucketsStatistic<'a> {
@@ -346,6 +638,7 @@ impl<'a> BucketsStatistic<'a> {
             clk_to_time,
             color,
             description,
+            binning: Binning::Log2,
         }
     }
     /// Returns counts of periods that are of each size
@@ -364,30 +657,21 @@ impl<'a> BucketsStatistic<'a> {
         }
         buckets
     }
-    /// Returns values of the buckets that are of logarythmic scale size
+    /// Returns numbers of periods in each bucket of [BucketsStatistic::binning].
     pub fn get_buckets(&self) -> HashMap<CyclesNum, usize> {
         let mut buckets = HashMap::new();
         for v in self.data.iter() {
-            let bucket = match v.duration {
-                0 => 0,
-                v if v > 0 => (v.ilog2() + 1) as i32,
-                v => -(v.abs().ilog2() as i32 + 1),
-            };
-            match buckets.get_mut(&bucket) {
-                Some(num) => *num += 1,
-                None => {
-                    buckets.insert(bucket, 1);
-                }
-            }
+            *buckets.entry(self.binning.bin(v.duration)).or_insert(0) += 1;
         }
         buckets
     }
-    fn bucket_num(cycle_num: CyclesNum) -> i32 {
-        match cycle_num {
-            0 => 0,
-            v if v > 0 => (v.ilog2() + 1) as i32,
-            v => -(v.abs().ilog2() as i32 + 1),
-        }
+    /// Returns the smallest and the largest duration in a bucket returned by [Self::get_buckets].
+    pub fn bucket_bounds(&self, bucket_num: i32) -> (i64, i64) {
+        self.binning.bounds(bucket_num)
+    }
+    /// Returns range of durations in a bucket returned by [Self::get_buckets], e.g. `4-7`.
+    pub fn bucket_label(&self, bucket_num: i32) -> String {
+        self.binning.label(bucket_num)
     }
     // Returns periods that have specified duration
     pub fn get_data_of_value(&self, value: CyclesNum) -> Vec<Period> {
@@ -401,14 +685,52 @@ impl<'a> BucketsStatistic<'a> {
     pub fn get_data_for_bucket(&self, bucket_num: i32) -> Vec<Period> {
         self.data
             .iter()
-            .filter(|d| {
-                let bucket = BucketsStatistic::bucket_num(d.duration);
-                bucket == bucket_num
-            })
+            .filter(|d| self.binning.bin(d.duration) == bucket_num)
             .copied()
             .collect()
     }
 
+    fn sorted_durations(&self) -> Vec<CyclesNum> {
+        let mut durations = self.data.iter().map(|d| d.duration).collect::<Vec<_>>();
+        durations.sort();
+        durations
+    }
+
+    /// Returns the `p`-th percentile of durations in clock cycles, using the nearest-rank
+    /// method, or [None] if there are no periods.
+    pub fn percentile(&self, p: f64) -> Option<CyclesNum> {
+        nearest_rank(&self.sorted_durations(), p)
+    }
+
+    /// Returns mean duration in clock cycles, or [None] if there are no periods.
+    pub fn mean(&self) -> Option<f64> {
+        if self.data.is_empty() {
+            return None;
+        }
+        Some(self.data.iter().map(|d| d.duration as f64).sum::<f64>() / self.data.len() as f64)
+    }
+
+    /// Returns summary of durations, or [None] if there are no periods.
+    pub fn summary(&self) -> Option<DurationSummary> {
+        let durations = self.sorted_durations();
+        let mean = self.mean()?;
+        let variance = durations
+            .iter()
+            .map(|&d| (d as f64 - mean).powi(2))
+            .sum::<f64>()
+            / durations.len() as f64;
+        Some(DurationSummary {
+            count: durations.len(),
+            min: durations[0],
+            max: durations[durations.len() - 1],
+            mean,
+            std_dev: variance.sqrt(),
+            p50: nearest_rank(&durations, 50.0)?,
+            p90: nearest_rank(&durations, 90.0)?,
+            p99: nearest_rank(&durations, 99.0)?,
+        })
+    }
+
     pub fn buckets_num(&self) -> u32 {
         self.get_buckets().len() as u32
     }
@@ -424,6 +746,39 @@ impl<'a> BucketsStatistic<'a> {
     }
 }
 
+// Smallest value such that at least `p` percent of `sorted` values are not greater than it
+fn nearest_rank(sorted: &[CyclesNum], p: f64) -> Option<CyclesNum> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = (p / 100.0 * sorted.len() as f64).ceil() as usize;
+    Some(sorted[rank.clamp(1, sorted.len()) - 1])
+}
+
+/// Exact statistics of durations of periods of a [BucketsStatistic] in clock cycles.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct DurationSummary {
+    pub count: usize,
+    pub min: CyclesNum,
+    pub max: CyclesNum,
+    pub mean: f64,
+    /// Population standard deviation.
+    pub std_dev: f64,
+    pub p50: CyclesNum,
+    pub p90: CyclesNum,
+    pub p99: CyclesNum,
+}
+
+impl std::fmt::Display for DurationSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "p50: {}, p90: {}, p99: {}, max: {}, mean: {:.2}, std dev: {:.2}",
+            self.p50, self.p90, self.p99, self.max, self.mean, self.std_dev
+        )
+    }
+}
+
 /// Waveform time.
 pub type RealTime = u64;
 type SignedRealTime = i64;
@@ -482,6 +837,25 @@ impl Period {
     }
 }
 
+/// Single transaction of a multichannel bus, as passed to [MultiChannelBusUsage::add_transaction].
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct Transaction<'a> {
+    /// Time of command issue.
+    pub start: RealTime,
+    /// Time of first data being transferred.
+    pub first_data: RealTime,
+    /// Time of last data transfer.
+    pub last_data: RealTime,
+    /// Time of response.
+    pub resp_time: RealTime,
+    /// Value of the response.
+    pub resp: &'a str,
+    /// Transaction ID, if the bus has one.
+    pub id: Option<&'a str>,
+    /// Start time of next transaction.
+    pub next: RealTime,
+}
+
 /// Contains statistics for a multichannel bus.
 #[derive(PartialEq, Debug, Clone, bincode::Encode, bincode::Decode)]
 pub struct MultiChannelBusUsage {
@@ -506,6 +880,11 @@ pub struct MultiChannelBusUsage {
     /// We have a statistic that calculates % of time that the bandwidth was BELOW this value
     y_rate: f64,
     intervals: Vec<[u64; 2]>,
+    // Distinct responses and IDs of transactions, with indices by which `tags` refer to them
+    labels: BTreeMap<String, u32>,
+    // Response and ID of each transaction, in order of `cmd_to_completion`. Times of transactions
+    // are those of its periods.
+    tags: Vec<(u32, Option<u32>)>,
 }
 
 impl MultiChannelBusUsage {
@@ -536,6 +915,8 @@ impl MultiChannelBusUsage {
             x_rate: x_rate as f64,
             y_rate: y_rate as f64,
             intervals: vec![],
+            labels: BTreeMap::new(),
+            tags: vec![],
         }
     }
 
@@ -575,6 +956,7 @@ impl MultiChannelBusUsage {
                 name: "Error rate [%]",
                 values: vec![],
                 vertical_lines: vec![], // TODO show times when error occured
+                value: self.error_rate as f64 * 100.0,
                 display: if self.error_rate.is_nan() {
                     "Invalid".to_string()
                 } else {
@@ -591,6 +973,7 @@ impl MultiChannelBusUsage {
                 .iter()
                 .flat_map(|&[a, b]| [a as f64, b as f64])
                 .collect(),
+            value: self.averaged_bandwidth as f64,
             display: format!("{:.4}", self.averaged_bandwidth),
             description: "Averaged bandwidth in transactions per clock cycle.",
         }));
@@ -604,6 +987,7 @@ impl MultiChannelBusUsage {
                 ],
             ],
             vertical_lines: vec![],
+            value: self.bandwidth_above_x_rate as f64 * 100.0,
             display: format!("{:.2}", self.bandwidth_above_x_rate * 100.0),
             description: "Percentage value of time during which bandwidth was higher than x rate.",
         }));
@@ -617,6 +1001,7 @@ impl MultiChannelBusUsage {
                 ],
             ],
             vertical_lines: vec![],
+            value: self.bandwidth_below_y_rate as f64 * 100.0,
             display: format!("{:.2}", self.bandwidth_below_y_rate * 100.0),
             description: "Percentage value of time during which bandwidth was smaller than y rate.",
         }));
@@ -624,6 +1009,7 @@ impl MultiChannelBusUsage {
     }
 
     /// Updates statistics given new transaction. When all transactions are added you should call end() to finish calculation of statistics.
+    #[allow(clippy::too_many_arguments)]
     pub fn add_transaction(
         &mut self,
         time: RealTime,
@@ -631,6 +1017,7 @@ impl MultiChannelBusUsage {
         last_write: RealTime,
         first_data: RealTime,
         resp: &str,
+        id: Option<&str>,
         next: RealTime,
     ) {
         self.cmd_to_completion
@@ -646,6 +1033,79 @@ impl MultiChannelBusUsage {
         }
         self.transaction_delays
             .push(Period::new(resp_time, next, self.clock_period));
+        let resp = self.label(resp);
+        let id = id.map(|id| self.label(id));
+        self.tags.push((resp, id));
+    }
+
+    // Returns index of the label, adding it if needed
+    fn label(&mut self, label: &str) -> u32 {
+        match self.labels.get(label) {
+            Some(&i) => i,
+            None => {
+                let i = self.labels.len() as u32;
+                self.labels.insert(label.to_owned(), i);
+                i
+            }
+        }
+    }
+
+    // Labels ordered by their indices
+    fn labels(&self) -> Vec<&str> {
+        let mut labels = vec![""; self.labels.len()];
+        for (label, &i) in self.labels.iter() {
+            if let Some(l) = labels.get_mut(i as usize) {
+                *l = label;
+            }
+        }
+        labels
+    }
+
+    // Whether every transaction has its periods and labels. Statistics saved by older versions of
+    // busperf have periods only.
+    fn has_transactions(&self) -> bool {
+        let num = self.tags.len();
+        [
+            &self.cmd_to_completion,
+            &self.cmd_to_first_data,
+            &self.last_data_to_completion,
+            &self.transaction_delays,
+        ]
+        .iter()
+        .all(|periods| periods.len() == num)
+            && self
+                .tags
+                .iter()
+                .flat_map(|&(resp, id)| [Some(resp), id])
+                .flatten()
+                .all(|label| (label as usize) < self.labels.len())
+    }
+
+    /// Returns all transactions, in order in which they were added. Statistics saved by older
+    /// versions of busperf, or whose transactions do not match their periods, have none.
+    pub fn transactions(&self) -> Vec<Transaction<'_>> {
+        if !self.has_transactions() {
+            return vec![];
+        }
+        let labels = self.labels();
+        self.tags
+            .iter()
+            .zip(&self.cmd_to_completion)
+            .zip(&self.cmd_to_first_data)
+            .zip(&self.last_data_to_completion)
+            .zip(&self.transaction_delays)
+            .map(
+                |((((&(resp, id), completion), first_data), last_data), delay)| Transaction {
+                    start: completion.start,
+                    first_data: first_data.end,
+                    last_data: last_data.start,
+                    resp_time: completion.end,
+                    resp: labels[resp as usize],
+                    id: id.map(|id| labels[id as usize]),
+                    next: delay.end,
+                },
+            )
+            .collect()
     }
 
     pub fn add_time(&mut self, time: RealTime) {
@@ -708,4 +1168,90 @@ impl MultiChannelBusUsage {
 
         self.intervals = intervals;
     }
+    /// Combines statistics of several simulations of the same bus.
+    ///
+    /// Averaged values are weighted by simulation time and the bandwidth timeline is averaged
+    /// over simulations covering each window. Simulations in which the bus was not analyzed at all
+    /// are given no weight. Boundaries of intervals of all simulations are kept. Transactions are
+    /// kept only if every usage has them.
+    pub fn merge(usages: &[&MultiChannelBusUsage]) -> MultiChannelBusUsage {
+        let mut merged = usages[0].clone();
+        let rest = &usages[1..];
+        for u in rest {
+            merged
+                .cmd_to_completion
+                .extend_from_slice(&u.cmd_to_completion);
+            merged
+                .cmd_to_first_data
+                .extend_from_slice(&u.cmd_to_first_data);
+            merged
+                .last_data_to_completion
+                .extend_from_slice(&u.last_data_to_completion);
+            merged
+                .transaction_delays
+                .extend_from_slice(&u.transaction_delays);
+            merged.errors.extend_from_slice(&u.errors);
+            for t in u.transactions() {
+                let resp = merged.label(t.resp);
+                let id = t.id.map(|id| merged.label(id));
+                merged.tags.push((resp, id));
+            }
+            merged.correct_num += u.correct_num;
+        }
+        // Transactions would not match their periods if any usage lacks them
+        if usages.iter().any(|u| !u.has_transactions()) {
+            merged.labels.clear();
+            merged.tags.clear();
+        }
+        let error_num = merged.errors.len() as f32;
+        merged.error_rate = error_num / (merged.correct_num as f32 + error_num);
+
+        // Averages of a bus with no time or windows are NaN, so such usages are skipped
+        let time: RealTime = usages.iter().map(|u| u.time).sum();
+        merged.time = time;
+        merged.averaged_bandwidth = if time == 0 {
+            0.0
+        } else {
+            usages
+                .iter()
+                .filter(|u| u.time > 0)
+                .map(|u| u.averaged_bandwidth as f64 * u.time as f64)
+                .sum::<f64>() as f32
+                / time as f32
+        };
+
+        let windows: usize = usages.iter().map(|u| u.bandwidth_windows.len()).sum();
+        let weighted = |rate: fn(&MultiChannelBusUsage) -> f32| {
+            if windows == 0 {
+                return 0.0;
+            }
+            usages
+                .iter()
+                .filter(|u| !u.bandwidth_windows.is_empty())
+                .map(|u| rate(u) * u.bandwidth_windows.len() as f32)
+                .sum::<f32>()
+                / windows as f32
+        };
+        merged.bandwidth_above_x_rate = weighted(|u| u.bandwidth_above_x_rate);
+        merged.bandwidth_below_y_rate = weighted(|u| u.bandwidth_below_y_rate);
+
+        // Windows start at the same times in simulations with the same intervals
+        let mut bandwidth: BTreeMap<u64, (f64, u32)> = Default::default();
+        for [start, value] in usages.iter().flat_map(|u| u.bandwidth_windows.iter()) {
+            let window = bandwidth.entry(*start as u64).or_default();
+            window.0 += value;
+            window.1 += 1;
+        }
+        merged.bandwidth_windows = bandwidth
+            .into_iter()
+            .map(|(start, (sum, num))| [start as f64, sum / num as f64])
+            .collect();
+        merged.intervals = usages
+            .iter()
+            .flat_map(|u| u.intervals.iter().copied())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        merged
+    }
 }
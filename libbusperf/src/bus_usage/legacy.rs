@@ -0,0 +1,123 @@
+//! Layout of statistics in files saved before the format had a version, see [crate::saved].
+
+use crate::{CyclesNum, SignalPath};
+
+use super::{CurrentlyCalculating, CycleCounts, Period, RealTime};
+
+#[derive(bincode::Decode)]
+pub(crate) struct BusData {
+    usage: BusUsage,
+    signals: Vec<SignalPath>,
+}
+
+#[derive(bincode::Decode)]
+enum BusUsage {
+    SingleChannel(SingleChannelBusUsage),
+    MultiChannel(MultiChannelBusUsage),
+}
+
+#[derive(bincode::Decode)]
+struct SingleChannelBusUsage {
+    bus_name: String,
+    busy: CyclesNum,
+    backpressure: CyclesNum,
+    no_data: CyclesNum,
+    no_transaction: CyclesNum,
+    free: CyclesNum,
+    reset: CyclesNum,
+    transaction_delays: Vec<Period>,
+    burst_lengths: Vec<Period>,
+    current: CurrentlyCalculating,
+    max_burst_delay: CyclesNum,
+    clk_period: RealTime,
+}
+
+#[derive(bincode::Decode)]
+struct MultiChannelBusUsage {
+    bus_name: String,
+    cmd_to_completion: Vec<Period>,
+    cmd_to_first_data: Vec<Period>,
+    last_data_to_completion: Vec<Period>,
+    transaction_delays: Vec<Period>,
+    error_rate: f32,
+    errors: Vec<RealTime>,
+    correct_num: u32,
+    averaged_bandwidth: f32,
+    bandwidth_windows: Vec<[f64; 2]>,
+    window_length: u32,
+    clock_period: RealTime,
+    bandwidth_above_x_rate: f32,
+    bandwidth_below_y_rate: f32,
+    time: RealTime,
+    x_rate: f64,
+    y_rate: f64,
+    intervals: Vec<[u64; 2]>,
+}
+
+impl From<SingleChannelBusUsage> for super::SingleChannelBusUsage {
+    fn from(u: SingleChannelBusUsage) -> Self {
+        let cycles = u.busy + u.backpressure + u.no_data + u.no_transaction + u.free + u.reset;
+        // Timelines were not saved, the whole trace is a single window
+        let window = CycleCounts {
+            start: 0,
+            cycles,
+            busy: u.busy,
+            backpressure: u.backpressure,
+            reset: u.reset,
+        };
+        super::SingleChannelBusUsage {
+            bus_name: u.bus_name,
+            busy: u.busy,
+            backpressure: u.backpressure,
+            no_data: u.no_data,
+            no_transaction: u.no_transaction,
+            free: u.free,
+            reset: u.reset,
+            transaction_delays: u.transaction_delays,
+            burst_lengths: u.burst_lengths,
+            current: u.current,
+            max_burst_delay: u.max_burst_delay,
+            clk_period: u.clk_period,
+            window_length: cycles.max(1) as u32,
+            windows: vec![window],
+            runs: None,
+        }
+    }
+}
+
+impl From<MultiChannelBusUsage> for super::MultiChannelBusUsage {
+    fn from(u: MultiChannelBusUsage) -> Self {
+        super::MultiChannelBusUsage {
+            bus_name: u.bus_name,
+            cmd_to_completion: u.cmd_to_completion,
+            cmd_to_first_data: u.cmd_to_first_data,
+            last_data_to_completion: u.last_data_to_completion,
+            transaction_delays: u.transaction_delays,
+            error_rate: u.error_rate,
+            errors: u.errors,
+            correct_num: u.correct_num,
+            averaged_bandwidth: u.averaged_bandwidth,
+            bandwidth_windows: u.bandwidth_windows,
+            window_length: u.window_length,
+            clock_period: u.clock_period,
+            bandwidth_above_x_rate: u.bandwidth_above_x_rate,
+            bandwidth_below_y_rate: u.bandwidth_below_y_rate,
+            time: u.time,
+            x_rate: u.x_rate,
+            y_rate: u.y_rate,
+            intervals: u.intervals,
+            labels: Default::default(),
+            tags: vec![],
+        }
+    }
+}
+
+impl From<BusData> for super::BusData {
+    fn from(data: BusData) -> Self {
+        let usage = match data.usage {
+            BusUsage::SingleChannel(u) => super::BusUsage::SingleChannel(u.into()),
+            BusUsage::MultiChannel(u) => super::BusUsage::MultiChannel(u.into()),
+        };
+        super::BusData::new(usage, data.signals)
+    }
+}
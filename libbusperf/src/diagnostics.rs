@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+
+use crate::bus_usage::RealTime;
+
+/// Severity of a [Diagnostic].
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, bincode::Encode, bincode::Decode,
+)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Warning => f.write_str("WARN"),
+            Severity::Error => f.write_str("ERROR"),
+        }
+    }
+}
+
+/// Problem found during the analysis of a bus, e.g. a transaction that did not finish.
+#[derive(Debug, Clone, PartialEq, bincode::Encode, bincode::Decode)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// Identifier of the kind of problem, e.g. `unfinished_transaction`.
+    pub kind: String,
+    /// Description of the first occurrence, which may include e.g. values of signals.
+    pub message: String,
+    /// Waveform time of the first occurrence.
+    pub time: RealTime,
+    /// Number of occurrences.
+    pub count: u32,
+}
+
+/// Diagnostics of a bus. Occurrences of the same kind of problem with the same severity are
+/// counted in a single [Diagnostic].
+#[derive(Debug, Clone, Default)]
+pub struct Diagnostics {
+    list: Vec<Diagnostic>,
+    // Position in `list` of each severity and kind
+    index: HashMap<(Severity, String), usize>,
+}
+
+impl PartialEq for Diagnostics {
+    fn eq(&self, other: &Self) -> bool {
+        self.list == other.list
+    }
+}
+
+// Only the list is saved, the index is rebuilt when it is loaded
+impl bincode::Encode for Diagnostics {
+    fn encode<E: bincode::enc::Encoder>(
+        &self,
+        encoder: &mut E,
+    ) -> Result<(), bincode::error::EncodeError> {
+        self.list.encode(encoder)
+    }
+}
+
+impl<Context> bincode::Decode<Context> for Diagnostics {
+    fn decode<D: bincode::de::Decoder<Context = Context>>(
+        decoder: &mut D,
+    ) -> Result<Self, bincode::error::DecodeError> {
+        let mut diagnostics = Diagnostics::default();
+        for diagnostic in Vec::<Diagnostic>::decode(decoder)? {
+            diagnostics.add(diagnostic);
+        }
+        Ok(diagnostics)
+    }
+}
+
+bincode::impl_borrow_decode!(Diagnostics);
+
+impl Diagnostics {
+    // Counts the occurrences of `diagnostic`, keeping message of the earliest one
+    fn add(&mut self, diagnostic: Diagnostic) {
+        match self
+            .index
+            .get(&(diagnostic.severity, diagnostic.kind.clone()))
+        {
+            Some(&i) => {
+                let existing = &mut self.list[i];
+                existing.count += diagnostic.count;
+                if diagnostic.time < existing.time {
+                    existing.time = diagnostic.time;
+                    existing.message = diagnostic.message;
+                }
+            }
+            None => {
+                self.index.insert(
+                    (diagnostic.severity, diagnostic.kind.clone()),
+                    self.list.len(),
+                );
+                self.list.push(diagnostic);
+            }
+        }
+    }
+
+    pub fn report(
+        &mut self,
+        severity: Severity,
+        kind: &str,
+        message: impl Into<String>,
+        time: RealTime,
+    ) {
+        self.add(Diagnostic {
+            severity,
+            kind: kind.to_owned(),
+            message: message.into(),
+            time,
+            count: 1,
+        });
+    }
+
+    pub fn warn(&mut self, kind: &str, message: impl Into<String>, time: RealTime) {
+        self.report(Severity::Warning, kind, message, time);
+    }
+
+    pub fn error(&mut self, kind: &str, message: impl Into<String>, time: RealTime) {
+        self.report(Severity::Error, kind, message, time);
+    }
+
+    /// Adds diagnostics of another analysis, e.g. of the same bus in a different simulation.
+    pub fn merge(&mut self, other: &Diagnostics) {
+        for d in other.iter() {
+            self.add(d.clone());
+        }
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Diagnostic> {
+        self.list.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+}
@@ -1,4 +1,7 @@
+pub mod binning;
 pub mod bus_usage;
+pub mod diagnostics;
+pub mod saved;
 
 /// State in which a bus was in during a clock cycle.
 ///
@@ -12,7 +15,7 @@ pub mod bus_usage;
 // use pyo3::prelude::*;
 // #[cfg(feature = "python-plugins")]
 // #[pyclass]
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Debug, bincode::Encode, bincode::Decode)]
 pub enum CycleType {
     Busy,
     Free,
@@ -41,6 +44,68 @@ impl std::fmt::Display for SignalPath {
     }
 }
 
+/// Unit of waveform time, a single time step lasts `factor * 10^exponent` seconds.
+#[derive(Debug, Clone, Copy, PartialEq, bincode::Encode, bincode::Decode)]
+pub struct Timescale {
+    pub factor: u32,
+    pub exponent: i8,
+}
+
+impl Default for Timescale {
+    fn default() -> Self {
+        Timescale {
+            factor: 1,
+            exponent: -9,
+        }
+    }
+}
+
+const TIME_UNITS: &[(i8, &str)] = &[
+    (0, "s"),
+    (-3, "ms"),
+    (-6, "us"),
+    (-9, "ns"),
+    (-12, "ps"),
+    (-15, "fs"),
+];
+
+impl Timescale {
+    pub fn new(factor: u32, exponent: i8) -> Self {
+        Timescale { factor, exponent }
+    }
+
+    /// Returns exponent of the unit name (e.g. -6 for "us"), [None] if the unit is not known.
+    pub fn unit_exponent(unit: &str) -> Option<i8> {
+        TIME_UNITS.iter().find(|(_, u)| *u == unit).map(|(e, _)| *e)
+    }
+
+    /// Converts `value` expressed in unit of 10^`exponent` seconds to waveform time.
+    pub fn to_waveform_time(&self, value: f64, exponent: i8) -> bus_usage::RealTime {
+        (value * 10f64.powi((exponent - self.exponent) as i32) / self.factor as f64).round()
+            as bus_usage::RealTime
+    }
+
+    /// Formats waveform time in the largest unit in which it is at least 1 (e.g. "12.5us").
+    pub fn format_time(&self, time: bus_usage::RealTime) -> String {
+        if time == 0 {
+            return "0s".into();
+        }
+        let time = time as f64 * self.factor as f64;
+        let (exponent, unit) = TIME_UNITS
+            .iter()
+            .find(|(e, _)| time * 10f64.powi((self.exponent - e) as i32) >= 1.0)
+            .unwrap_or(&TIME_UNITS[TIME_UNITS.len() - 1]);
+        let value = format!(
+            "{:.3}",
+            time * 10f64.powi((self.exponent - exponent) as i32)
+        );
+        format!(
+            "{}{unit}",
+            value.trim_end_matches('0').trim_end_matches('.')
+        )
+    }
+}
+
 #[cfg(feature = "file-hash")]
 pub fn calculate_file_hash(filename: &str) -> Result<blake3::Hash, Box<dyn std::error::Error>> {
     use std::fs::File;
@@ -0,0 +1,60 @@
+//! Format of files with saved statistics of buses.
+//!
+//! A file starts with [MAGIC] and [FORMAT_VERSION], followed by the trace path, hash of the trace,
+//! its timescale and statistics of every bus, all encoded with bincode. Files saved before the
+//! format had a version have neither the header nor the timescale.
+
+use crate::{
+    Timescale,
+    bus_usage::{BusData, legacy},
+};
+
+/// Marks files with a format version.
+pub const MAGIC: &[u8] = b"BUSPERF\0";
+/// Version of the format, increased whenever layout of saved statistics changes.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// Trace path, trace hash, timescale and statistics of buses.
+pub type Saved = (String, String, Timescale, Vec<BusData>);
+
+/// Encodes statistics of buses in the current format version.
+pub fn encode(
+    trace_path: &str,
+    hash: &str,
+    timescale: &Timescale,
+    usages: &[BusData],
+) -> Result<Vec<u8>, String> {
+    let config = bincode::config::standard();
+    let mut data = MAGIC.to_vec();
+    bincode::encode_into_std_write(
+        (FORMAT_VERSION, trace_path, hash, timescale, usages),
+        &mut data,
+        config,
+    )
+    .map_err(|_| "Serialization failed")?;
+    Ok(data)
+}
+
+/// Decodes statistics of buses. Files saved before the format had a version get the default
+/// timescale.
+pub fn decode(data: &[u8]) -> Result<Saved, String> {
+    let config = bincode::config::standard();
+    let Some(data) = data.strip_prefix(MAGIC) else {
+        let ((trace_path, hash, usages), _): ((String, String, Vec<legacy::BusData>), _) =
+            bincode::decode_from_slice(data, config).map_err(|_| {
+                "Invalid file data, the file may have been saved by an incompatible version of busperf"
+            })?;
+        let usages = usages.into_iter().map(BusData::from).collect();
+        return Ok((trace_path, hash, Timescale::default(), usages));
+    };
+    let (version, read): (u32, _) =
+        bincode::decode_from_slice(data, config).map_err(|_| "Invalid file data")?;
+    if version != FORMAT_VERSION {
+        Err(format!(
+            "File was saved in format version {version}, while this version of busperf reads version {FORMAT_VERSION}"
+        ))?
+    }
+    let (saved, _) =
+        bincode::decode_from_slice(&data[read..], config).map_err(|_| "Invalid file data")?;
+    Ok(saved)
+}
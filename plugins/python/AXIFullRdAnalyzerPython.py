@@ -85,6 +85,7 @@ class Analyzer:
                         read,
                         resp,
                         completed.next,
+                        id_value,
                     ))
 
         leftover_times = [
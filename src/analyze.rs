@@ -1,24 +1,34 @@
 use std::{
+    collections::{HashMap, HashSet},
     error::Error,
     fs::File,
-    io::{BufReader, Read},
+    io::{BufRead, BufReader, Cursor, Read, Seek},
+    path::{Path, PathBuf},
     sync::{Arc, atomic::AtomicU64},
 };
 
 use wellen::{
-    Hierarchy, LoadOptions,
+    FileFormat, Hierarchy, LoadOptions,
     viewers::{self, BodyResult},
 };
-use yaml_rust2::YamlLoader;
+use yaml_rust2::{Yaml, YamlLoader};
 
 use analyzer::{Analyzer, AnalyzerBuilder};
 use bus::SignalPath;
-use libbusperf::CyclesNum;
+pub use cache::ResultCache;
+pub use follow::follow_simulation_trace;
+use intervals::Phase;
+use libbusperf::{CyclesNum, Timescale};
 
 pub mod analyzer;
 mod bus;
+mod cache;
+mod follow;
+mod intervals;
+mod ipxact;
 #[cfg(feature = "python-plugins")]
 mod plugins;
+mod stream;
 
 /// Loads descriptions of the buses from yaml file with given name.
 pub fn load_bus_analyzers(
@@ -29,55 +39,292 @@ pub fn load_bus_analyzers(
     y_rate: f32,
     plugins_path: &str,
 ) -> Result<Vec<Box<dyn Analyzer>>, Box<dyn std::error::Error>> {
-    let mut f = File::open(filename)?;
-    let mut s = String::new();
-    f.read_to_string(&mut s)?;
-    let mut yaml = YamlLoader::load_from_str(&s)?;
-    let mut doc = yaml
-        .remove(0)
-        .into_hash()
-        .ok_or("Yaml should not be empty")?;
-    let interfaces = doc
-        .remove(&yaml_rust2::Yaml::from_str("interfaces"))
-        .ok_or("Yaml should define interfaces")?
-        .into_hash()
-        .ok_or("Invalid yaml format")?;
-    let unused = doc
-        .into_iter()
-        .filter_map(|(name, _)| {
-            if let Some(s) = name.into_string()
-                && s != "scopes"
-                && s != "common_clk_rst_ifs"
-            {
-                Some(s)
-            } else {
-                None
+    load_merged_bus_analyzers(
+        &[filename],
+        default_max_burst_delay,
+        window_length,
+        x_rate,
+        y_rate,
+        plugins_path,
+    )
+}
+
+/// Loads descriptions of the buses from several yaml files and merges them.
+///
+/// Files listed in `include` of each yaml are loaded as well. Every bus and phase name has to be
+/// unique across all loaded files. If phases are defined, every bus is analyzed separately in each of them.
+pub fn load_merged_bus_analyzers(
+    filenames: &[&str],
+    default_max_burst_delay: CyclesNum,
+    window_length: u32,
+    x_rate: f32,
+    y_rate: f32,
+    plugins_path: &str,
+) -> Result<Vec<Box<dyn Analyzer>>, Box<dyn std::error::Error>> {
+    let mut descriptions = Descriptions::default();
+    for filename in filenames {
+        descriptions.load_file(Path::new(filename))?;
+    }
+    descriptions.build_analyzers(
+        default_max_burst_delay,
+        window_length,
+        x_rate,
+        y_rate,
+        plugins_path,
+    )
+}
+
+// Interfaces and phases of bus descriptions, with sources in which they are defined
+#[derive(Default, Clone)]
+pub(crate) struct Descriptions {
+    interfaces: Vec<(Yaml, Yaml, PathBuf)>,
+    phases: Vec<(Yaml, Yaml, PathBuf)>,
+    loaded: Vec<PathBuf>,
+    skipped: Vec<String>,
+}
+
+impl Descriptions {
+    // Reads interfaces and phases of a yaml file and of all files it includes. Files that were already loaded are skipped.
+    pub fn load_file(&mut self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let canonical = path
+            .canonicalize()
+            .map_err(|e| format!("{}: {e}", path.display()))?;
+        if self.loaded.contains(&canonical) {
+            return Ok(());
+        }
+        self.loaded.push(canonical);
+
+        let mut f = File::open(path)?;
+        let mut s = String::new();
+        f.read_to_string(&mut s)?;
+        self.load_str(&s, path)
+    }
+
+    // Reads a yaml document, `source` is used in error messages and includes are relative to it
+    pub fn load_str(&mut self, s: &str, source: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let mut yaml =
+            YamlLoader::load_from_str(s).map_err(|e| format!("{}: {e}", source.display()))?;
+        if yaml.is_empty() {
+            Err(format!("{}: Yaml should not be empty", source.display()))?
+        }
+        self.load_yaml(yaml.remove(0), source)
+    }
+
+    pub fn load_yaml(&mut self, doc: Yaml, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let mut doc = doc
+            .into_hash()
+            .ok_or(format!("{}: Yaml should not be empty", path.display()))?;
+        let includes = match doc.remove(&Yaml::from_str("include")) {
+            Some(Yaml::String(include)) => vec![include],
+            Some(Yaml::Array(includes)) => includes
+                .into_iter()
+                .map(|i| i.into_string())
+                .collect::<Option<Vec<_>>>()
+                .ok_or(format!(
+                    "{}: include should be a path or a list of paths",
+                    path.display()
+                ))?,
+            Some(_) => Err(format!(
+                "{}: include should be a path or a list of paths",
+                path.display()
+            ))?,
+            None => vec![],
+        };
+        let file_interfaces = match doc.remove(&Yaml::from_str("interfaces")) {
+            Some(interfaces) => interfaces
+                .into_hash()
+                .ok_or(format!("{}: Invalid yaml format", path.display()))?,
+            None if !includes.is_empty() => Default::default(),
+            None => Err(format!("{}: Yaml should define interfaces", path.display()))?,
+        };
+        let file_phases = match doc.remove(&Yaml::from_str("phases")) {
+            Some(phases) => phases
+                .into_hash()
+                .ok_or(format!("{}: phases should be a mapping", path.display()))?,
+            None => Default::default(),
+        };
+        let unused = doc
+            .into_iter()
+            .filter_map(|(name, _)| {
+                if let Some(s) = name.into_string()
+                    && s != "scopes"
+                    && s != "common_clk_rst_ifs"
+                    && s != "common_analysis_params"
+                {
+                    Some(s)
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+        if !unused.is_empty() {
+            Err(format!(
+                "{}: Yaml can only have interfaces, include(optional), phases(optional), scopes(optional), common_clk_rst_ifs(optional) and common_analysis_params(optional) in top level, but has extra: {}",
+                path.display(),
+                unused.join(", ")
+            ))?;
+        }
+
+        // Included paths are relative to the including file
+        let dir = path.parent().unwrap_or(Path::new(""));
+        for include in includes {
+            self.load_file(&dir.join(include))?;
+        }
+        self.interfaces.extend(
+            file_interfaces
+                .into_iter()
+                .map(|(name, dict)| (name, dict, path.to_owned())),
+        );
+        self.phases.extend(
+            file_phases
+                .into_iter()
+                .map(|(name, dict)| (name, dict, path.to_owned())),
+        );
+        Ok(())
+    }
+
+    // Adds AMBA bus interfaces of an IP-XACT component
+    pub fn load_ipxact(
+        &mut self,
+        path: &Path,
+        scope: Option<&[String]>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let xml = std::fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+        let (interfaces, skipped) = ipxact::interfaces_from_ipxact(&xml, scope)?;
+        self.skipped.extend(
+            skipped
+                .into_iter()
+                .map(|s| format!("{}: {s}", path.display())),
+        );
+        self.interfaces.extend(
+            interfaces
+                .into_iter()
+                .map(|(name, dict)| (name, dict, path.to_owned())),
+        );
+        Ok(())
+    }
+
+    // Messages about IP-XACT bus interfaces that are not supported and were skipped
+    pub fn skipped(&self) -> &[String] {
+        &self.skipped
+    }
+
+    // Checks that names of buses and phases are unique and creates analyzers of every bus in every phase
+    pub fn build_analyzers(
+        &self,
+        default_max_burst_delay: CyclesNum,
+        window_length: u32,
+        x_rate: f32,
+        y_rate: f32,
+        plugins_path: &str,
+    ) -> Result<Vec<Box<dyn Analyzer>>, Box<dyn std::error::Error>> {
+        for (kind, definitions) in [("bus", &self.interfaces), ("phase", &self.phases)] {
+            let mut sources: HashMap<&str, &Path> = HashMap::new();
+            for (name, _, source) in definitions.iter() {
+                let name = name
+                    .as_str()
+                    .ok_or(format!("Each {kind} should have a name"))?;
+                if let Some(first) = sources.insert(name, source) {
+                    Err(format!(
+                        "{kind} {name} is defined in both {} and {}",
+                        first.display(),
+                        source.display()
+                    ))?;
+                }
             }
-        })
-        .collect::<Vec<_>>();
-    if !unused.is_empty() {
-        Err(format!(
-            "Yaml can only have interfaces, scopes(optional) and common_clk_rst_ifs(optional) in top level, but has extra: {}",
-            unused.join(", ")
-        ))?;
+        }
+        let phases = self
+            .phases
+            .iter()
+            .map(|(name, dict, _)| Phase::from_yaml(name, dict))
+            .collect::<Result<Vec<_>, _>>()?;
+        build_analyzers(
+            self.interfaces
+                .iter()
+                .map(|(name, dict, _)| (name.clone(), dict.clone())),
+            &phases,
+            default_max_burst_delay,
+            window_length,
+            x_rate,
+            y_rate,
+            plugins_path,
+        )
     }
+}
+
+/// Creates analyzers for the AMBA bus interfaces of an IP-XACT component file with given name.
+/// Bus interfaces of other types are skipped, see [Session::skipped_interfaces](crate::session::Session::skipped_interfaces).
+///
+/// * `scope` - scope of the component instance in the trace, if [None] the component name is used.
+pub fn load_ipxact_analyzers(
+    filename: &str,
+    scope: Option<&[String]>,
+    default_max_burst_delay: CyclesNum,
+    window_length: u32,
+    x_rate: f32,
+    y_rate: f32,
+) -> Result<Vec<Box<dyn Analyzer>>, Box<dyn std::error::Error>> {
+    let mut descriptions = Descriptions::default();
+    descriptions.load_ipxact(Path::new(filename), scope)?;
+    descriptions.build_analyzers(default_max_burst_delay, window_length, x_rate, y_rate, "")
+}
+
+fn build_analyzers(
+    interfaces: impl IntoIterator<Item = (Yaml, Yaml)>,
+    phases: &[Phase],
+    default_max_burst_delay: CyclesNum,
+    window_length: u32,
+    x_rate: f32,
+    y_rate: f32,
+    plugins_path: &str,
+) -> Result<Vec<Box<dyn Analyzer>>, Box<dyn std::error::Error>> {
     let mut analyzers: Vec<Box<dyn Analyzer>> = vec![];
     for (name, dict) in interfaces {
         let n = name
             .as_str()
             .ok_or("Each bus should have a name")?
             .to_owned();
-        analyzers.push(
-            AnalyzerBuilder::build(
-                (name, dict),
-                default_max_burst_delay,
-                window_length,
-                x_rate,
-                y_rate,
-                plugins_path,
-            )
-            .map_err(|e| format!("bus {n}, {e}"))?,
-        );
+        let phases = if phases.is_empty() {
+            vec![None]
+        } else {
+            phases.iter().map(Some).collect()
+        };
+        for phase in phases {
+            analyzers.push(
+                AnalyzerBuilder::build(
+                    (name.clone(), dict.clone()),
+                    default_max_burst_delay,
+                    window_length,
+                    x_rate,
+                    y_rate,
+                    plugins_path,
+                    phase,
+                )
+                .map_err(|e| format!("bus {n}, {e}"))?,
+            );
+        }
+    }
+    for analyzer in analyzers.iter() {
+        for feeds in analyzer.feeds() {
+            if feeds == analyzer.name() || analyzers.iter().all(|a| a.name() != feeds) {
+                Err(format!(
+                    "bus {}, feeds {feeds} which is not another described bus",
+                    analyzer.name()
+                ))?
+            }
+        }
+    }
+    // States of buses in chains are needed to attribute backpressure
+    let chained = analyzers
+        .iter()
+        .filter(|a| !a.feeds().is_empty())
+        .flat_map(|a| a.feeds().iter().map(String::as_str).chain([a.name()]))
+        .map(str::to_owned)
+        .collect::<HashSet<_>>();
+    for analyzer in analyzers.iter_mut() {
+        if chained.contains(analyzer.name()) {
+            analyzer.common_mut().set_chained();
+        }
     }
     Ok(analyzers)
 }
@@ -87,8 +334,111 @@ pub struct SimulationData {
     body: BodyResult,
 }
 
+impl SimulationData {
+    /// Returns timescale of the trace, [None] if the trace does not define it.
+    pub fn timescale(&self) -> Option<Timescale> {
+        let timescale = self.hierarchy.timescale()?;
+        Some(Timescale::new(
+            timescale.factor,
+            timescale.unit.to_exponent()?,
+        ))
+    }
+
+    /// Creates simulation data from a trace already loaded with wellen.
+    pub fn from_wellen(hierarchy: Hierarchy, body: BodyResult) -> Self {
+        SimulationData { hierarchy, body }
+    }
+
+    /// Returns time of the last time step in the trace.
+    pub fn end_time(&self) -> wellen::Time {
+        self.body.time_table.last().copied().unwrap_or_default()
+    }
+}
+
+enum TraceInput {
+    File(BufReader<File>),
+    // Standard input or decompressed file, which can only be read once
+    Stream(Box<dyn BufRead + Send + Sync>),
+}
+
+// Opens trace file, or standard input if `filename` is "-". Gzip compressed input is decompressed.
+fn open_trace(filename: &str) -> Result<TraceInput, Box<dyn Error>> {
+    let mut input: Box<dyn BufRead + Send + Sync> = if filename == "-" {
+        Box::new(BufReader::new(std::io::stdin()))
+    } else {
+        let mut file = BufReader::new(File::open(filename)?);
+        if !file.fill_buf()?.starts_with(&[0x1f, 0x8b]) {
+            return Ok(TraceInput::File(file));
+        }
+        Box::new(file)
+    };
+    if input.fill_buf()?.starts_with(&[0x1f, 0x8b]) {
+        input = Box::new(BufReader::new(flate2::bufread::MultiGzDecoder::new(input)));
+    }
+    Ok(TraceInput::Stream(input))
+}
+
+// VCD starts with a command, other formats are binary
+fn is_vcd(input: &mut impl BufRead) -> Result<bool, Box<dyn Error>> {
+    Ok(input
+        .fill_buf()?
+        .iter()
+        .find(|c| !c.is_ascii_whitespace())
+        .is_some_and(|c| *c == b'$'))
+}
+
+/// Checks whether file is a simulation trace supported by busperf, based on its content.
+pub fn is_simulation_trace(filename: &str) -> bool {
+    // Standard input and named pipes can be read only once
+    if filename == "-" {
+        return true;
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::FileTypeExt;
+        if std::fs::metadata(filename).is_ok_and(|m| m.file_type().is_fifo()) {
+            return true;
+        }
+    }
+    match open_trace(filename) {
+        Ok(TraceInput::File(mut file)) => {
+            viewers::detect_file_format(&mut file) != FileFormat::Unknown
+        }
+        Ok(TraceInput::Stream(mut input)) => is_vcd(&mut input).unwrap_or(false),
+        Err(_) => false,
+    }
+}
+
+fn read_seekable_trace<R: BufRead + Seek + Send + Sync + 'static>(
+    input: R,
+) -> Result<SimulationData, Box<dyn Error>> {
+    let load_options = LoadOptions {
+        multi_thread: true,
+        remove_scopes_with_empty_name: false,
+    };
+    let header = viewers::read_header(input, &load_options)?;
+    let hierarchy = header.hierarchy;
+    let body = viewers::read_body(header.body, &hierarchy, Some(Arc::new(AtomicU64::new(0))))?;
+    Ok(SimulationData { hierarchy, body })
+}
+
+fn load_trace_input(input: TraceInput) -> Result<SimulationData, Box<dyn Error>> {
+    match input {
+        TraceInput::File(file) => read_seekable_trace(file),
+        // Format of the trace is detected by reading its beginning, which requires seeking
+        TraceInput::Stream(mut input) => {
+            let mut data = vec![];
+            input.read_to_end(&mut data)?;
+            read_seekable_trace(Cursor::new(data))
+        }
+    }
+}
+
 /// Loads waveform file.
 ///
+/// VCD, FST and GHW formats are supported. Gzip compressed files are decompressed and `-` reads
+/// the trace from standard input.
+///
 /// * `filename` - path to file.
 /// * `verbose` - prints how long it took to load.
 pub fn load_simulation_trace(
@@ -96,18 +446,33 @@ pub fn load_simulation_trace(
     verbose: bool,
 ) -> Result<SimulationData, Box<dyn std::error::Error>> {
     let start = std::time::Instant::now();
-    let load_options = LoadOptions {
-        multi_thread: true,
-        remove_scopes_with_empty_name: false,
-    };
-    let file = BufReader::new(std::fs::File::open(filename)?);
-    let header = viewers::read_header(file, &load_options)?;
-    let hierarchy = header.hierarchy;
-    let body = viewers::read_body(header.body, &hierarchy, Some(Arc::new(AtomicU64::new(0))))?;
+    let data = load_trace_input(open_trace(filename)?)?;
     if verbose {
         println!("Loading trace took {:?}", start.elapsed());
     }
-    Ok(SimulationData { hierarchy, body })
+    Ok(data)
+}
+
+/// Loads waveform file keeping only signals used by the `analyzers`.
+///
+/// VCD files, also compressed or read from standard input, are read incrementally, so memory usage
+/// depends on the number of value changes of analyzed signals instead of the size of the trace.
+/// The value changes of analyzed signals are kept in memory for the whole trace. Other formats are
+/// loaded whole with [load_simulation_trace].
+///
+/// * `filename` - path to file.
+/// * `analyzers` - analyzers that will be run on the trace.
+/// * `verbose` - prints how long it took to load.
+pub fn stream_simulation_trace(
+    filename: &str,
+    analyzers: &[Box<dyn Analyzer>],
+    verbose: bool,
+) -> Result<SimulationData, Box<dyn std::error::Error>> {
+    let signals: Vec<&SignalPath> = analyzers
+        .iter()
+        .flat_map(|a| a.required_signals())
+        .collect();
+    stream::stream_trace(filename, &signals, verbose)
 }
 
 fn load_signals<'signal_buffer>(
@@ -115,9 +480,21 @@ fn load_signals<'signal_buffer>(
     signal_paths: &Vec<&SignalPath>,
     buffer: &'signal_buffer mut Vec<(wellen::SignalRef, wellen::Signal)>,
 ) -> Result<Vec<&'signal_buffer (wellen::SignalRef, wellen::Signal)>, Box<dyn Error>> {
+    let signal_refs = signal_refs(simulation_data, signal_paths)?;
+    *buffer =
+        simulation_data
+            .body
+            .source
+            .load_signals(&signal_refs, &simulation_data.hierarchy, true);
+    Ok(select_signals(buffer, &signal_refs))
+}
+
+fn signal_refs(
+    simulation_data: &SimulationData,
+    signal_paths: &[&SignalPath],
+) -> Result<Vec<wellen::SignalRef>, Box<dyn Error>> {
     let hierarchy = &simulation_data.hierarchy;
-    let body = &mut simulation_data.body;
-    let signal_refs: Vec<wellen::SignalRef> = signal_paths
+    signal_paths
         .iter()
         .map(|path| {
             Ok(hierarchy[hierarchy
@@ -125,13 +502,17 @@ fn load_signals<'signal_buffer>(
                 .ok_or(format!("signal \"{}\" does not exist", path))?]
             .signal_ref())
         })
-        .collect::<Result<_, Box<dyn Error>>>()?;
+        .collect()
+}
 
-    *buffer = body.source.load_signals(&signal_refs, hierarchy, true);
-    // SignalSource::load_signals can return a vector of different size than passsed signals refs vector
-    // e.g when some ref is duplicated (this can happen if a user uses the same simulation signal in two
-    // analyzer signals) but we want to return loaded signals in same order as in requested paths
-    let loaded = signal_refs
+// SignalSource::load_signals can return a vector of different size than passsed signals refs vector
+// e.g when some ref is duplicated (this can happen if a user uses the same simulation signal in two
+// analyzer signals) but we want to return loaded signals in same order as in requested refs
+fn select_signals<'signal_buffer>(
+    buffer: &'signal_buffer [(wellen::SignalRef, wellen::Signal)],
+    signal_refs: &[wellen::SignalRef],
+) -> Vec<&'signal_buffer (wellen::SignalRef, wellen::Signal)> {
+    signal_refs
         .iter()
         .map(|signal_ref| {
             buffer
@@ -139,7 +520,5 @@ fn load_signals<'signal_buffer>(
                 .find(|(r, _)| signal_ref == r)
                 .expect("Signal should be loaded for each SignalRef")
         })
-        .collect();
-
-    Ok(loaded)
+        .collect()
 }
@@ -1,18 +1,24 @@
-use std::error::Error;
+use std::{error::Error, sync::Mutex};
 
 use default_analyzer::DefaultAnalyzer;
 #[cfg(feature = "python-plugins")]
 use python_analyzer::PythonAnalyzer;
-use yaml_rust2::Yaml;
+use yaml_rust2::{Yaml, YamlEmitter};
 
 #[cfg(feature = "python-plugins")]
 use crate::analyze::bus::BusCommon;
 use crate::analyze::{
     SimulationData,
     analyzer::axi_analyzer::{AXIRdAnalyzer, AXIWrAnalyzer},
-    load_signals,
+    intervals::Phase,
+    load_signals, select_signals, signal_refs,
+};
+use libbusperf::{
+    CyclesNum, SignalPath,
+    binning::{Binning, Bins},
+    bus_usage::{BusData, BusUsage},
+    diagnostics::Diagnostics,
 };
-use libbusperf::{CyclesNum, bus_usage::BusUsage};
 
 mod axi_analyzer;
 mod default_analyzer;
@@ -29,10 +35,123 @@ const COMMON_YAML: &[&str] = &[
     "reset_type",
     "custom_analyzer",
     "intervals",
+    "exclude_intervals",
     "custom_handshake",
     "handshake",
+    "max_burst_delay",
+    "analysis_params.max_burst_delay",
+    "window_length",
+    "analysis_params.window_length",
+    "x_rate",
+    "analysis_params.x_rate",
+    "y_rate",
+    "analysis_params.y_rate",
+    "bins",
+    "analysis_params.bins",
+    "feeds",
 ];
 
+// Returns value of an analysis parameter set for the bus, either directly or in its analysis_params.
+// Values set directly take precedence.
+fn parse_param<T>(
+    dict: &Yaml,
+    key: &str,
+    default: T,
+    parse: impl Fn(&Yaml) -> Option<T>,
+) -> Result<T, Box<dyn Error>> {
+    match &dict["analysis_params"] {
+        Yaml::Hash(_) | Yaml::BadValue => (),
+        _ => Err("analysis_params should be a mapping of analysis parameters")?,
+    }
+    for value in [&dict[key], &dict["analysis_params"][key]] {
+        if !matches!(value, Yaml::BadValue) {
+            return parse(value).ok_or(format!("invalid value of {key}: {value:?}").into());
+        }
+    }
+    Ok(default)
+}
+
+fn parse_rate(yaml: &Yaml) -> Option<f32> {
+    yaml.as_f64()
+        .or(yaml.as_i64().map(|v| v as f64))
+        .filter(|v| *v >= 0.0)
+        .map(|v| v as f32)
+}
+
+fn parse_binning(yaml: &Yaml) -> Result<Binning, String> {
+    match yaml {
+        Yaml::String(s) => s.parse(),
+        Yaml::Array(edges) => edges
+            .iter()
+            .map(|e| {
+                e.as_i64()
+                    .and_then(|e| CyclesNum::try_from(e).ok())
+                    .ok_or(format!("invalid bin edge: {e:?}"))
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .and_then(Binning::edges),
+        _ => Err("binning should be log2, linear:WIDTH, edges:EDGE,... or a list of edges".into()),
+    }
+}
+
+// Returns binning of bucket statistics, set either for all of them or for each statistic by its key.
+fn parse_bins(dict: &Yaml) -> Result<Bins, Box<dyn Error>> {
+    let yaml = match &dict["bins"] {
+        Yaml::BadValue => &dict["analysis_params"]["bins"],
+        yaml => yaml,
+    };
+    let mut bins = Bins::default();
+    match yaml {
+        Yaml::BadValue => (),
+        Yaml::Hash(statistics) => {
+            for (key, binning) in statistics {
+                let key = key
+                    .as_str()
+                    .ok_or("keys of bins should be names of statistics")?;
+                let binning = parse_binning(binning).map_err(|e| format!("bins of {key}: {e}"))?;
+                match key {
+                    "default" => bins.set_default(binning),
+                    key => bins.set(key, binning),
+                }
+            }
+        }
+        yaml => bins.set_default(parse_binning(yaml).map_err(|e| format!("bins: {e}"))?),
+    }
+    Ok(bins)
+}
+
+// Returns names of buses fed by the bus, given as a single name or a list
+fn parse_feeds(dict: &Yaml) -> Result<Vec<String>, Box<dyn Error>> {
+    match &dict["feeds"] {
+        Yaml::BadValue => Ok(vec![]),
+        Yaml::String(name) => Ok(vec![name.clone()]),
+        Yaml::Array(names) => Ok(names
+            .iter()
+            .map(|n| n.as_str().map(|n| n.to_owned()))
+            .collect::<Option<_>>()
+            .ok_or("feeds should be a list of bus names")?),
+        _ => Err("feeds should be a bus name or a list of bus names")?,
+    }
+}
+
+// Returns code of Python plugins used by the bus, which can change without any change of its
+// description
+#[cfg(feature = "python-plugins")]
+fn plugin_sources(dict: &Yaml, plugins_path: &str) -> String {
+    [&dict["custom_analyzer"], &dict["custom_handshake"]]
+        .into_iter()
+        .filter_map(|name| name.as_str())
+        .filter(|name| !matches!(*name, "AXIWrAnalyzer" | "AXIRdAnalyzer"))
+        .filter_map(|name| crate::analyze::plugins::load_python_code(plugins_path, name).ok())
+        .map(|code| code.to_string_lossy().into_owned())
+        .collect()
+}
+
+#[cfg(not(feature = "python-plugins"))]
+fn plugin_sources(_dict: &Yaml, _plugins_path: &str) -> String {
+    String::new()
+}
+
 pub(crate) struct AnalyzerBuilder {}
 
 impl AnalyzerBuilder {
@@ -43,10 +162,45 @@ impl AnalyzerBuilder {
         x_rate: f32,
         y_rate: f32,
         plugins_path: &str,
+        phase: Option<&Phase>,
     ) -> Result<Box<dyn Analyzer>, Box<dyn Error>> {
         let (name, dict) = yaml;
+        let default_max_burst_delay =
+            parse_param(&dict, "max_burst_delay", default_max_burst_delay, |y| {
+                y.as_i64()
+                    .and_then(|v| CyclesNum::try_from(v).ok())
+                    .filter(|v| *v >= 0)
+            })?;
+        let window_length = parse_param(&dict, "window_length", window_length, |y| {
+            y.as_i64()
+                .and_then(|v| u32::try_from(v).ok())
+                .filter(|v| *v > 0)
+        })?;
+        let x_rate = parse_param(&dict, "x_rate", x_rate, parse_rate)?;
+        let y_rate = parse_param(&dict, "y_rate", y_rate, parse_rate)?;
+        let bins = parse_bins(&dict)?;
+        let feeds = parse_feeds(&dict)?;
+        if let Some(custom) = dict["custom_analyzer"].as_str()
+            && !feeds.is_empty()
+        {
+            Err(format!(
+                "feeds is not supported by buses analyzed by {custom}, backpressure of multichannel buses is not known"
+            ))?
+        }
         let to_check = dict.clone();
-        let analyzer: Box<dyn Analyzer> = if let Some(custom) = dict["custom_analyzer"].as_str() {
+        // Results of the analysis depend only on the trace and on everything hashed here
+        let mut yaml = String::new();
+        YamlEmitter::new(&mut yaml).dump(&dict)?;
+        let plugins = plugin_sources(&dict, plugins_path);
+        let config_hash = blake3::hash(
+            format!(
+                "{} {name:?} {yaml} {default_max_burst_delay} {window_length} {x_rate} {y_rate} {plugins_path} {plugins} {phase:?}",
+                env!("CARGO_PKG_VERSION")
+            )
+            .as_bytes(),
+        );
+        let mut analyzer: Box<dyn Analyzer> = if let Some(custom) = dict["custom_analyzer"].as_str()
+        {
             match custom {
                 "AXIWrAnalyzer" => Box::new(AXIWrAnalyzer::build_from_yaml(
                     (name, dict),
@@ -96,6 +250,7 @@ impl AnalyzerBuilder {
             Box::new(DefaultAnalyzer::from_yaml(
                 (name, dict),
                 default_max_burst_delay,
+                window_length,
                 plugins_path,
             )?)
         };
@@ -104,6 +259,12 @@ impl AnalyzerBuilder {
             &analyzer.required_yaml_definitions(),
             &mut vec![],
         );
+        analyzer.common_mut().set_config_hash(config_hash);
+        analyzer.common_mut().set_bins(bins);
+        analyzer.common_mut().set_feeds(feeds);
+        if let Some(phase) = phase {
+            analyzer.common_mut().set_phase(phase.clone());
+        }
         Ok(analyzer)
     }
 }
@@ -111,11 +272,19 @@ impl AnalyzerBuilder {
 mod private {
     use std::error::Error;
 
-    use crate::analyze::bus::SignalPath;
+    use crate::analyze::bus::{BusCommon, SignalPath};
+    use libbusperf::bus_usage::BusUsage;
     use wellen::{Signal, SignalRef, TimeTable};
 
     pub trait AnalyzerInternal {
         fn bus_name(&self) -> &str;
+        fn common(&self) -> &BusCommon;
+        fn common_mut(&mut self) -> &mut BusCommon;
+        fn set_results(&mut self, usage: Option<BusUsage>);
+        // Analyzers that call Python are run one at a time, as they would wait for the GIL anyway
+        fn uses_python(&self) -> bool {
+            false
+        }
         // Returns waveform scope paths to every signal required by the analyzer.
         fn get_signals(&self) -> Vec<&SignalPath>;
         // Method that should perform all calculations for an analysis of the bus
@@ -127,7 +296,7 @@ mod private {
     }
 }
 
-pub trait Analyzer: private::AnalyzerInternal {
+pub trait Analyzer: private::AnalyzerInternal + Send {
     /// Trait method that performs an analysis of a loaded bus.
     fn analyze(
         &mut self,
@@ -135,6 +304,7 @@ pub trait Analyzer: private::AnalyzerInternal {
         verbose: bool,
     ) -> Result<(), Box<dyn Error>> {
         let start = std::time::Instant::now();
+        self.common_mut().resolve_intervals(simulation_data)?;
         let signal_paths = self.get_signals();
         let mut buffer = Vec::new();
         let loaded = load_signals(simulation_data, &signal_paths, &mut buffer)?;
@@ -157,8 +327,56 @@ pub trait Analyzer: private::AnalyzerInternal {
         }
         Ok(())
     }
+    /// Returns paths to all signals read during the analysis, including signals defining intervals.
+    fn required_signals(&self) -> Vec<&SignalPath> {
+        let mut signals = self.get_signals();
+        signals.extend(self.common().interval_signals());
+        signals
+    }
+    /// Returns name of the analyzed bus.
+    fn name(&self) -> &str {
+        self.bus_name()
+    }
+    /// Returns problems found during the analysis.
+    fn diagnostics(&self) -> &Diagnostics {
+        self.common().diagnostics()
+    }
+    /// Returns name of the phase to which the analysis is limited.
+    fn phase(&self) -> Option<&str> {
+        self.common().phase()
+    }
+    /// Returns binning of bucket statistics of the bus.
+    fn bins(&self) -> &Bins {
+        self.common().bins()
+    }
+    /// Uses binning from `defaults` for statistics whose binning is not set in the description.
+    fn set_default_bins(&mut self, defaults: &Bins) {
+        let bins = self.common().bins().clone().with_defaults(defaults);
+        self.common_mut().set_bins(bins);
+    }
+    /// Returns names of buses to which the bus passes data.
+    fn feeds(&self) -> &[String] {
+        self.common().feeds()
+    }
+    /// Returns scope of the module in which the bus is described.
+    fn module_scope(&self) -> &[String] {
+        self.common().module_scope()
+    }
     /// If the analysis was run returns [Some] result of the analysis. If not - returns [None].
     fn get_results(&self) -> Option<&BusUsage>;
+    /// Returns result of the analysis together with the description of the bus, [None] if the
+    /// analysis was not run.
+    fn bus_data(&self) -> Option<BusData> {
+        self.get_results().cloned().map(|usage| BusData {
+            usage,
+            signals: self.get_signals().into_iter().cloned().collect(),
+            phase: self.phase().map(|p| p.to_owned()),
+            diagnostics: self.diagnostics().clone(),
+            bins: self.bins().clone(),
+            feeds: self.feeds().to_vec(),
+            scope: self.module_scope().to_vec(),
+        })
+    }
     fn finished_analysis(&self) -> bool {
         self.get_results().is_some()
     }
@@ -166,8 +384,86 @@ pub trait Analyzer: private::AnalyzerInternal {
     fn required_yaml_definitions(&self) -> Vec<&str>;
 }
 
+/// Analyzes every bus that was not analyzed yet and returns results in order of `analyzers`.
+///
+/// Signals of all buses are loaded from the trace at once and shared by the analyzers, which run on
+/// separate threads. Analyzers calling Python plugins run one after another on a single thread, as
+/// only one of them can hold the GIL at a time.
+pub fn analyze_all(
+    analyzers: &mut [Box<dyn Analyzer>],
+    simulation_data: &mut SimulationData,
+    verbose: bool,
+) -> Vec<Result<(), String>> {
+    let start = std::time::Instant::now();
+    let mut results = vec![Ok(()); analyzers.len()];
+    let mut loaded_refs = vec![None; analyzers.len()];
+    for (i, analyzer) in analyzers.iter_mut().enumerate() {
+        if analyzer.finished_analysis() {
+            continue;
+        }
+        let refs = analyzer
+            .common_mut()
+            .resolve_intervals(simulation_data)
+            .and_then(|_| signal_refs(simulation_data, &analyzer.get_signals()));
+        match refs {
+            Ok(refs) => loaded_refs[i] = Some(refs),
+            Err(e) => results[i] = Err(e.to_string()),
+        }
+    }
+    let all_refs: Vec<_> = loaded_refs.iter().flatten().flatten().copied().collect();
+    let buffer =
+        simulation_data
+            .body
+            .source
+            .load_signals(&all_refs, &simulation_data.hierarchy, true);
+    if verbose {
+        println!("Loading signals took {:?}", start.elapsed());
+    }
+
+    let time_table = &simulation_data.body.time_table;
+    let (python, native): (Vec<_>, Vec<_>) = analyzers
+        .iter_mut()
+        .zip(loaded_refs)
+        .zip(results.iter_mut())
+        .filter_map(|((analyzer, refs), result)| {
+            refs.map(|refs| (analyzer, select_signals(&buffer, &refs), result))
+        })
+        .partition(|(analyzer, _, _)| analyzer.uses_python());
+    let calculate = |(analyzer, loaded, result): (&mut Box<dyn Analyzer>, _, &mut Result<_, _>)| {
+        let start = std::time::Instant::now();
+        *result = analyzer
+            .calculate(loaded, time_table)
+            .map_err(|e| e.to_string());
+        if verbose {
+            println!(
+                "Calculating statistics for {} took {:?}",
+                analyzer.bus_name(),
+                start.elapsed()
+            );
+        }
+    };
+    let calculate = &calculate;
+    let native = &Mutex::new(native.into_iter());
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    std::thread::scope(|scope| {
+        scope.spawn(move || python.into_iter().for_each(calculate));
+        for _ in 0..threads {
+            scope.spawn(move || {
+                loop {
+                    let next = native.lock().unwrap().next();
+                    let Some(item) = next else { break };
+                    calculate(item);
+                }
+            });
+        }
+    });
+    results
+}
+
 fn check_unused_signals(yaml: &Yaml, used: &[&str], path: &mut Vec<String>) {
     match yaml {
+        // Whole mappings can be used, e.g. bins of statistics
+        Yaml::Hash(_) if !path.is_empty() && used.contains(&path.join(".").as_str()) => (),
         Yaml::Hash(linked_hash_map) => {
             for (k, v) in linked_hash_map {
                 if let Yaml::String(s) = k {
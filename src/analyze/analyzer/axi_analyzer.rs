@@ -14,8 +14,8 @@ use crate::analyze::{
         BusCommon, BusDescription, SignalPath, ValueType, axi::AXIBus, get_value, is_value_of_type,
     },
 };
-use libbusperf::CyclesNum;
 use libbusperf::bus_usage::{BusUsage, MultiChannelBusUsage};
+use libbusperf::{CyclesNum, diagnostics::Diagnostics};
 
 use super::Analyzer;
 
@@ -181,6 +181,7 @@ impl AXIRdAnalyzer {
     fn calculate_lite(
         &self,
         usage: &mut MultiChannelBusUsage,
+        diagnostics: &mut Diagnostics,
         mut ar: Peekable<ReadyValidTransactionIterator>,
         mut r: Peekable<ReadyValidTransactionIterator>,
         mut rst: RisingSignalIterator,
@@ -201,7 +202,7 @@ impl AXIRdAnalyzer {
                 while let Some(&n) = r.peek()
                     && n < *next_transaction
                 {
-                    eprintln!("[WARN] Read without AR at {}", time_table[n as usize]);
+                    diagnostics.warn("read_without_ar", "read without AR", time_table[n as usize]);
                     r.next();
                 }
                 let resp = r_resp
@@ -225,13 +226,14 @@ impl AXIRdAnalyzer {
                     read_time,
                     read_time,
                     &resp,
+                    None,
                     next_transaction,
                 );
             } else {
-                eprintln!(
-                    "[WARN] unfinished transaction on {} at {}",
-                    self.bus_name(),
-                    time_table[time as usize]
+                diagnostics.warn(
+                    "unfinished_transaction",
+                    "unfinished transaction",
+                    time_table[time as usize],
                 )
             }
         }
@@ -243,6 +245,7 @@ impl AXIRdAnalyzer {
     fn calculate_full(
         &self,
         usage: &mut MultiChannelBusUsage,
+        diagnostics: &mut Diagnostics,
         mut ar: Peekable<ReadyValidTransactionIterator>,
         mut r: Peekable<ReadyValidTransactionIterator>,
         mut rst: RisingSignalIterator,
@@ -255,7 +258,6 @@ impl AXIRdAnalyzer {
     ) -> Result<(), Box<dyn Error>> {
         let mut next_rst = rst.next().unwrap_or(*last_time + 1);
         let mut counting: HashMap<String, VecDeque<Transaction>> = HashMap::new();
-        let mut unfinished = String::new();
         'transaction_loop: while let Some(time) = ar.next() {
             while next_rst < time {
                 next_rst = rst.next().unwrap_or(*last_time + 1);
@@ -275,17 +277,13 @@ impl AXIRdAnalyzer {
                 && read < next_transaction
             {
                 if read > next_rst {
-                    unfinished.push_str(
-                        &counting
-                            .values()
-                            .flat_map(|vec| {
-                                vec.iter()
-                                    .map(|t| time_table[t.start as usize].to_string())
-                                    .collect::<Vec<_>>()
-                            })
-                            .collect::<Vec<_>>()
-                            .join(", "),
-                    );
+                    for t in counting.values().flatten() {
+                        diagnostics.warn(
+                            "unfinished_transaction",
+                            "unfinished transaction",
+                            time_table[t.start as usize],
+                        );
+                    }
                     counting.clear();
                     continue 'transaction_loop;
                 }
@@ -294,18 +292,18 @@ impl AXIRdAnalyzer {
                     .ok_or(format!("rid is invalid at {}", time_table[read as usize]))?;
 
                 let Some(t_vec) = counting.get_mut(&id) else {
-                    eprintln!(
-                        "[WARN] R without AR on {} at {}",
-                        self.bus_name(),
-                        time_table[read as usize]
+                    diagnostics.warn(
+                        "read_without_ar",
+                        "read without AR",
+                        time_table[read as usize],
                     );
                     continue 'transaction_loop;
                 };
                 let Some(t) = t_vec.get_mut(0) else {
-                    eprintln!(
-                        "[WARN] R without AR on {} at {}",
-                        self.bus_name(),
-                        time_table[read as usize]
+                    diagnostics.warn(
+                        "read_without_ar",
+                        "read without AR",
+                        time_table[read as usize],
                     );
                     continue 'transaction_loop;
                 };
@@ -330,27 +328,17 @@ impl AXIRdAnalyzer {
                         last_data,
                         first_data,
                         &resp,
+                        Some(&id),
                         next_transaction,
                     );
                 }
             }
         }
-        unfinished.push_str(
-            &counting
-                .values()
-                .flat_map(|vec| {
-                    vec.iter()
-                        .map(|t| time_table[t.start as usize].to_string())
-                        .collect::<Vec<_>>()
-                })
-                .collect::<Vec<_>>()
-                .join(", "),
-        );
-        if !unfinished.is_empty() {
-            eprintln!(
-                "[WARN] Unfinished transactions on {} at times: {}",
-                self.bus_name(),
-                unfinished
+        for t in counting.values().flatten() {
+            diagnostics.warn(
+                "unfinished_transaction",
+                "unfinished transaction",
+                time_table[t.start as usize],
             );
         }
         Ok(())
@@ -386,6 +374,7 @@ impl AnalyzerInternal for AXIRdAnalyzer {
         let (_, r_resp) = &loaded[6];
 
         let mut reset = 0;
+        let mut diagnostics = Diagnostics::default();
 
         let last_time = clk.time_indices().last().ok_or("clock has no values")?;
         let clock_period = *time_table.get(2).ok_or(
@@ -430,17 +419,35 @@ impl AnalyzerInternal for AXIRdAnalyzer {
                     let (_, r_last) = &loaded[9];
 
                     self.calculate_full(
-                        &mut usage, ar, r, rst, r_resp, ar_id, r_id, r_last, &end_idx, time_table,
+                        &mut usage,
+                        &mut diagnostics,
+                        ar,
+                        r,
+                        rst,
+                        r_resp,
+                        ar_id,
+                        r_id,
+                        r_last,
+                        &end_idx,
+                        time_table,
                     )?;
                 }
-                None => {
-                    self.calculate_lite(&mut usage, ar, r, rst, r_resp, &end_idx, time_table)?
-                }
+                None => self.calculate_lite(
+                    &mut usage,
+                    &mut diagnostics,
+                    ar,
+                    r,
+                    rst,
+                    r_resp,
+                    &end_idx,
+                    time_table,
+                )?,
             }
             usage.add_time(end - start);
         }
 
         usage.end(reset, intervals);
+        self.common.set_diagnostics(diagnostics);
         self.result = Some(BusUsage::MultiChannel(usage));
         Ok(())
     }
@@ -448,6 +455,18 @@ impl AnalyzerInternal for AXIRdAnalyzer {
     fn bus_name(&self) -> &str {
         self.common.bus_name()
     }
+
+    fn common(&self) -> &BusCommon {
+        &self.common
+    }
+
+    fn common_mut(&mut self) -> &mut BusCommon {
+        &mut self.common
+    }
+
+    fn set_results(&mut self, usage: Option<BusUsage>) {
+        self.result = usage;
+    }
 }
 
 impl Analyzer for AXIRdAnalyzer {
@@ -532,6 +551,7 @@ impl AXIWrAnalyzer {
     fn calculate_lite(
         &self,
         usage: &mut MultiChannelBusUsage,
+        diagnostics: &mut Diagnostics,
         mut aw: Peekable<ReadyValidTransactionIterator>,
         mut w: Peekable<ReadyValidTransactionIterator>,
         mut b: Peekable<ReadyValidTransactionIterator>,
@@ -575,13 +595,14 @@ impl AXIWrAnalyzer {
                     data_time,
                     data_time,
                     &resp,
+                    None,
                     next_transaction,
                 );
             } else {
-                eprintln!(
-                    "[WARN] unfinished transaction on {} at {}",
-                    self.bus_name(),
-                    time_table[time as usize]
+                diagnostics.warn(
+                    "unfinished_transaction",
+                    "unfinished transaction",
+                    time_table[time as usize],
                 )
             }
         }
@@ -593,6 +614,7 @@ impl AXIWrAnalyzer {
     fn calculate_full(
         &self,
         usage: &mut MultiChannelBusUsage,
+        diagnostics: &mut Diagnostics,
         mut aw: Peekable<ReadyValidTransactionIterator>,
         mut w: Peekable<ReadyValidTransactionIterator>,
         mut b: Peekable<ReadyValidTransactionIterator>,
@@ -606,7 +628,6 @@ impl AXIWrAnalyzer {
     ) -> Result<(), Box<dyn Error>> {
         let mut next_rst = rst.next().unwrap_or(*last_time + 1);
         let mut counting: HashMap<String, VecDeque<Transaction>> = HashMap::new();
-        let mut unfinished = String::new();
         'transactions_loop: while let Some(time) = aw.next() {
             while next_rst < time {
                 next_rst = rst.next().unwrap_or(*last_time + 1);
@@ -630,17 +651,13 @@ impl AXIWrAnalyzer {
                 .expect("Should be valid because it's just been added");
             while let Some(&write) = w.peek() {
                 if write > next_rst {
-                    unfinished.push_str(
-                        &counting
-                            .values()
-                            .flat_map(|vec| {
-                                vec.iter()
-                                    .map(|t| time_table[t.start as usize].to_string())
-                                    .collect::<Vec<_>>()
-                            })
-                            .collect::<Vec<_>>()
-                            .join(", "),
-                    );
+                    for t in counting.values().flatten() {
+                        diagnostics.warn(
+                            "unfinished_transaction",
+                            "unfinished transaction",
+                            time_table[t.start as usize],
+                        );
+                    }
                     counting.clear();
                     continue 'transactions_loop;
                 }
@@ -662,17 +679,13 @@ impl AXIWrAnalyzer {
                 && resp_time < next_transaction
             {
                 if resp_time > next_rst {
-                    unfinished.push_str(
-                        &counting
-                            .values()
-                            .flat_map(|vec| {
-                                vec.iter()
-                                    .map(|t| time_table[t.start as usize].to_string())
-                                    .collect::<Vec<_>>()
-                            })
-                            .collect::<Vec<_>>()
-                            .join(", "),
-                    );
+                    for t in counting.values().flatten() {
+                        diagnostics.warn(
+                            "unfinished_transaction",
+                            "unfinished transaction",
+                            time_table[t.start as usize],
+                        );
+                    }
                     counting.clear();
                     continue 'transactions_loop;
                 }
@@ -682,16 +695,18 @@ impl AXIWrAnalyzer {
                     time_table[resp_time as usize]
                 ))?;
                 let Some(t_vec) = counting.get_mut(&b_id) else {
-                    eprintln!(
-                        "[WARN] Transaction response without command at {}",
-                        time_table[resp_time as usize]
+                    diagnostics.warn(
+                        "response_without_command",
+                        "transaction response without command",
+                        time_table[resp_time as usize],
                     );
                     continue;
                 };
                 let Some(t) = t_vec.pop_front() else {
-                    eprintln!(
-                        "[WARN] Transaction response without command at {}",
-                        time_table[resp_time as usize]
+                    diagnostics.warn(
+                        "response_without_command",
+                        "transaction response without command",
+                        time_table[resp_time as usize],
                     );
                     continue;
                 };
@@ -715,26 +730,16 @@ impl AXIWrAnalyzer {
                     last_data,
                     first_data,
                     &resp,
+                    Some(&b_id),
                     next_transaction,
                 );
             }
         }
-        unfinished.push_str(
-            &counting
-                .values()
-                .flat_map(|vec| {
-                    vec.iter()
-                        .map(|t| time_table[t.start as usize].to_string())
-                        .collect::<Vec<_>>()
-                })
-                .collect::<Vec<_>>()
-                .join(", "),
-        );
-        if !unfinished.is_empty() {
-            eprintln!(
-                "[WARN] Unfinished transactions on {} at times: {}",
-                self.bus_name(),
-                unfinished
+        for t in counting.values().flatten() {
+            diagnostics.warn(
+                "unfinished_transaction",
+                "unfinished transaction",
+                time_table[t.start as usize],
             );
         }
         Ok(())
@@ -761,6 +766,18 @@ impl AnalyzerInternal for AXIWrAnalyzer {
         self.common.bus_name()
     }
 
+    fn common(&self) -> &BusCommon {
+        &self.common
+    }
+
+    fn common_mut(&mut self) -> &mut BusCommon {
+        &mut self.common
+    }
+
+    fn set_results(&mut self, usage: Option<BusUsage>) {
+        self.result = usage;
+    }
+
     fn calculate(
         &mut self,
         loaded: Vec<&(wellen::SignalRef, Signal)>,
@@ -777,6 +794,7 @@ impl AnalyzerInternal for AXIWrAnalyzer {
         let (_, b_resp) = &loaded[8];
 
         let mut reset = 0;
+        let mut diagnostics = Diagnostics::default();
         let last_time = clk
             .time_indices()
             .last()
@@ -825,18 +843,37 @@ impl AnalyzerInternal for AXIWrAnalyzer {
                     let (_, w_last) = &loaded[10];
                     let (_, b_id) = &loaded[11];
                     self.calculate_full(
-                        &mut usage, aw, w, b, aw_id, w_last, b_id, b_resp, rst, &end_idx,
+                        &mut usage,
+                        &mut diagnostics,
+                        aw,
+                        w,
+                        b,
+                        aw_id,
+                        w_last,
+                        b_id,
+                        b_resp,
+                        rst,
+                        &end_idx,
                         time_table,
                     )?;
                 }
-                None => {
-                    self.calculate_lite(&mut usage, aw, w, b, b_resp, rst, &end_idx, time_table)?
-                }
+                None => self.calculate_lite(
+                    &mut usage,
+                    &mut diagnostics,
+                    aw,
+                    w,
+                    b,
+                    b_resp,
+                    rst,
+                    &end_idx,
+                    time_table,
+                )?,
             }
             usage.add_time(end - start);
         }
 
         usage.end(reset, intervals);
+        self.common.set_diagnostics(diagnostics);
         self.result = Some(BusUsage::MultiChannel(usage));
         Ok(())
     }
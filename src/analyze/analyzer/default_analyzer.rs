@@ -8,12 +8,13 @@ use crate::analyze::{
     bus::{BusCommon, BusDescription, BusDescriptionBuilder, SignalPath, is_value_of_type},
 };
 use libbusperf::bus_usage::{BusUsage, SingleChannelBusUsage};
-use libbusperf::{CycleType, CyclesNum};
+use libbusperf::{CycleType, CyclesNum, diagnostics::Diagnostics};
 
 use super::Analyzer;
 
 pub struct DefaultAnalyzer {
     common: BusCommon,
+    window_length: u32,
     bus_desc: Box<dyn BusDescription>,
     result: Option<BusUsage>,
 }
@@ -22,6 +23,7 @@ impl DefaultAnalyzer {
     pub fn from_yaml(
         yaml: (yaml_rust2::Yaml, yaml_rust2::Yaml),
         default_max_burst_delay: CyclesNum,
+        window_length: u32,
         plugins_path: &str,
     ) -> Result<Self, Box<dyn std::error::Error>> {
         let (name, dict) = yaml;
@@ -32,6 +34,7 @@ impl DefaultAnalyzer {
         let bus_desc = BusDescriptionBuilder::build(dict, common.module_scope(), plugins_path)?;
         Ok(DefaultAnalyzer {
             common,
+            window_length,
             bus_desc,
             result: None,
         })
@@ -43,6 +46,22 @@ impl AnalyzerInternal for DefaultAnalyzer {
         self.common.bus_name()
     }
 
+    fn common(&self) -> &BusCommon {
+        &self.common
+    }
+
+    fn common_mut(&mut self) -> &mut BusCommon {
+        &mut self.common
+    }
+
+    fn set_results(&mut self, usage: Option<BusUsage>) {
+        self.result = usage;
+    }
+
+    fn uses_python(&self) -> bool {
+        self.bus_desc.uses_python()
+    }
+
     fn get_signals(&self) -> Vec<&SignalPath> {
         let mut signals = vec![self.common.clk_path(), self.common.rst_path()];
         signals.append(&mut self.bus_desc.signals());
@@ -63,7 +82,12 @@ impl AnalyzerInternal for DefaultAnalyzer {
             *time_table.get(2).ok_or(
                 "trace is too short (less than 3 time indices), cannot calculate clock period",
             )?,
+            self.window_length,
         );
+        if self.common.chained() {
+            usage.record_runs();
+        }
+        let mut diagnostics = Diagnostics::default();
         for (time, value) in clock.iter_changes() {
             if let SignalValue::Binary(v, 1) = value
                 && v[0] == 0
@@ -78,6 +102,7 @@ impl AnalyzerInternal for DefaultAnalyzer {
             {
                 continue;
             }
+            let edge_time = time_table[time as usize];
             // We subtract one to use values just before clock signal
             let time = time.saturating_sub(1);
             let reset = reset.get_value_at(
@@ -101,28 +126,33 @@ impl AnalyzerInternal for DefaultAnalyzer {
                 .collect::<Result<_, _>>()?;
 
             if !is_value_of_type(reset, self.common.rst_active_value()) {
-                let type_ = self.bus_desc.interpret_cycle(&values, time);
+                // Problems are reported at the edge at which the cycle is recorded
+                let type_ = self
+                    .bus_desc
+                    .interpret_cycle(&values, edge_time, &mut diagnostics);
                 if let CycleType::Unknown = type_ {
-                    let mut state = String::new();
-                    self.bus_desc
+                    let state = self
+                        .bus_desc
                         .signals()
                         .iter()
                         .zip(values)
-                        .for_each(|(name, value)| state.push_str(&format!("{name}: {value}, ")));
-                    eprintln!(
-                        "[WARN] bus \"{}\" in unknown state outside reset at time: {} - {}",
-                        self.common.bus_name(),
-                        time_table[time as usize],
-                        state
+                        .map(|(name, value)| format!("{name}: {value}"))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    diagnostics.warn(
+                        "unknown_state",
+                        format!("bus in unknown state outside reset - {state}"),
+                        edge_time,
                     );
                 }
 
-                usage.add_cycle(type_);
+                usage.add_cycle(edge_time, type_);
             } else {
-                usage.add_cycle(CycleType::Reset);
+                usage.add_cycle(edge_time, CycleType::Reset);
             }
         }
 
+        self.common.set_diagnostics(diagnostics);
         self.result = Some(BusUsage::SingleChannel(usage));
         Ok(())
     }
@@ -4,7 +4,7 @@ use super::private::AnalyzerInternal;
 use crate::analyze::{
     analyzer::axi_analyzer::ReadyValidTransactionIterator,
     bus::{BusCommon, SignalPath, SignalPathFromYaml, is_value_of_type},
-    plugins::load_python_plugin,
+    plugins::{init_python, load_python_plugin},
 };
 use libbusperf::bus_usage::{BusUsage, MultiChannelBusUsage, RealTime};
 use owo_colors::OwoColorize;
@@ -34,11 +34,13 @@ struct Transaction {
     resp_time: RealTime,
     resp: String,
     next_start: RealTime,
+    id: Option<String>,
 }
 
 #[pymethods]
 impl Transaction {
     #[new]
+    #[pyo3(signature = (start, first_data, last_data, resp_time, resp, next_start, id=None))]
     fn new(
         start: RealTime,
         first_data: RealTime,
@@ -46,6 +48,7 @@ impl Transaction {
         resp_time: RealTime,
         resp: String,
         next_start: RealTime,
+        id: Option<String>,
     ) -> PyResult<Self> {
         Ok(Transaction {
             start,
@@ -54,6 +57,7 @@ impl Transaction {
             resp_time,
             resp,
             next_start,
+            id,
         })
     }
 }
@@ -88,6 +92,7 @@ impl PythonAnalyzer {
         y_rate: f32,
         plugins_path: &str,
     ) -> Result<Self, Box<dyn std::error::Error>> {
+        init_python();
         Python::with_gil(|py| -> PyResult<()> {
             let module = match py.import("sys")?.getattr("modules")?.get_item("busperf") {
                 Ok(module) => module.extract()?,
@@ -173,6 +178,22 @@ impl AnalyzerInternal for PythonAnalyzer {
         self.common.bus_name()
     }
 
+    fn common(&self) -> &BusCommon {
+        &self.common
+    }
+
+    fn common_mut(&mut self) -> &mut BusCommon {
+        &mut self.common
+    }
+
+    fn set_results(&mut self, usage: Option<BusUsage>) {
+        self.result = usage;
+    }
+
+    fn uses_python(&self) -> bool {
+        true
+    }
+
     fn get_signals(&self) -> Vec<&SignalPath> {
         let mut signals = vec![self.common.clk_path(), self.common.rst_path()];
         signals.append(&mut self.signals.iter().flat_map(|(_, path)| path).collect());
@@ -279,9 +300,18 @@ impl AnalyzerInternal for PythonAnalyzer {
                         first_data,
                         resp,
                         next_start: next,
+                        id,
                     } in results
                     {
-                        usage.add_transaction(time, resp_time, last_write, first_data, &resp, next);
+                        usage.add_transaction(
+                            time,
+                            resp_time,
+                            last_write,
+                            first_data,
+                            &resp,
+                            id.as_deref(),
+                            next,
+                        );
                     }
                 }
                 Err(e) => Err(format!(
@@ -14,7 +14,13 @@ use custom_python::PythonCustomBus;
 use wellen::SignalValue;
 use yaml_rust2::Yaml;
 
-use libbusperf::{CycleType, CyclesNum, bus_usage::RealTime};
+use crate::analyze::{
+    SimulationData,
+    intervals::{IntervalSpecs, Phase},
+};
+use libbusperf::{
+    CycleType, CyclesNum, binning::Bins, bus_usage::RealTime, diagnostics::Diagnostics,
+};
 
 pub use libbusperf::SignalPath;
 
@@ -85,7 +91,14 @@ pub struct BusCommon {
     rst_path: SignalPath,
     rst_active_value: u8,
     max_burst_delay: CyclesNum,
+    interval_specs: IntervalSpecs,
     intervals: Vec<[RealTime; 2]>,
+    phase: Option<Phase>,
+    config_hash: Option<blake3::Hash>,
+    diagnostics: Diagnostics,
+    bins: Bins,
+    feeds: Vec<String>,
+    chained: bool,
 }
 
 fn parse_scope(yaml: &Yaml) -> Result<Vec<String>, Box<dyn std::error::Error>> {
@@ -104,42 +117,6 @@ fn parse_scope(yaml: &Yaml) -> Result<Vec<String>, Box<dyn std::error::Error>> {
     }
 }
 
-fn parse_intervals(yaml: &Yaml) -> Result<Vec<[RealTime; 2]>, Box<dyn std::error::Error>> {
-    if let Some(intervals) = yaml["intervals"].as_vec() {
-        let mut intervals = intervals
-            .iter()
-            .map(|i| -> Result<_, Box<dyn std::error::Error>> {
-                let i = i
-                    .as_vec()
-                    .ok_or("interval should be an array of two numbers")?;
-                if i.len() != 2 {
-                    Err("each interval should be a 2 element list, defining start and end")?
-                }
-                if i[0] >= i[1] {
-                    Err("interval start is later than end")?;
-                }
-                Ok([
-                    i[0].as_i64()
-                        .ok_or(format!("interval start should be a number not {:?}", i[0]))?
-                        as u64,
-                    i[1].as_i64()
-                        .ok_or(format!("interval end should be a number not {:?}", i[1]))?
-                        as u64,
-                ])
-            })
-            .collect::<Result<Vec<_>, _>>()?;
-        intervals.sort_by(|a, b| a[0].cmp(&b[0]));
-        Ok(intervals)
-    } else if let Yaml::BadValue = yaml["intervals"] {
-        Ok(vec![])
-    } else {
-        Err(format!(
-            "intervals should be an array of intervals not {:?}",
-            yaml["intervals"]
-        ))?
-    }
-}
-
 impl BusCommon {
     pub fn from_yaml(
         name: String,
@@ -178,36 +155,24 @@ impl BusCommon {
         } else {
             Err("reset type should be \"high\" or \"low\"")?
         };
-        let intervals = parse_intervals(yaml)?;
-
-        Ok(Self::new(
-            name,
-            scope,
-            clk,
-            rst,
-            rst_type,
-            default_max_burst,
-            intervals,
-        ))
-    }
-    pub fn new(
-        bus_name: String,
-        module_scope: Vec<String>,
-        clk_path: SignalPath,
-        rst_path: SignalPath,
-        rst_active_value: u8,
-        max_burst_delay: CyclesNum,
-        intervals: Vec<[RealTime; 2]>,
-    ) -> Self {
-        BusCommon {
-            bus_name,
-            module_scope,
-            clk_path,
-            rst_path,
-            rst_active_value,
-            max_burst_delay,
-            intervals,
-        }
+        let interval_specs = IntervalSpecs::from_yaml(&scope, yaml)?;
+
+        Ok(BusCommon {
+            bus_name: name,
+            module_scope: scope,
+            clk_path: clk,
+            rst_path: rst,
+            rst_active_value: rst_type,
+            max_burst_delay: default_max_burst,
+            interval_specs,
+            intervals: vec![],
+            phase: None,
+            config_hash: None,
+            diagnostics: Diagnostics::default(),
+            bins: Bins::default(),
+            feeds: vec![],
+            chained: false,
+        })
     }
 
     pub fn bus_name(&self) -> &str {
@@ -236,9 +201,86 @@ impl BusCommon {
             _ => ValueType::X,
         }
     }
+    /// Returns analysis intervals in waveform time, valid after [BusCommon::resolve_intervals].
     pub fn intervals(&self) -> &Vec<[RealTime; 2]> {
         &self.intervals
     }
+
+    /// Calculates intervals given in yaml in waveform time of the trace.
+    pub fn resolve_intervals(
+        &mut self,
+        simulation_data: &mut SimulationData,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let intervals = self.interval_specs.resolve(simulation_data)?;
+        self.intervals = match &self.phase {
+            Some(phase) => phase.restrict(intervals, simulation_data)?,
+            None => intervals,
+        };
+        Ok(())
+    }
+
+    /// Returns signals used to calculate intervals of the bus and its phase.
+    pub fn interval_signals(&self) -> Vec<&SignalPath> {
+        let mut signals = self.interval_specs.signals();
+        if let Some(phase) = &self.phase {
+            signals.extend(phase.signals());
+        }
+        signals
+    }
+
+    pub fn phase(&self) -> Option<&str> {
+        self.phase.as_ref().map(|p| p.name())
+    }
+
+    /// Returns hash of everything that affects results of the analysis, apart from the trace.
+    pub fn config_hash(&self) -> Option<&blake3::Hash> {
+        self.config_hash.as_ref()
+    }
+
+    pub fn set_config_hash(&mut self, hash: blake3::Hash) {
+        self.config_hash = Some(hash);
+    }
+
+    /// Returns diagnostics of the last analysis.
+    pub fn diagnostics(&self) -> &Diagnostics {
+        &self.diagnostics
+    }
+
+    pub fn set_diagnostics(&mut self, diagnostics: Diagnostics) {
+        self.diagnostics = diagnostics;
+    }
+
+    /// Returns binning of bucket statistics of the bus.
+    pub fn bins(&self) -> &Bins {
+        &self.bins
+    }
+
+    pub fn set_bins(&mut self, bins: Bins) {
+        self.bins = bins;
+    }
+
+    /// Returns names of buses to which the bus passes data.
+    pub fn feeds(&self) -> &[String] {
+        &self.feeds
+    }
+
+    pub fn set_feeds(&mut self, feeds: Vec<String>) {
+        self.feeds = feeds;
+    }
+
+    /// Returns whether the bus feeds or is fed by another bus, so its states are recorded.
+    pub fn chained(&self) -> bool {
+        self.chained
+    }
+
+    pub fn set_chained(&mut self) {
+        self.chained = true;
+    }
+
+    /// Limits the analysis to the given phase.
+    pub fn set_phase(&mut self, phase: Phase) {
+        self.phase = Some(phase);
+    }
 }
 
 pub struct BusDescriptionBuilder {}
@@ -286,12 +328,22 @@ impl BusDescriptionBuilder {
     }
 }
 
-pub trait BusDescription {
+pub trait BusDescription: Send {
     fn signals(&self) -> Vec<&SignalPath>;
-    fn interpret_cycle(&self, signals: &[SignalValue], time: u32) -> CycleType;
+    // Problems with the cycle at waveform time `time` are reported in `diagnostics`
+    fn interpret_cycle(
+        &self,
+        signals: &[SignalValue],
+        time: RealTime,
+        diagnostics: &mut Diagnostics,
+    ) -> CycleType;
+    // Buses interpreted by Python plugins have to hold the GIL
+    fn uses_python(&self) -> bool {
+        false
+    }
 }
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ValueType {
     V0,
     V1,
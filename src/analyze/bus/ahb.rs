@@ -2,7 +2,7 @@ use wellen::SignalValue;
 use yaml_rust2::Yaml;
 
 use super::{BusDescription, SignalPath, ValueType, bus_from_yaml, get_value};
-use libbusperf::CycleType;
+use libbusperf::{CycleType, bus_usage::RealTime, diagnostics::Diagnostics};
 
 #[derive(Debug)]
 pub struct AHBBus {
@@ -22,7 +22,12 @@ impl BusDescription for AHBBus {
         vec![&self.htrans, &self.hready]
     }
 
-    fn interpret_cycle(&self, signals: &[SignalValue<'_>], time: u32) -> CycleType {
+    fn interpret_cycle(
+        &self,
+        signals: &[SignalValue<'_>],
+        time: RealTime,
+        diagnostics: &mut Diagnostics,
+    ) -> CycleType {
         let htrans = signals[0];
         let hready = signals[1];
         if let SignalValue::Binary(htrans_v, 2) = htrans
@@ -41,26 +46,17 @@ impl BusDescription for AHBBus {
                 (0b00, V1) => CycleType::Free,
                 (0b01, V1) => CycleType::NoData,
                 (0b00, V0) | (0b01, V0) => {
-                    eprintln!(
-                        "ahb bus in disallowed state htrans: {} hready: {}, time: {}",
-                        htrans, hready, time
+                    diagnostics.warn(
+                        "disallowed_state",
+                        format!("ahb bus in disallowed state htrans: {htrans} hready: {hready}"),
+                        time,
                     );
                     CycleType::Backpressure
                 }
                 (_, V0) => CycleType::Backpressure,
-                _ => {
-                    eprintln!(
-                        "signal has invalid value hready: {} htrans: {}",
-                        hready, htrans
-                    );
-                    CycleType::Unknown
-                }
+                _ => CycleType::Unknown,
             }
         } else {
-            eprintln!(
-                "bus in unknown state outside reset hready: {}, htrans: {}",
-                hready, htrans
-            );
             CycleType::Unknown
         }
     }
@@ -1,5 +1,5 @@
 use super::{BusDescription, SignalPath, ValueType, bus_from_yaml, get_value};
-use libbusperf::CycleType;
+use libbusperf::{CycleType, bus_usage::RealTime, diagnostics::Diagnostics};
 
 use wellen::SignalValue;
 use yaml_rust2::Yaml;
@@ -26,7 +26,12 @@ impl BusDescription for APBBus {
         vec![&self.psel, &self.penable, &self.pready]
     }
 
-    fn interpret_cycle(&self, signals: &[SignalValue<'_>], _time: u32) -> CycleType {
+    fn interpret_cycle(
+        &self,
+        signals: &[SignalValue<'_>],
+        _time: RealTime,
+        _diagnostics: &mut Diagnostics,
+    ) -> CycleType {
         let psel = signals[0];
         let penable = signals[1];
         let pready = signals[2];
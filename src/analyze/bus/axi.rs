@@ -2,7 +2,7 @@ use wellen::SignalValue;
 use yaml_rust2::Yaml;
 
 use super::{BusDescription, SignalPath, ValueType, bus_from_yaml, is_value_of_type};
-use libbusperf::CycleType;
+use libbusperf::{CycleType, bus_usage::RealTime, diagnostics::Diagnostics};
 
 #[derive(Debug)]
 pub struct AXIBus {
@@ -22,7 +22,12 @@ impl BusDescription for AXIBus {
         vec![&self.ready, &self.valid]
     }
 
-    fn interpret_cycle(&self, signals: &[SignalValue<'_>], _time: u32) -> CycleType {
+    fn interpret_cycle(
+        &self,
+        signals: &[SignalValue<'_>],
+        _time: RealTime,
+        _diagnostics: &mut Diagnostics,
+    ) -> CycleType {
         let ready = signals[0];
         let valid = signals[1];
         match (
@@ -3,7 +3,7 @@ use wellen::SignalValue;
 use yaml_rust2::Yaml;
 
 use super::{BusDescription, SignalPath, ValueType, bus_from_yaml, get_value};
-use libbusperf::CycleType;
+use libbusperf::{CycleType, bus_usage::RealTime, diagnostics::Diagnostics};
 
 #[derive(Debug)]
 pub struct CreditValidBus {
@@ -28,7 +28,12 @@ impl BusDescription for CreditValidBus {
         vec![&self.credit, &self.valid]
     }
 
-    fn interpret_cycle(&self, signals: &[SignalValue<'_>], time: u32) -> CycleType {
+    fn interpret_cycle(
+        &self,
+        signals: &[SignalValue<'_>],
+        time: RealTime,
+        diagnostics: &mut Diagnostics,
+    ) -> CycleType {
         let credit = signals[0];
         let valid = signals[1];
         if let Some(credit_v) = get_value(credit)
@@ -46,26 +51,13 @@ impl BusDescription for CreditValidBus {
                 }
                 (1.., V0) => CycleType::Free,
                 (0, V1) => {
-                    eprintln!(
-                        "[WARN] credit is 0 and valid 1 on credit/valid bus time: {}",
-                        time
-                    );
+                    diagnostics.warn("valid_without_credit", "credit is 0 and valid 1", time);
                     CycleType::Busy
                 }
                 (0, V0) => CycleType::NoTransaction,
-                _ => {
-                    eprintln!(
-                        "[WARN] signal has invalid value credit: {} valid: {}",
-                        credit, valid
-                    );
-                    CycleType::Unknown
-                }
+                _ => CycleType::Unknown,
             }
         } else {
-            eprintln!(
-                "[WARN] bus in unknown state outside reset credit: {}, valid: {}",
-                credit, valid
-            );
             CycleType::Unknown
         }
     }
@@ -1,8 +1,8 @@
 use crate::analyze::bus::{SignalPath, SignalPathFromYaml};
-use crate::analyze::plugins::load_python_plugin;
+use crate::analyze::plugins::{init_python, load_python_plugin};
 
 use super::BusDescription;
-use owo_colors::OwoColorize;
+use libbusperf::{bus_usage::RealTime, diagnostics::Diagnostics};
 use pyo3::{
     prelude::*,
     types::{PyList, PyTuple},
@@ -48,6 +48,7 @@ impl PythonCustomBus {
         bus_scope: &[String],
         plugins_path: &str,
     ) -> Result<Self, Box<dyn std::error::Error>> {
+        init_python();
         Python::with_gil(|py| -> PyResult<()> {
             let module = match py.import("sys")?.getattr("modules")?.get_item("busperf") {
                 Ok(module) => module.extract()?,
@@ -78,20 +79,22 @@ impl BusDescription for PythonCustomBus {
         self.signals.iter().collect()
     }
 
-    fn interpret_cycle(&self, signals: &[SignalValue<'_>], _time: u32) -> libbusperf::CycleType {
-        let signals: Vec<String> = match signals
+    fn uses_python(&self) -> bool {
+        true
+    }
+
+    fn interpret_cycle(
+        &self,
+        signals: &[SignalValue<'_>],
+        time: RealTime,
+        diagnostics: &mut Diagnostics,
+    ) -> libbusperf::CycleType {
+        let Some(signals) = signals
             .iter()
-            .map(|s| {
-                s.to_bit_string()
-                    .ok_or(format!("invalid signal value at {}", _time))
-            })
-            .collect::<Result<_, _>>()
-        {
-            Ok(signals) => signals,
-            Err(e) => {
-                eprintln!("{e}");
-                return libbusperf::CycleType::Unknown;
-            }
+            .map(|s| s.to_bit_string())
+            .collect::<Option<Vec<_>>>()
+        else {
+            return libbusperf::CycleType::Unknown;
         };
 
         Python::with_gil(|py| -> PyResult<CycleType> {
@@ -104,10 +107,10 @@ impl BusDescription for PythonCustomBus {
             Ok(*o.borrow(py))
         })
         .unwrap_or_else(|e| {
-            eprintln!(
-                "{} {}",
-                "[ERROR] Python returned bad result".bright_red(),
-                e.bright_red()
+            diagnostics.error(
+                "plugin_error",
+                format!("Python returned bad result {e}"),
+                time,
             );
             CycleType::Unknown
         })
@@ -0,0 +1,79 @@
+use std::{error::Error, path::PathBuf};
+
+use blake3::Hash;
+use libbusperf::{bus_usage::BusUsage, calculate_file_hash, diagnostics::Diagnostics};
+
+use crate::analyze::analyzer::Analyzer;
+
+/// Results of analyses saved on disk.
+///
+/// Each result is stored in a separate file named after the hash of the trace and the hash of the
+/// bus configuration, so only buses with a changed description or parameters are analyzed again.
+pub struct ResultCache {
+    dir: PathBuf,
+    trace_hash: Hash,
+}
+
+impl ResultCache {
+    /// Opens cache in directory `dir` (creating it if needed) for results of trace `trace`.
+    pub fn new(dir: &str, trace: &str) -> Result<Self, Box<dyn Error>> {
+        std::fs::create_dir_all(dir).map_err(|e| format!("failed to create cache {dir}: {e}"))?;
+        let trace_hash = calculate_file_hash(trace)
+            .map_err(|e| format!("failed to calculate trace hash: {e}"))?;
+        Ok(ResultCache {
+            dir: PathBuf::from(dir),
+            trace_hash,
+        })
+    }
+
+    fn path(&self, analyzer: &dyn Analyzer) -> Option<PathBuf> {
+        let config_hash = analyzer.common().config_hash()?;
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(self.trace_hash.as_bytes());
+        hasher.update(config_hash.as_bytes());
+        // States of the bus are recorded only if other buses are chained with it
+        hasher.update(&[analyzer.common().chained() as u8]);
+        Some(self.dir.join(format!("{}.bin", hasher.finalize())))
+    }
+
+    /// Sets results of analyzers found in the cache. Returns number of such analyzers.
+    pub fn load(&self, analyzers: &mut [Box<dyn Analyzer>]) -> usize {
+        let config = bincode::config::standard();
+        let mut loaded = 0;
+        for analyzer in analyzers.iter_mut().filter(|a| !a.finished_analysis()) {
+            let Some(data) = self
+                .path(analyzer.as_ref())
+                .and_then(|path| std::fs::read(path).ok())
+            else {
+                continue;
+            };
+            // Entries that cannot be decoded, e.g. saved by another version, are analyzed again
+            if let Ok(((usage, diagnostics), _)) =
+                bincode::decode_from_slice::<(BusUsage, Diagnostics), _>(&data, config)
+            {
+                analyzer.common_mut().set_diagnostics(diagnostics);
+                analyzer.set_results(Some(usage));
+                loaded += 1;
+            }
+        }
+        loaded
+    }
+
+    /// Saves results of analyzers which are not yet in the cache.
+    pub fn store(&self, analyzers: &[Box<dyn Analyzer>]) -> Result<(), Box<dyn Error>> {
+        let config = bincode::config::standard();
+        for analyzer in analyzers {
+            let (Some(usage), Some(path)) = (analyzer.get_results(), self.path(analyzer.as_ref()))
+            else {
+                continue;
+            };
+            if !path.exists() {
+                let data = bincode::encode_to_vec((usage, analyzer.diagnostics()), config)
+                    .map_err(|_| "Serialization failed")?;
+                std::fs::write(&path, data)
+                    .map_err(|e| format!("failed to write cache {}: {e}", path.display()))?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,162 @@
+use std::{
+    error::Error,
+    fs::File,
+    io::{BufReader, Cursor, Read, Write},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::analyze::{
+    SimulationData,
+    analyzer::{Analyzer, analyze_all},
+    bus::SignalPath,
+    read_seekable_trace,
+    stream::{Tokens, filter_body, filter_header},
+};
+
+// How long to wait before checking again for data appended to the trace
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+// Each update analyzes the whole trace read so far, so it waits until the trace grows by at least
+// 1/UPDATE_GROWTH since the previous one, which keeps the total work linear in size of the trace
+const UPDATE_GROWTH: usize = 10;
+
+// Input that is still being written. Reads wait for more data, until the writer closes a pipe or
+// nothing is appended for `idle_timeout`.
+struct Follow<R: Read> {
+    input: R,
+    ends_at_eof: bool,
+    idle_timeout: Duration,
+    last_data: Instant,
+}
+
+impl<R: Read> Read for Follow<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        loop {
+            let read = self.input.read(buf)?;
+            if read > 0 || buf.is_empty() {
+                self.last_data = Instant::now();
+                return Ok(read);
+            }
+            if self.ends_at_eof || self.last_data.elapsed() >= self.idle_timeout {
+                return Ok(0);
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
+
+// Filtered trace shared between the reading thread and the analysis
+#[derive(Clone, Default)]
+struct Shared(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Shared {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+fn open_followed(filename: &str) -> Result<(Box<dyn Read + Send>, bool), Box<dyn Error>> {
+    if filename == "-" {
+        return Ok((Box::new(std::io::stdin()), true));
+    }
+    let file = File::open(filename).map_err(|e| format!("failed to open {filename}: {e}"))?;
+    #[cfg(unix)]
+    let is_pipe = {
+        use std::os::unix::fs::FileTypeExt;
+        file.metadata()?.file_type().is_fifo()
+    };
+    #[cfg(not(unix))]
+    let is_pipe = false;
+    Ok((Box::new(file), is_pipe))
+}
+
+/// Analyzes a VCD trace while it is being written.
+///
+/// The trace is read from file `filename`, a named pipe or standard input (`-`), keeping only
+/// signals used by the `analyzers`. Every `refresh` the analysis is repeated on the part of the
+/// trace read so far and `update` is called with the analyzers, their results, loaded trace and
+/// whether the whole trace was read. As the analysis starts from the beginning of the trace each
+/// time, updates are skipped until the filtered trace grows by a tenth. A file ends when nothing
+/// is appended to it for `idle_timeout`, a pipe when it is closed by the writer.
+pub fn follow_simulation_trace(
+    filename: &str,
+    analyzers: &mut [Box<dyn Analyzer>],
+    refresh: Duration,
+    idle_timeout: Duration,
+    verbose: bool,
+    mut update: impl FnMut(
+        &[Box<dyn Analyzer>],
+        Vec<Result<(), String>>,
+        &SimulationData,
+        bool,
+    ) -> Result<(), Box<dyn Error>>,
+) -> Result<(), Box<dyn Error>> {
+    let (input, ends_at_eof) = open_followed(filename)?;
+    let input = Follow {
+        input,
+        ends_at_eof,
+        idle_timeout,
+        last_data: Instant::now(),
+    };
+    let signals: Vec<SignalPath> = analyzers
+        .iter()
+        .flat_map(|a| a.required_signals())
+        .cloned()
+        .collect();
+    let filtered = Shared::default();
+
+    std::thread::scope(|scope| {
+        let mut out = filtered.clone();
+        let reader = scope.spawn(move || -> Result<(), String> {
+            let mut tokens = Tokens::new(BufReader::new(input));
+            let signals: Vec<&SignalPath> = signals.iter().collect();
+            let read = filter_header(&mut tokens, &signals).and_then(|(header, ids)| {
+                out.write_all(&header)?;
+                filter_body(&mut tokens, &ids, &mut out)
+            });
+            read.map(|_| ()).map_err(|e| e.to_string())
+        });
+
+        let mut analyze = |data: Vec<u8>, finished: bool| -> Result<(), Box<dyn Error>> {
+            let start = Instant::now();
+            let mut simulation_data = read_seekable_trace(Cursor::new(data))?;
+            if verbose {
+                println!("Loading followed trace took {:?}", start.elapsed());
+            }
+            for analyzer in analyzers.iter_mut() {
+                analyzer.set_results(None);
+            }
+            let results = analyze_all(analyzers, &mut simulation_data, verbose);
+            update(analyzers, results, &simulation_data, finished)
+        };
+
+        let mut analyzed_len = 0;
+        loop {
+            let start = Instant::now();
+            while start.elapsed() < refresh && !reader.is_finished() {
+                std::thread::sleep(POLL_INTERVAL.min(refresh));
+            }
+            if reader.is_finished() {
+                break;
+            }
+            let data = {
+                let data = filtered.0.lock().unwrap();
+                // Skip if too little was read since the last update, or no value changes were read
+                let grown = data.len() > analyzed_len + analyzed_len / UPDATE_GROWTH;
+                (grown && !data.ends_with(b"$enddefinitions $end\n")).then(|| data.clone())
+            };
+            if let Some(data) = data {
+                analyzed_len = data.len();
+                analyze(data, false)?;
+            }
+        }
+        reader.join().map_err(|_| "trace reader panicked")??;
+        let data = std::mem::take(&mut *filtered.0.lock().unwrap());
+        analyze(data, true)
+    })
+}
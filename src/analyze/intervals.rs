@@ -0,0 +1,458 @@
+use std::error::Error;
+
+use wellen::Signal;
+use yaml_rust2::Yaml;
+
+use crate::analyze::{
+    SimulationData,
+    bus::{SignalPath, SignalPathFromYaml, ValueType, is_value_of_type},
+    load_signals,
+};
+use libbusperf::{Timescale, bus_usage::RealTime};
+
+/// Time given in yaml, either in waveform time units or with a unit of time.
+#[derive(Debug, Clone, Copy)]
+enum TimeSpec {
+    Waveform(RealTime),
+    // Value and exponent of its unit
+    WithUnit(f64, i8),
+}
+
+impl TimeSpec {
+    fn from_yaml(yaml: &Yaml) -> Option<Self> {
+        match yaml {
+            Yaml::Integer(i) => u64::try_from(*i).ok().map(TimeSpec::Waveform),
+            Yaml::String(s) => {
+                let s = s.trim();
+                if let Ok(time) = s.parse() {
+                    return Some(TimeSpec::Waveform(time));
+                }
+                let split = s.find(|c: char| c.is_ascii_alphabetic())?;
+                let (value, unit) = s.split_at(split);
+                let value: f64 = value.trim().parse().ok()?;
+                if value < 0.0 {
+                    return None;
+                }
+                Some(TimeSpec::WithUnit(value, Timescale::unit_exponent(unit)?))
+            }
+            _ => None,
+        }
+    }
+
+    fn resolve(&self, timescale: Option<Timescale>) -> Result<RealTime, Box<dyn Error>> {
+        match *self {
+            TimeSpec::Waveform(time) => Ok(time),
+            TimeSpec::WithUnit(value, exponent) => Ok(timescale
+                .ok_or("trace has no timescale, intervals have to be given in waveform time units")?
+                .to_waveform_time(value, exponent)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Edge {
+    Rising,
+    Falling,
+}
+
+/// Start or end of an interval, either a fixed time or every edge of a signal.
+#[derive(Debug, Clone)]
+enum Boundary {
+    Time(TimeSpec),
+    Edge(Edge, SignalPath),
+}
+
+impl Boundary {
+    fn from_yaml(scope: &[String], yaml: &Yaml) -> Result<Self, Box<dyn Error>> {
+        if let Some(time) = TimeSpec::from_yaml(yaml) {
+            return Ok(Boundary::Time(time));
+        }
+        let hash = yaml.as_hash().filter(|h| h.len() == 1).ok_or(format!(
+            "should be a time, rising or falling edge not {yaml:?}"
+        ))?;
+        let (edge, signal) = hash.front().expect("hash has one element");
+        let edge = match edge.as_str() {
+            Some("rising") => Edge::Rising,
+            Some("falling") => Edge::Falling,
+            _ => Err(format!("edge should be rising or falling not {edge:?}"))?,
+        };
+        let signal = SignalPathFromYaml::from_yaml_ref_with_prefix(scope, signal)
+            .map_err(|e| format!("{edge:?} edge signal {e}"))?;
+        Ok(Boundary::Edge(edge, signal))
+    }
+}
+
+#[derive(Debug, Clone)]
+enum IntervalSpec {
+    Fixed([TimeSpec; 2]),
+    // Starts at each `from` and lasts until the first `to` that follows it
+    Events {
+        from: Boundary,
+        to: Option<Boundary>,
+    },
+    // Lasts while the signal has given value
+    While {
+        signal: SignalPath,
+        level: ValueType,
+    },
+}
+
+impl IntervalSpec {
+    fn from_yaml(scope: &[String], yaml: &Yaml) -> Result<Self, Box<dyn Error>> {
+        match yaml {
+            Yaml::Array(i) => {
+                if i.len() != 2 {
+                    Err("each interval should be a 2 element list, defining start and end")?
+                }
+                Ok(IntervalSpec::Fixed([
+                    TimeSpec::from_yaml(&i[0])
+                        .ok_or(format!("interval start should be a time not {:?}", i[0]))?,
+                    TimeSpec::from_yaml(&i[1])
+                        .ok_or(format!("interval end should be a time not {:?}", i[1]))?,
+                ]))
+            }
+            Yaml::Hash(hash) => {
+                if let Some(unknown) = hash
+                    .keys()
+                    .find(|k| !matches!(k.as_str(), Some("from" | "to" | "while" | "level")))
+                {
+                    Err(format!("unknown interval key {unknown:?}"))?
+                }
+                match (&yaml["from"], &yaml["while"]) {
+                    (Yaml::BadValue, Yaml::BadValue) => {
+                        Err("interval should define either from or while")?
+                    }
+                    (from, Yaml::BadValue) => {
+                        if !matches!(yaml["level"], Yaml::BadValue) {
+                            Err("level can only be used with while")?
+                        }
+                        Ok(IntervalSpec::Events {
+                            from: Boundary::from_yaml(scope, from)
+                                .map_err(|e| format!("interval from {e}"))?,
+                            to: match &yaml["to"] {
+                                Yaml::BadValue => None,
+                                to => Some(
+                                    Boundary::from_yaml(scope, to)
+                                        .map_err(|e| format!("interval to {e}"))?,
+                                ),
+                            },
+                        })
+                    }
+                    (Yaml::BadValue, signal) => {
+                        if !matches!(yaml["to"], Yaml::BadValue) {
+                            Err("to can only be used with from")?
+                        }
+                        Ok(IntervalSpec::While {
+                            signal: SignalPathFromYaml::from_yaml_ref_with_prefix(scope, signal)
+                                .map_err(|e| format!("interval while signal {e}"))?,
+                            level: match yaml["level"].as_str() {
+                                None if matches!(yaml["level"], Yaml::BadValue) => ValueType::V1,
+                                Some("high") => ValueType::V1,
+                                Some("low") => ValueType::V0,
+                                _ => Err("interval level should be \"high\" or \"low\"")?,
+                            },
+                        })
+                    }
+                    _ => Err("interval cannot define both from and while")?,
+                }
+            }
+            _ => Err(format!(
+                "interval should be a [start, end] list or a mapping not {yaml:?}"
+            ))?,
+        }
+    }
+
+    fn signals(&self) -> Vec<&SignalPath> {
+        let mut signals = vec![];
+        match self {
+            IntervalSpec::Fixed(_) => (),
+            IntervalSpec::Events { from, to } => {
+                for boundary in [Some(from), to.as_ref()].into_iter().flatten() {
+                    if let Boundary::Edge(_, signal) = boundary {
+                        signals.push(signal);
+                    }
+                }
+            }
+            IntervalSpec::While { signal, .. } => signals.push(signal),
+        }
+        signals
+    }
+}
+
+/// Intervals of a bus as defined in its yaml `intervals` and `exclude_intervals`.
+#[derive(Debug, Clone)]
+pub struct IntervalSpecs {
+    include: Vec<IntervalSpec>,
+    exclude: Vec<IntervalSpec>,
+}
+
+fn parse_interval_list(
+    scope: &[String],
+    yaml: &Yaml,
+    key: &str,
+) -> Result<Vec<IntervalSpec>, Box<dyn Error>> {
+    match &yaml[key] {
+        Yaml::Array(intervals) => intervals
+            .iter()
+            .map(|i| IntervalSpec::from_yaml(scope, i))
+            .collect(),
+        Yaml::BadValue => Ok(vec![]),
+        other => Err(format!(
+            "{key} should be an array of intervals not {other:?}"
+        ))?,
+    }
+}
+
+// Changes of a single bit signal between 0 and 1 as (time, new value)
+fn level_changes<'a>(
+    signal: &'a Signal,
+    time_table: &'a [RealTime],
+) -> impl Iterator<Item = (RealTime, bool)> + 'a {
+    let mut previous = None;
+    signal.iter_changes().filter_map(move |(idx, value)| {
+        let value = if is_value_of_type(value, ValueType::V1) {
+            Some(true)
+        } else if is_value_of_type(value, ValueType::V0) {
+            Some(false)
+        } else {
+            None
+        };
+        let changed = value.is_some() && previous.is_some() && value != previous;
+        previous = value.or(previous);
+        changed.then(|| (time_table[idx as usize], value.expect("checked above")))
+    })
+}
+
+fn edges(signal: &Signal, time_table: &[RealTime], edge: Edge) -> Vec<RealTime> {
+    let rising = edge == Edge::Rising;
+    level_changes(signal, time_table)
+        .filter(|(_, value)| *value == rising)
+        .map(|(time, _)| time)
+        .collect()
+}
+
+// Returns parts of time covered by both lists of sorted and not overlapping intervals
+fn intersect(a: &[[RealTime; 2]], b: &[[RealTime; 2]]) -> Vec<[RealTime; 2]> {
+    let mut intervals = vec![];
+    for &[a_start, a_end] in a {
+        for &[b_start, b_end] in b {
+            let (start, end) = (a_start.max(b_start), a_end.min(b_end));
+            if start < end {
+                intervals.push([start, end]);
+            }
+        }
+    }
+    intervals
+}
+
+// Sorts intervals and joins the ones that overlap
+fn merge(mut intervals: Vec<[RealTime; 2]>) -> Vec<[RealTime; 2]> {
+    intervals.sort_by(|a, b| a[0].cmp(&b[0]));
+    let mut merged: Vec<[RealTime; 2]> = vec![];
+    for [start, end] in intervals {
+        match merged.last_mut() {
+            Some(last) if start <= last[1] => last[1] = last[1].max(end),
+            _ => merged.push([start, end]),
+        }
+    }
+    merged
+}
+
+impl IntervalSpecs {
+    pub fn from_yaml(scope: &[String], yaml: &Yaml) -> Result<Self, Box<dyn Error>> {
+        Ok(IntervalSpecs {
+            include: parse_interval_list(scope, yaml, "intervals")?,
+            exclude: parse_interval_list(scope, yaml, "exclude_intervals")?,
+        })
+    }
+
+    /// Returns signals on which the intervals depend.
+    pub fn signals(&self) -> Vec<&SignalPath> {
+        self.include
+            .iter()
+            .chain(self.exclude.iter())
+            .flat_map(|i| i.signals())
+            .collect()
+    }
+
+    /// Calculates intervals in waveform time. Returns an empty list if whole trace should be analyzed.
+    pub fn resolve(
+        &self,
+        simulation_data: &mut SimulationData,
+    ) -> Result<Vec<[RealTime; 2]>, Box<dyn Error>> {
+        if self.include.is_empty() && self.exclude.is_empty() {
+            return Ok(vec![]);
+        }
+        let timescale = simulation_data.timescale();
+        let signal_paths = self.signals();
+        let mut buffer = Vec::new();
+        let loaded = load_signals(simulation_data, &signal_paths, &mut buffer)
+            .map_err(|e| format!("interval {e}"))?;
+        let time_table = &simulation_data.body.time_table;
+        let trace_end = *time_table.last().ok_or("trace is empty")?;
+
+        let mut loaded = loaded.into_iter().map(|(_, signal)| signal);
+        let mut resolve = |specs: &[IntervalSpec]| -> Result<_, Box<dyn Error>> {
+            let mut intervals = vec![];
+            for spec in specs {
+                match spec {
+                    IntervalSpec::Fixed([start, end]) => {
+                        let (start, end) = (start.resolve(timescale)?, end.resolve(timescale)?);
+                        if start >= end {
+                            Err("interval start is later than end")?;
+                        }
+                        intervals.push([start, end]);
+                    }
+                    IntervalSpec::While { level, .. } => {
+                        let signal = loaded.next().expect("signal loaded for each path");
+                        let high = matches!(level, ValueType::V1);
+                        let mut start = None;
+                        if let Some((idx, value)) = signal.iter_changes().next()
+                            && is_value_of_type(value, *level)
+                        {
+                            start = Some(time_table[idx as usize]);
+                        }
+                        for (time, value) in level_changes(signal, time_table) {
+                            match (value == high, start) {
+                                (true, None) => start = Some(time),
+                                (false, Some(s)) => {
+                                    intervals.push([s, time]);
+                                    start = None;
+                                }
+                                _ => (),
+                            }
+                        }
+                        if let Some(start) = start {
+                            intervals.push([start, trace_end]);
+                        }
+                    }
+                    IntervalSpec::Events { from, to } => {
+                        let mut times = |boundary: &Boundary| -> Result<_, Box<dyn Error>> {
+                            Ok(match boundary {
+                                Boundary::Time(time) => vec![time.resolve(timescale)?],
+                                Boundary::Edge(edge, _) => edges(
+                                    loaded.next().expect("signal loaded for each path"),
+                                    time_table,
+                                    *edge,
+                                ),
+                            })
+                        };
+                        let starts = times(from)?;
+                        let ends = match to {
+                            Some(to) => times(to)?,
+                            None => vec![],
+                        };
+                        let mut last_end = None;
+                        for start in starts {
+                            if last_end.is_some_and(|e| start < e) {
+                                continue;
+                            }
+                            match ends.iter().find(|&&end| end > start) {
+                                Some(&end) => {
+                                    intervals.push([start, end]);
+                                    last_end = Some(end);
+                                }
+                                None => {
+                                    intervals.push([start, trace_end]);
+                                    break;
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            Ok(intervals)
+        };
+        let include = resolve(&self.include)?;
+        let exclude = merge(resolve(&self.exclude)?);
+        let include = if self.include.is_empty() {
+            vec![[0, trace_end]]
+        } else {
+            merge(include)
+        };
+
+        let mut intervals = vec![];
+        for [mut start, end] in include {
+            for &[ex_start, ex_end] in exclude.iter() {
+                if ex_end <= start || ex_start >= end {
+                    continue;
+                }
+                if ex_start > start {
+                    intervals.push([start, ex_start]);
+                }
+                start = start.max(ex_end);
+            }
+            if start < end {
+                intervals.push([start, end]);
+            }
+        }
+        if intervals.is_empty() {
+            Err("intervals do not cover any part of the trace")?
+        }
+        Ok(intervals)
+    }
+}
+
+/// Named part of the trace defined in the top level `phases` of yaml, each bus is analyzed
+/// separately in every phase.
+#[derive(Debug, Clone)]
+pub struct Phase {
+    name: String,
+    specs: IntervalSpecs,
+}
+
+impl Phase {
+    pub fn from_yaml(name: &Yaml, yaml: &Yaml) -> Result<Self, Box<dyn Error>> {
+        let name = name.as_str().ok_or("Each phase should have a name")?;
+        let hash = yaml.as_hash().ok_or(format!(
+            "phase {name} should be a mapping with intervals and/or exclude_intervals"
+        ))?;
+        if let Some(unknown) = hash
+            .keys()
+            .find(|k| !matches!(k.as_str(), Some("intervals" | "exclude_intervals")))
+        {
+            Err(format!("phase {name} has unknown key {unknown:?}"))?
+        }
+        // Signals of phases are not relative to any bus, so their paths start at the top of the trace
+        let specs =
+            IntervalSpecs::from_yaml(&[], yaml).map_err(|e| format!("phase {name}, {e}"))?;
+        if specs.include.is_empty() && specs.exclude.is_empty() {
+            Err(format!("phase {name} should define intervals"))?
+        }
+        Ok(Phase {
+            name: name.to_owned(),
+            specs,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn signals(&self) -> Vec<&SignalPath> {
+        self.specs.signals()
+    }
+
+    /// Limits intervals of a bus to the phase. Empty `intervals` mean that whole trace is analyzed.
+    pub fn restrict(
+        &self,
+        intervals: Vec<[RealTime; 2]>,
+        simulation_data: &mut SimulationData,
+    ) -> Result<Vec<[RealTime; 2]>, Box<dyn Error>> {
+        let phase = self
+            .specs
+            .resolve(simulation_data)
+            .map_err(|e| format!("phase {}, {e}", self.name))?;
+        if intervals.is_empty() {
+            return Ok(phase);
+        }
+        let intervals = intersect(&intervals, &phase);
+        if intervals.is_empty() {
+            Err(format!(
+                "phase {} does not overlap intervals of the bus",
+                self.name
+            ))?
+        }
+        Ok(intervals)
+    }
+}
@@ -0,0 +1,308 @@
+use std::{collections::BTreeMap, error::Error};
+
+use roxmltree::{Document, Node};
+use yaml_rust2::{Yaml, yaml::Hash};
+
+/// AMBA bus families that can be imported from IP-XACT bus interfaces.
+#[derive(Clone, Copy)]
+enum AmbaBus {
+    Axi,
+    AxiStream,
+    Ahb,
+    Apb,
+}
+
+impl AmbaBus {
+    fn from_bus_type(name: &str) -> Option<Self> {
+        let name = name.to_ascii_uppercase();
+        if name.contains("STREAM") || name == "AXIS" {
+            Some(AmbaBus::AxiStream)
+        } else if name.starts_with("AXI") {
+            Some(AmbaBus::Axi)
+        } else if name.starts_with("AHB") {
+            Some(AmbaBus::Ahb)
+        } else if name.starts_with("APB") {
+            Some(AmbaBus::Apb)
+        } else {
+            None
+        }
+    }
+}
+
+// Logical AMBA port names mapped to signal keys expected by the analyzers
+const AXI_RD_PORTS: &[(&str, &str)] = &[
+    ("ARID", "ar.id"),
+    ("ARREADY", "ar.ready"),
+    ("ARVALID", "ar.valid"),
+    ("RID", "r.id"),
+    ("RREADY", "r.ready"),
+    ("RVALID", "r.valid"),
+    ("RRESP", "r.resp"),
+    ("RLAST", "r.last"),
+];
+
+const AXI_WR_PORTS: &[(&str, &str)] = &[
+    ("AWID", "aw.id"),
+    ("AWREADY", "aw.ready"),
+    ("AWVALID", "aw.valid"),
+    ("WREADY", "w.ready"),
+    ("WVALID", "w.valid"),
+    ("WLAST", "w.last"),
+    ("BID", "b.id"),
+    ("BREADY", "b.ready"),
+    ("BVALID", "b.valid"),
+    ("BRESP", "b.resp"),
+];
+
+const AXI_STREAM_PORTS: &[(&str, &str)] = &[("TREADY", "ready"), ("TVALID", "valid")];
+
+const AHB_PORTS: &[(&str, &str)] = &[
+    ("HTRANS", "htrans"),
+    ("HREADY", "hready"),
+    ("HREADYOUT", "hready"),
+];
+
+const APB_PORTS: &[(&str, &str)] = &[
+    ("PSEL", "psel"),
+    ("PSELX", "psel"),
+    ("PENABLE", "penable"),
+    ("PREADY", "pready"),
+];
+
+/// Port maps of a single bus interface: logical port name (uppercase) to physical port name.
+type PortMap = BTreeMap<String, String>;
+
+#[derive(Clone)]
+struct ClkRst {
+    clock: String,
+    reset: String,
+    reset_type: &'static str,
+}
+
+impl ClkRst {
+    fn from_port_map(ports: &PortMap) -> Option<Self> {
+        let (_, clock) = ports.iter().find(|(l, _)| l.ends_with("CLK"))?;
+        let (logical, reset) = ports
+            .iter()
+            .find(|(l, _)| l.contains("RESET") || l.starts_with("RST"))?;
+        Some(ClkRst {
+            clock: clock.clone(),
+            reset: reset.clone(),
+            // Active low resets follow the AMBA convention of an "n" suffix
+            reset_type: if logical.ends_with('N') {
+                "low"
+            } else {
+                "high"
+            },
+        })
+    }
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.children()
+        .find(|n| n.is_element() && n.tag_name().name() == name)
+}
+
+fn child_text<'a>(node: Node<'a, '_>, name: &str) -> Option<&'a str> {
+    child(node, name)?.text().map(|t| t.trim())
+}
+
+fn descendants<'a, 'input>(
+    node: Node<'a, 'input>,
+    name: &'static str,
+) -> impl Iterator<Item = Node<'a, 'input>> {
+    node.descendants()
+        .filter(move |n| n.is_element() && n.tag_name().name() == name)
+}
+
+fn parse_port_map(interface: Node) -> Result<PortMap, Box<dyn Error>> {
+    descendants(interface, "portMap")
+        .map(|port_map| {
+            let logical = child(port_map, "logicalPort")
+                .and_then(|p| child_text(p, "name"))
+                .ok_or("portMap without logicalPort name")?;
+            let physical = child(port_map, "physicalPort")
+                .and_then(|p| child_text(p, "name"))
+                .ok_or(format!("logical port {logical} has no physicalPort name"))?;
+            Ok((logical.to_ascii_uppercase(), physical.to_owned()))
+        })
+        .collect()
+}
+
+// Physical ports of SystemVerilog interfaces are referenced with a dot separated path
+fn signal_yaml(physical: &str) -> Yaml {
+    if physical.contains('.') {
+        Yaml::Array(
+            physical
+                .split('.')
+                .map(|s| Yaml::String(s.to_owned()))
+                .collect(),
+        )
+    } else {
+        Yaml::String(physical.to_owned())
+    }
+}
+
+fn insert_path(hash: &mut Hash, path: &str, value: Yaml) {
+    match path.split_once('.') {
+        Some((first, rest)) => {
+            let entry = hash
+                .entry(Yaml::from_str(first))
+                .or_insert_with(|| Yaml::Hash(Hash::new()));
+            if let Yaml::Hash(inner) = entry {
+                insert_path(inner, rest, value);
+            }
+        }
+        None => {
+            hash.insert(Yaml::String(path.to_owned()), value);
+        }
+    }
+}
+
+fn interface_yaml(
+    scope: &[String],
+    clk_rst: &ClkRst,
+    kind: (&str, &str),
+    ports: &PortMap,
+    mapping: &[(&str, &str)],
+) -> Yaml {
+    let mut hash = Hash::new();
+    hash.insert(
+        Yaml::from_str("scope"),
+        Yaml::Array(scope.iter().map(|s| Yaml::String(s.clone())).collect()),
+    );
+    hash.insert(Yaml::from_str("clock"), signal_yaml(&clk_rst.clock));
+    hash.insert(Yaml::from_str("reset"), signal_yaml(&clk_rst.reset));
+    hash.insert(
+        Yaml::from_str("reset_type"),
+        Yaml::from_str(clk_rst.reset_type),
+    );
+    let (key, value) = kind;
+    hash.insert(Yaml::from_str(key), Yaml::from_str(value));
+    for (logical, key) in mapping {
+        if let Some(physical) = ports.get(*logical)
+            && !hash.contains_key(&Yaml::from_str(key))
+        {
+            insert_path(&mut hash, key, signal_yaml(physical));
+        }
+    }
+    Yaml::Hash(hash)
+}
+
+/// Names and descriptions of buses.
+pub type Interfaces = Vec<(Yaml, Yaml)>;
+
+/// Creates bus descriptions equivalent to the yaml `interfaces` section from an IP-XACT component.
+///
+/// Every AMBA bus interface of the component is converted, AXI interfaces are split into read and
+/// write analyzers named `<interface>_rd` and `<interface>_wr`. If `scope` is [None] the component
+/// name is used as the scope of its instance in the trace. Bus interfaces of other types are
+/// skipped and returned as messages with their names and types.
+pub fn interfaces_from_ipxact(
+    xml: &str,
+    scope: Option<&[String]>,
+) -> Result<(Interfaces, Vec<String>), Box<dyn Error>> {
+    let doc = Document::parse(xml)?;
+    let component = doc.root_element();
+    if component.tag_name().name() != "component" {
+        Err("IP-XACT file should describe a component")?
+    }
+    let scope = match scope {
+        Some(scope) => scope.to_vec(),
+        None => vec![
+            child_text(component, "name")
+                .ok_or("IP-XACT component has no name")?
+                .to_owned(),
+        ],
+    };
+
+    let mut clk_rst_ifs = vec![];
+    let mut amba_ifs = vec![];
+    let mut skipped = vec![];
+    for interface in child(component, "busInterfaces")
+        .into_iter()
+        .flat_map(|i| i.children())
+        .filter(|n| n.is_element() && n.tag_name().name() == "busInterface")
+    {
+        let name = child_text(interface, "name").ok_or("bus interface has no name")?;
+        let bus_type = child(interface, "busType")
+            // IP-XACT 1685-2009 qualifies the attributes with the spirit namespace
+            .and_then(|t| t.attributes().find(|a| a.name() == "name"))
+            .map(|a| a.value())
+            .ok_or(format!("bus interface {name} has no busType"))?;
+        let ports = parse_port_map(interface).map_err(|e| format!("bus interface {name}: {e}"))?;
+        match AmbaBus::from_bus_type(bus_type) {
+            Some(bus) => amba_ifs.push((name, bus, ports)),
+            None if ["clock", "reset"].contains(&bus_type.to_ascii_lowercase().as_str()) => {
+                clk_rst_ifs.push(ports)
+            }
+            None => skipped.push(format!(
+                "bus interface {name} of type {bus_type} is not supported"
+            )),
+        }
+    }
+
+    // Clock and reset can also be described by separate bus interfaces of the component
+    let shared_clk_rst = {
+        let mut ports = PortMap::new();
+        clk_rst_ifs.into_iter().for_each(|p| ports.extend(p));
+        ClkRst::from_port_map(&ports)
+    };
+
+    let mut interfaces = vec![];
+    for (name, bus, ports) in amba_ifs {
+        let clk_rst = ClkRst::from_port_map(&ports)
+            .or_else(|| shared_clk_rst.clone())
+            .ok_or(format!("bus interface {name} does not map clock and reset"))?;
+        match bus {
+            AmbaBus::Axi => {
+                if ports.contains_key("ARVALID") {
+                    interfaces.push((
+                        Yaml::String(format!("{name}_rd")),
+                        interface_yaml(
+                            &scope,
+                            &clk_rst,
+                            ("custom_analyzer", "AXIRdAnalyzer"),
+                            &ports,
+                            AXI_RD_PORTS,
+                        ),
+                    ));
+                }
+                if ports.contains_key("AWVALID") {
+                    interfaces.push((
+                        Yaml::String(format!("{name}_wr")),
+                        interface_yaml(
+                            &scope,
+                            &clk_rst,
+                            ("custom_analyzer", "AXIWrAnalyzer"),
+                            &ports,
+                            AXI_WR_PORTS,
+                        ),
+                    ));
+                }
+            }
+            AmbaBus::AxiStream => interfaces.push((
+                Yaml::String(name.to_owned()),
+                interface_yaml(
+                    &scope,
+                    &clk_rst,
+                    ("handshake", "ReadyValid"),
+                    &ports,
+                    AXI_STREAM_PORTS,
+                ),
+            )),
+            AmbaBus::Ahb => interfaces.push((
+                Yaml::String(name.to_owned()),
+                interface_yaml(&scope, &clk_rst, ("handshake", "AHB"), &ports, AHB_PORTS),
+            )),
+            AmbaBus::Apb => interfaces.push((
+                Yaml::String(name.to_owned()),
+                interface_yaml(&scope, &clk_rst, ("handshake", "APB"), &ports, APB_PORTS),
+            )),
+        }
+    }
+    if interfaces.is_empty() {
+        Err("IP-XACT component has no supported bus interfaces")?
+    }
+    Ok((interfaces, skipped))
+}
@@ -2,6 +2,11 @@ use std::{error::Error, ffi::CString, path::PathBuf};
 
 use pyo3::prelude::*;
 
+// Starts the interpreter, unless busperf was loaded by Python as an extension module
+pub fn init_python() {
+    pyo3::prepare_freethreaded_python();
+}
+
 pub fn load_python_plugin(path: &str, class_name: &str) -> Result<Py<PyAny>, Box<dyn Error>> {
     let code = load_python_code(path, class_name)?;
     let obj = Python::with_gil(|py| -> PyResult<Py<PyAny>> {
@@ -19,7 +24,7 @@ pub fn load_python_plugin(path: &str, class_name: &str) -> Result<Py<PyAny>, Box
     Ok(obj)
 }
 
-fn load_python_code(search_path: &str, class_name: &str) -> Result<CString, Box<dyn Error>> {
+pub fn load_python_code(search_path: &str, class_name: &str) -> Result<CString, Box<dyn Error>> {
     // if CARGO_MANIFEST_DIR is set we search in that directory if not we want to search in the location of the binary
     let mut path = match std::env::var("CARGO_MANIFEST_DIR") {
         Ok(path) => PathBuf::from(path),
@@ -0,0 +1,225 @@
+use std::{
+    collections::HashSet,
+    error::Error,
+    io::{BufRead, Cursor, Write},
+};
+
+use wellen::Time;
+
+use crate::analyze::{
+    SimulationData, TraceInput, bus::SignalPath, is_vcd, load_trace_input, open_trace,
+    read_seekable_trace,
+};
+
+// Whitespace separated tokens of a VCD file, read one line at a time
+pub struct Tokens<R: BufRead> {
+    input: R,
+    line: Vec<u8>,
+    pos: usize,
+}
+
+impl<R: BufRead> Tokens<R> {
+    pub fn new(input: R) -> Self {
+        Tokens {
+            input,
+            line: vec![],
+            pos: 0,
+        }
+    }
+
+    fn next(&mut self) -> Result<Option<&[u8]>, Box<dyn Error>> {
+        loop {
+            while self.pos < self.line.len() && self.line[self.pos].is_ascii_whitespace() {
+                self.pos += 1;
+            }
+            if self.pos < self.line.len() {
+                let start = self.pos;
+                while self.pos < self.line.len() && !self.line[self.pos].is_ascii_whitespace() {
+                    self.pos += 1;
+                }
+                return Ok(Some(&self.line[start..self.pos]));
+            }
+            self.line.clear();
+            self.pos = 0;
+            if self.input.read_until(b'\n', &mut self.line)? == 0 {
+                return Ok(None);
+            }
+        }
+    }
+
+    fn expect(&mut self, what: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+        Ok(self
+            .next()?
+            .ok_or(format!("unexpected end of trace, expected {what}"))?
+            .to_vec())
+    }
+
+    // Returns tokens of a command up to its $end
+    fn command(&mut self) -> Result<Vec<Vec<u8>>, Box<dyn Error>> {
+        let mut tokens = vec![];
+        while let Some(token) = self.next()? {
+            if token == b"$end" {
+                return Ok(tokens);
+            }
+            tokens.push(token.to_vec());
+        }
+        Err("unexpected end of trace, expected $end")?
+    }
+}
+
+// Identifier codes of VCD variables
+pub type VarIds = HashSet<Vec<u8>>;
+
+fn write_command(out: &mut Vec<u8>, command: &[u8], args: &[Vec<u8>]) {
+    out.extend_from_slice(command);
+    for arg in args {
+        out.push(b' ');
+        out.extend_from_slice(arg);
+    }
+    out.extend_from_slice(b" $end\n");
+}
+
+// Returns VCD header leaving only variables with given paths, and identifier codes of these variables
+pub fn filter_header<R: BufRead>(
+    tokens: &mut Tokens<R>,
+    paths: &[&SignalPath],
+) -> Result<(Vec<u8>, VarIds), Box<dyn Error>> {
+    let mut out = vec![];
+    let mut scope: Vec<String> = vec![];
+    let mut ids = HashSet::new();
+    while let Some(token) = tokens.next()? {
+        let command = token.to_vec();
+        let args = tokens.command()?;
+        match command.as_slice() {
+            b"$scope" => {
+                let name = args.get(1).ok_or("scope without a name")?;
+                scope.push(String::from_utf8_lossy(name).into_owned());
+            }
+            b"$upscope" => {
+                scope.pop();
+            }
+            b"$var" => {
+                let [_, _, id, name, ..] = args.as_slice() else {
+                    Err("variable definition is too short")?
+                };
+                // Bit index is not a part of the variable name
+                let name = String::from_utf8_lossy(name);
+                let name = match name.find('[') {
+                    Some(index) if name.ends_with(']') => &name[..index],
+                    _ => &name,
+                };
+                if !paths.iter().any(|p| p.name == name && p.scope == scope) {
+                    continue;
+                }
+                ids.insert(id.clone());
+            }
+            b"$enddefinitions" => {
+                write_command(&mut out, &command, &args);
+                return Ok((out, ids));
+            }
+            _ => (),
+        }
+        write_command(&mut out, &command, &args);
+    }
+    Err("trace has no $enddefinitions")?
+}
+
+// Copies value changes of variables with given identifier codes. Time steps without any of these
+// changes are skipped, except for the first and the last one. Each line is written at once, so `out`
+// never ends in the middle of a line. Returns number of copied time steps.
+pub fn filter_body<R: BufRead>(
+    tokens: &mut Tokens<R>,
+    ids: &VarIds,
+    out: &mut impl Write,
+) -> Result<usize, Box<dyn Error>> {
+    let mut time: Time = 0;
+    let mut written: Option<Time> = None;
+    let mut first = true;
+    let mut written_steps = 0;
+    let mut write_time = |out: &mut dyn Write, time: Time| -> Result<(), Box<dyn Error>> {
+        if written != Some(time) {
+            out.write_all(format!("#{time}\n").as_bytes())?;
+            written = Some(time);
+            written_steps += 1;
+        }
+        Ok(())
+    };
+    while let Some(token) = tokens.next()? {
+        let (value, id) = match token[0] {
+            b'#' => {
+                time = std::str::from_utf8(&token[1..])?.parse()?;
+                if first {
+                    write_time(out, time)?;
+                    first = false;
+                }
+                continue;
+            }
+            b'$' => {
+                if token == b"$comment" {
+                    tokens.command()?;
+                }
+                // $dumpvars, $dumpall, $dumpon, $dumpoff and their $end
+                continue;
+            }
+            b'b' | b'B' | b'r' | b'R' | b's' | b'S' => {
+                let value = token.to_vec();
+                (value, tokens.expect("value change identifier")?)
+            }
+            _ => (token[..1].to_vec(), token[1..].to_vec()),
+        };
+        if ids.contains(&id) {
+            write_time(out, time)?;
+            let mut line = value;
+            if line.len() > 1 {
+                line.push(b' ');
+            }
+            line.extend_from_slice(&id);
+            line.push(b'\n');
+            out.write_all(&line)?;
+        }
+    }
+    write_time(out, time)?;
+    Ok(written_steps)
+}
+
+// Reads VCD trace keeping only value changes of `signals`, traces in other formats are loaded whole
+pub fn stream_trace(
+    filename: &str,
+    signals: &[&SignalPath],
+    verbose: bool,
+) -> Result<SimulationData, Box<dyn Error>> {
+    let start = std::time::Instant::now();
+    let mut input = open_trace(filename)?;
+    let vcd = match &mut input {
+        TraceInput::File(file) => is_vcd(file)?,
+        TraceInput::Stream(input) => is_vcd(input)?,
+    };
+    if !vcd {
+        let data = load_trace_input(input)?;
+        if verbose {
+            println!("Loading trace took {:?}", start.elapsed());
+        }
+        return Ok(data);
+    }
+    let input: Box<dyn BufRead> = match input {
+        TraceInput::File(file) => Box::new(file),
+        TraceInput::Stream(input) => input,
+    };
+    let mut tokens = Tokens::new(input);
+    let (mut filtered, ids) = filter_header(&mut tokens, signals)?;
+    let steps = filter_body(&mut tokens, &ids, &mut filtered)?;
+    if verbose {
+        println!(
+            "Streaming trace took {:?}, kept {} time steps",
+            start.elapsed(),
+            steps
+        );
+    }
+
+    let start = std::time::Instant::now();
+    let data = read_seekable_trace(Cursor::new(filtered))?;
+    if verbose {
+        println!("Loading streamed signals took {:?}", start.elapsed());
+    }
+    Ok(data)
+}
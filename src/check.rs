@@ -0,0 +1,452 @@
+//! Checking analysis results against performance limits, e.g. in CI.
+//!
+//! Limits are read from a yaml file with a list of expressions comparing a statistic of a bus with
+//! a number:
+//!
+//! ```yaml
+//! - ram_rd.bandwidth >= 0.4
+//! - a_.backpressure_pct < 10
+//! - ram_wr.p99(cmd_to_completion) < 200
+//! - a_[init].busy > 0
+//! ```
+//!
+//! Statistics are named like in the output of `busperf analyze`, lowercase with units dropped and
+//! words joined with `_`. Each cycle type of a single channel bus gives two values: the number of
+//! cycles (`busy`) and their percentage of all cycles (`busy_pct`). Distributions, such as
+//! `cmd_to_completion`, are reduced with `min`, `max`, `mean`, `count` or percentile `pN`
+//! functions, e.g. `p99(cmd_to_completion)`.
+
+use std::{error::Error, fmt::Display, io::Write, str::FromStr};
+
+use libbusperf::bus_usage::{BucketsStatistic, BusUsage, Statistic};
+use yaml_rust2::{Yaml, YamlLoader};
+
+use crate::session::{AnalysisResults, Session};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Comparison {
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    Equal,
+    NotEqual,
+}
+
+impl Comparison {
+    const OPERATORS: [(&str, Comparison); 6] = [
+        ("<=", Comparison::LessOrEqual),
+        (">=", Comparison::GreaterOrEqual),
+        ("==", Comparison::Equal),
+        ("!=", Comparison::NotEqual),
+        ("<", Comparison::Less),
+        (">", Comparison::Greater),
+    ];
+
+    fn holds(&self, value: f64, threshold: f64) -> bool {
+        match self {
+            Comparison::Less => value < threshold,
+            Comparison::LessOrEqual => value <= threshold,
+            Comparison::Greater => value > threshold,
+            Comparison::GreaterOrEqual => value >= threshold,
+            Comparison::Equal => value == threshold,
+            Comparison::NotEqual => value != threshold,
+        }
+    }
+}
+
+/// Function reducing durations of periods of a bucket statistic to a single value.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Function {
+    Min,
+    Max,
+    Mean,
+    Count,
+    Percentile(f64),
+}
+
+impl FromStr for Function {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "min" => Function::Min,
+            "max" => Function::Max,
+            "mean" => Function::Mean,
+            "count" => Function::Count,
+            _ => match s.strip_prefix('p').map(|p| p.parse::<f64>()) {
+                Some(Ok(p)) if (0.0..=100.0).contains(&p) => Function::Percentile(p),
+                _ => Err(format!("unknown function `{s}`"))?,
+            },
+        })
+    }
+}
+
+impl Function {
+    /// Returns `None` if there are no periods, except for [Function::Count].
+    pub(crate) fn apply(&self, statistic: &BucketsStatistic) -> Option<f64> {
+        match self {
+            Function::Count => Some(statistic.data.len() as f64),
+            Function::Mean => statistic.mean(),
+            Function::Min => statistic.percentile(0.0).map(f64::from),
+            Function::Max => statistic.percentile(100.0).map(f64::from),
+            Function::Percentile(p) => statistic.percentile(*p).map(f64::from),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Metric {
+    Value(String),
+    Function(Function, String),
+}
+
+fn is_identifier(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+impl FromStr for Metric {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some((function, rest)) = s.split_once('(')
+            && let Some(statistic) = rest.strip_suffix(')')
+            && is_identifier(statistic.trim())
+        {
+            Ok(Metric::Function(
+                function.trim().parse()?,
+                statistic.trim().to_owned(),
+            ))
+        } else if is_identifier(s) {
+            Ok(Metric::Value(s.to_owned()))
+        } else {
+            Err(format!("invalid statistic `{s}`"))
+        }
+    }
+}
+
+// Name of a statistic or a cycle type used in limits, e.g. `Bandwidth [t/clk]` -> `bandwidth`
+fn metric_name(name: &str) -> String {
+    libbusperf::binning::statistic_key(name)
+}
+
+fn available_metrics(statistics: &[Statistic]) -> Vec<String> {
+    statistics
+        .iter()
+        .flat_map(|statistic| match statistic {
+            Statistic::Percentage(s) => s
+                .data_labels
+                .iter()
+                .flat_map(|(_, l)| [metric_name(l), metric_name(l) + "_pct"])
+                .collect(),
+            Statistic::Bucket(s) => vec![format!("p99({})", metric_name(s.name))],
+            Statistic::Timeline(s) => vec![metric_name(s.name)],
+        })
+        .collect()
+}
+
+impl Metric {
+    fn evaluate(&self, usage: &BusUsage) -> Result<f64, String> {
+        let statistics = usage.get_statistics(&[]);
+        let unknown = |name: &str| {
+            format!(
+                "unknown statistic `{name}`, available: {}",
+                available_metrics(&statistics).join(", ")
+            )
+        };
+        match self {
+            Metric::Value(name) => {
+                for statistic in statistics.iter() {
+                    match statistic {
+                        Statistic::Percentage(s) => {
+                            let total: f32 = s.data_labels.iter().map(|(v, _)| v).sum();
+                            for (value, label) in s.data_labels.iter() {
+                                let label = metric_name(label);
+                                if *name == label {
+                                    return Ok(*value as f64);
+                                }
+                                if name.strip_suffix("_pct") == Some(&label) {
+                                    return Ok(if total > 0.0 {
+                                        (value / total * 100.0) as f64
+                                    } else {
+                                        0.0
+                                    });
+                                }
+                            }
+                        }
+                        Statistic::Timeline(s) if metric_name(s.name) == *name => {
+                            return if s.value.is_nan() {
+                                Err(format!("`{name}` is not a number"))
+                            } else {
+                                Ok(s.value)
+                            };
+                        }
+                        Statistic::Bucket(s) if metric_name(s.name) == *name => {
+                            return Err(format!(
+                                "`{name}` is a distribution, use e.g. `p99({name})`, `mean({name})` or `max({name})`"
+                            ));
+                        }
+                        _ => (),
+                    }
+                }
+                Err(unknown(name))
+            }
+            Metric::Function(function, name) => {
+                let statistic = statistics
+                    .iter()
+                    .find_map(|statistic| match statistic {
+                        Statistic::Bucket(s) if metric_name(s.name) == *name => Some(s),
+                        _ => None,
+                    })
+                    .ok_or_else(|| unknown(name))?;
+                function
+                    .apply(statistic)
+                    .ok_or_else(|| format!("`{name}` has no data"))
+            }
+        }
+    }
+}
+
+/// Limit of a statistic of a bus, e.g. `ram_rd.bandwidth >= 0.4`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Limit {
+    expression: String,
+    bus: String,
+    phase: Option<String>,
+    metric: Metric,
+    comparison: Comparison,
+    threshold: f64,
+}
+
+impl FromStr for Limit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = |e: &str| format!("invalid limit `{s}`: {e}");
+        let (position, operator, comparison) = Comparison::OPERATORS
+            .iter()
+            .filter_map(|(op, c)| s.find(op).map(|i| (i, *op, *c)))
+            // At the same position prefer `<=` to `<`
+            .min_by_key(|(i, op, _)| (*i, std::cmp::Reverse(op.len())))
+            .ok_or_else(|| invalid("expected a comparison"))?;
+        let threshold = s[position + operator.len()..]
+            .trim()
+            .parse::<f64>()
+            .map_err(|_| invalid("expected a number after the comparison"))?;
+
+        // Bus names may contain dots, so take the first split leaving a valid statistic
+        let left = s[..position].trim();
+        let (bus, metric) = left
+            .match_indices('.')
+            .find_map(|(i, _)| {
+                let metric = left[i + 1..].parse::<Metric>().ok()?;
+                Some((left[..i].trim(), metric))
+            })
+            .ok_or_else(|| invalid("expected `bus.statistic`"))?;
+        let (bus, phase) = match bus.strip_suffix(']').and_then(|b| b.split_once('[')) {
+            Some((bus, phase)) => (bus.trim(), Some(phase.trim().to_owned())),
+            None => (bus, None),
+        };
+        if bus.is_empty() {
+            Err(invalid("missing bus name"))?
+        }
+        Ok(Limit {
+            expression: s.trim().to_owned(),
+            bus: bus.to_owned(),
+            phase,
+            metric,
+            comparison,
+            threshold,
+        })
+    }
+}
+
+impl Display for Limit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.expression)
+    }
+}
+
+impl Limit {
+    /// Bus name followed by the phase, if there is one, as in [BusData::display_name](libbusperf::bus_usage::BusData::display_name).
+    pub fn bus_display_name(&self) -> String {
+        match &self.phase {
+            Some(phase) => format!("{} [{phase}]", self.bus),
+            None => self.bus.clone(),
+        }
+    }
+
+    /// Checks the limit against results of the analysis.
+    pub fn check(&self, results: &AnalysisResults) -> Outcome {
+        let bus_name = self.bus_display_name();
+        if let Some(failed) = results
+            .failed
+            .iter()
+            .find(|f| f.name == self.bus && f.phase == self.phase)
+        {
+            return Outcome::Error(format!(
+                "analysis of bus `{bus_name}` failed: {}",
+                failed.error
+            ));
+        }
+        let buses = results
+            .buses
+            .iter()
+            .filter(|b| b.usage.get_name() == self.bus)
+            .collect::<Vec<_>>();
+        let Some(bus) = buses.iter().find(|b| b.phase == self.phase) else {
+            return Outcome::Error(match buses.first() {
+                Some(bus) if self.phase.is_none() => format!(
+                    "bus `{bus_name}` is analyzed in phases, use e.g. `{}`",
+                    self.expression.replacen(
+                        &self.bus,
+                        &format!("{}[{}]", self.bus, bus.phase.as_deref().unwrap_or_default()),
+                        1
+                    )
+                ),
+                _ => format!("unknown bus `{bus_name}`"),
+            });
+        };
+        match self.metric.evaluate(&bus.usage) {
+            Ok(value) if self.comparison.holds(value, self.threshold) => Outcome::Passed(value),
+            Ok(value) => Outcome::Failed(value),
+            Err(e) => Outcome::Error(e),
+        }
+    }
+}
+
+/// Result of checking a [Limit].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    /// The limit holds for the measured value.
+    Passed(f64),
+    /// The limit is violated by the measured value.
+    Failed(f64),
+    /// The value could not be measured, e.g. there is no such bus.
+    Error(String),
+}
+
+impl Outcome {
+    pub fn passed(&self) -> bool {
+        matches!(self, Outcome::Passed(_))
+    }
+}
+
+pub(crate) fn format_value(value: f64) -> String {
+    format!("{}", (value * 10000.0).round() / 10000.0)
+}
+
+/// Loads limits from a yaml file with a list of expressions.
+pub fn load_limits(filename: &str) -> Result<Vec<Limit>, Box<dyn Error>> {
+    let text = std::fs::read_to_string(filename)
+        .map_err(|e| format!("failed to read limits from {filename}: {e}"))?;
+    let yaml = YamlLoader::load_from_str(&text)?;
+    let Some(Yaml::Array(limits)) = yaml.first() else {
+        Err(format!("{filename} should contain a list of limits"))?
+    };
+    Ok(limits
+        .iter()
+        .map(|limit| match limit {
+            Yaml::String(limit) => limit.parse::<Limit>(),
+            _ => Err(format!("limit should be a string, got {limit:?}")),
+        })
+        .collect::<Result<_, _>>()?)
+}
+
+/// Checks every limit against results of the analysis.
+pub fn check_limits<'a>(
+    limits: &'a [Limit],
+    results: &AnalysisResults,
+) -> Vec<(&'a Limit, Outcome)> {
+    limits.iter().map(|l| (l, l.check(results))).collect()
+}
+
+/// Writes outcome of every limit followed by a summary line.
+pub fn write_summary(
+    write: &mut impl Write,
+    outcomes: &[(&Limit, Outcome)],
+) -> Result<(), Box<dyn Error>> {
+    for (limit, outcome) in outcomes {
+        match outcome {
+            Outcome::Passed(value) => writeln!(write, "PASS  {limit} ({})", format_value(*value))?,
+            Outcome::Failed(value) => writeln!(write, "FAIL  {limit} ({})", format_value(*value))?,
+            Outcome::Error(e) => writeln!(write, "ERROR {limit}: {e}")?,
+        }
+    }
+    let passed = outcomes.iter().filter(|(_, o)| o.passed()).count();
+    writeln!(write, "{passed} of {} limits passed", outcomes.len())?;
+    Ok(())
+}
+
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Writes outcomes as a JUnit XML report, with a test case for every limit.
+pub fn write_junit(
+    write: &mut impl Write,
+    outcomes: &[(&Limit, Outcome)],
+) -> Result<(), Box<dyn Error>> {
+    let count = |f: fn(&Outcome) -> bool| outcomes.iter().filter(|(_, o)| f(o)).count();
+    let failures = count(|o| matches!(o, Outcome::Failed(_)));
+    let errors = count(|o| matches!(o, Outcome::Error(_)));
+    writeln!(write, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(write, "<testsuites>")?;
+    writeln!(
+        write,
+        r#"  <testsuite name="busperf" tests="{}" failures="{failures}" errors="{errors}">"#,
+        outcomes.len()
+    )?;
+    for (limit, outcome) in outcomes {
+        let testcase = format!(
+            r#"    <testcase classname="busperf.{}" name="{}""#,
+            escape_xml(&limit.bus_display_name()),
+            escape_xml(&limit.expression)
+        );
+        match outcome {
+            Outcome::Passed(_) => writeln!(write, "{testcase}/>")?,
+            Outcome::Failed(value) => {
+                writeln!(write, "{testcase}>")?;
+                writeln!(
+                    write,
+                    r#"      <failure message="measured {}"/>"#,
+                    format_value(*value)
+                )?;
+                writeln!(write, "    </testcase>")?;
+            }
+            Outcome::Error(e) => {
+                writeln!(write, "{testcase}>")?;
+                writeln!(write, r#"      <error message="{}"/>"#, escape_xml(e))?;
+                writeln!(write, "    </testcase>")?;
+            }
+        }
+    }
+    writeln!(write, "  </testsuite>")?;
+    writeln!(write, "</testsuites>")?;
+    Ok(())
+}
+
+/// Analyzes the trace in `session` and checks results against limits from `limits_file`.
+///
+/// Summary is written to `out` and, if `junit` is set, JUnit XML report to that file. Returns
+/// whether all limits passed.
+pub fn run_check(
+    mut session: Session,
+    limits_file: &str,
+    junit: Option<&str>,
+    out: &mut impl Write,
+) -> Result<bool, Box<dyn Error>> {
+    let limits = load_limits(limits_file)?;
+    let results = session.analyze()?;
+    let outcomes = check_limits(&limits, &results);
+    write_summary(out, &outcomes)?;
+    if let Some(junit) = junit {
+        let mut file =
+            std::fs::File::create(junit).map_err(|e| format!("failed to create {junit}: {e}"))?;
+        write_junit(&mut file, &outcomes)?;
+    }
+    Ok(outcomes.iter().all(|(_, o)| o.passed()))
+}
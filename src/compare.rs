@@ -0,0 +1,461 @@
+//! Comparison of statistics saved from a baseline and a candidate run.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    error::Error,
+    io::Write,
+};
+
+use libbusperf::bus_usage::{BusData, BusUsage, Statistic};
+use serde::Serialize;
+
+use crate::{
+    check::{Function, format_value},
+    show::{ReportFormat, load_data, text_output::generate_tabled},
+};
+
+/// Whether a change of a statistic is an improvement or a regression.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+    /// Changed by no more than the tolerance.
+    Unchanged,
+    /// Changed beyond the tolerance, for statistics that are neither better nor worse when higher.
+    Changed,
+    Improvement,
+    Regression,
+}
+
+/// Change of a single value of a statistic.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct Delta {
+    pub statistic: String,
+    pub base: f64,
+    pub new: f64,
+    pub delta: f64,
+    /// Change relative to the base value, `None` if the base value is 0 and the new is not.
+    pub change_pct: Option<f64>,
+    pub status: Status,
+}
+
+/// Change of the number of periods in a bucket of a bucket statistic.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct BucketShift {
+    pub statistic: String,
+    /// Range of durations in clock cycles, e.g. `4-7`.
+    pub bucket: String,
+    pub base_count: usize,
+    pub new_count: usize,
+    /// Share of all periods of the statistic in the bucket.
+    pub base_share_pct: f64,
+    pub new_share_pct: f64,
+}
+
+/// Comparison of a bus present in both runs.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct BusComparison {
+    pub bus: String,
+    pub deltas: Vec<Delta>,
+    pub bucket_shifts: Vec<BucketShift>,
+}
+
+/// Comparison of all buses, matched by name and phase.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct Comparison {
+    /// Change in percent above which a statistic is reported as changed.
+    pub tolerance_pct: f64,
+    pub buses: Vec<BusComparison>,
+    pub only_in_base: Vec<String>,
+    pub only_in_new: Vec<String>,
+}
+
+impl Status {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Status::Unchanged => "unchanged",
+            Status::Changed => "changed",
+            Status::Improvement => "improvement",
+            Status::Regression => "regression",
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub(crate) enum Direction {
+    HigherIsBetter,
+    LowerIsBetter,
+    Neutral,
+}
+
+fn direction(statistic: &str) -> Direction {
+    match statistic {
+        "Busy" | "Bandwidth [t/clk]" | "Bandwidth above x rate [%]" | "Throughput [t/clk]" => {
+            Direction::HigherIsBetter
+        }
+        "Backpressure"
+        | "Backpressure rate [%]"
+        | "No data"
+        | "Error rate [%]"
+        | "Bandwidth below y rate [%]"
+        | "Transaction delays"
+        | "Cmd to completion"
+        | "Cmd to first data"
+        | "Last data to completion" => Direction::LowerIsBetter,
+        _ => Direction::Neutral,
+    }
+}
+
+// Values compared for every period of a bucket statistic
+const REDUCTIONS: [(&str, Function); 5] = [
+    ("count", Function::Count),
+    ("mean", Function::Mean),
+    ("p50", Function::Percentile(50.0)),
+    ("p99", Function::Percentile(99.0)),
+    ("max", Function::Max),
+];
+
+/// Values of all statistics of a bus with their names and direction in which they improve.
+pub(crate) fn values(usage: &BusUsage) -> Vec<(String, f64, Direction)> {
+    let mut values = vec![];
+    for statistic in usage.get_statistics(&[]) {
+        match statistic {
+            Statistic::Percentage(s) => {
+                for (value, label) in s.data_labels {
+                    values.push((label.to_owned(), value as f64, direction(label)));
+                }
+            }
+            Statistic::Bucket(s) => {
+                for (reduction, function) in REDUCTIONS {
+                    // Only the number of periods is meaningful when there are none
+                    let value = function.apply(&s).unwrap_or(0.0);
+                    let direction = match function {
+                        Function::Count => Direction::Neutral,
+                        _ => direction(s.name),
+                    };
+                    values.push((format!("{} ({reduction})", s.name), value, direction));
+                }
+            }
+            Statistic::Timeline(s) => values.push((s.name.to_owned(), s.value, direction(s.name))),
+        }
+    }
+    values
+}
+
+fn delta(statistic: String, base: f64, new: f64, direction: Direction, tolerance: f64) -> Delta {
+    let delta = new - base;
+    let change_pct = if delta == 0.0 {
+        Some(0.0)
+    } else {
+        (base != 0.0).then(|| delta / base.abs() * 100.0)
+    };
+    let beyond_tolerance = delta != 0.0 && change_pct.is_none_or(|c| c.abs() > tolerance);
+    let status = match direction {
+        _ if !beyond_tolerance => Status::Unchanged,
+        Direction::Neutral => Status::Changed,
+        Direction::HigherIsBetter if delta > 0.0 => Status::Improvement,
+        Direction::LowerIsBetter if delta < 0.0 => Status::Improvement,
+        _ => Status::Regression,
+    };
+    Delta {
+        statistic,
+        base,
+        new,
+        delta,
+        change_pct,
+        status,
+    }
+}
+
+fn bucket_shifts(base: &BusData, new: &BusData) -> Vec<BucketShift> {
+    let mut shifts = vec![];
+    for (base, new) in base
+        .get_statistics(&[])
+        .iter()
+        .zip(new.usage.get_statistics(&[]))
+    {
+        let (Statistic::Bucket(base), Statistic::Bucket(mut new)) = (base, new) else {
+            continue;
+        };
+        // Buckets of both runs are compared with binning of the base
+        new.binning = base.binning.clone();
+        let base_buckets: BTreeMap<_, _> = base.get_buckets().into_iter().collect();
+        let new_buckets: BTreeMap<_, _> = new.get_buckets().into_iter().collect();
+        let share = |count: usize, total: usize| {
+            if total > 0 {
+                count as f64 / total as f64 * 100.0
+            } else {
+                0.0
+            }
+        };
+        let buckets: BTreeSet<_> = base_buckets.keys().chain(new_buckets.keys()).collect();
+        for &bucket in buckets {
+            let base_count = base_buckets.get(&bucket).copied().unwrap_or(0);
+            let new_count = new_buckets.get(&bucket).copied().unwrap_or(0);
+            if base_count != new_count {
+                shifts.push(BucketShift {
+                    statistic: base.name.to_owned(),
+                    bucket: base.bucket_label(bucket),
+                    base_count,
+                    new_count,
+                    base_share_pct: share(base_count, base.data.len()),
+                    new_share_pct: share(new_count, new.data.len()),
+                });
+            }
+        }
+    }
+    shifts
+}
+
+/// Compares statistics of buses from two runs. A statistic is reported as changed when it differs
+/// by more than `tolerance_pct` percent from the base value.
+pub fn compare(base: &[BusData], new: &[BusData], tolerance_pct: f64) -> Comparison {
+    let mut buses = vec![];
+    let mut only_in_base = vec![];
+    for base in base {
+        let name = base.display_name();
+        let Some(new) = new.iter().find(|n| n.display_name() == name) else {
+            only_in_base.push(name);
+            continue;
+        };
+        let new_values = values(&new.usage);
+        let deltas = values(&base.usage)
+            .into_iter()
+            .filter_map(|(statistic, base, direction)| {
+                let (_, new, _) = new_values.iter().find(|(s, _, _)| *s == statistic)?;
+                // Values that could not be calculated, e.g. error rate without transactions
+                if base.is_nan() || new.is_nan() {
+                    return None;
+                }
+                Some(delta(statistic, base, *new, direction, tolerance_pct))
+            })
+            .collect();
+        buses.push(BusComparison {
+            bus: name,
+            deltas,
+            bucket_shifts: bucket_shifts(base, new),
+        });
+    }
+    let only_in_new = new
+        .iter()
+        .map(|n| n.display_name())
+        .filter(|name| !base.iter().any(|b| b.display_name() == *name))
+        .collect();
+    Comparison {
+        tolerance_pct,
+        buses,
+        only_in_base,
+        only_in_new,
+    }
+}
+
+impl Comparison {
+    pub fn regressions(&self) -> usize {
+        self.buses
+            .iter()
+            .flat_map(|b| b.deltas.iter())
+            .filter(|d| d.status == Status::Regression)
+            .count()
+    }
+
+    /// Writes the comparison in given format. Text and md tables list only values changed beyond
+    /// the tolerance, unless `all` is set.
+    pub fn write(
+        &self,
+        write: &mut impl Write,
+        format: ReportFormat,
+        all: bool,
+    ) -> Result<(), Box<dyn Error>> {
+        match format {
+            ReportFormat::Text => {
+                self.write_table(write, tabled::settings::Style::rounded(), false, all)
+            }
+            ReportFormat::Md => {
+                self.write_table(write, tabled::settings::Style::markdown(), true, all)
+            }
+            ReportFormat::Csv => self.write_csv(write),
+            ReportFormat::Json => {
+                serde_json::to_writer_pretty(&mut *write, self)?;
+                writeln!(write)?;
+                Ok(())
+            }
+        }
+    }
+
+    fn delta_rows(&self, highlight: bool, all: bool) -> Vec<Vec<String>> {
+        let mut rows = vec![];
+        for bus in self.buses.iter() {
+            for d in bus.deltas.iter() {
+                if !all && d.status == Status::Unchanged {
+                    continue;
+                }
+                let status = match d.status {
+                    Status::Unchanged => "",
+                    Status::Changed => "changed",
+                    Status::Improvement => "improvement",
+                    Status::Regression if highlight => "**REGRESSION**",
+                    Status::Regression => "REGRESSION",
+                };
+                rows.push(vec![
+                    bus.bus.clone(),
+                    d.statistic.clone(),
+                    format_value(d.base),
+                    format_value(d.new),
+                    format_value(d.delta),
+                    d.change_pct
+                        .map(|c| format!("{c:+.2}%"))
+                        .unwrap_or_else(|| "n/a".to_owned()),
+                    status.to_owned(),
+                ]);
+            }
+        }
+        rows
+    }
+
+    fn shift_rows(&self) -> Vec<Vec<String>> {
+        let mut rows = vec![];
+        for bus in self.buses.iter() {
+            for s in bus.bucket_shifts.iter() {
+                rows.push(vec![
+                    bus.bus.clone(),
+                    s.statistic.clone(),
+                    s.bucket.clone(),
+                    format!("{} ({:.1}%)", s.base_count, s.base_share_pct),
+                    format!("{} ({:.1}%)", s.new_count, s.new_share_pct),
+                    format!("{:+.1} pp", s.new_share_pct - s.base_share_pct),
+                ]);
+            }
+        }
+        rows
+    }
+
+    fn write_table<O>(
+        &self,
+        write: &mut impl Write,
+        style: O,
+        markdown: bool,
+        all: bool,
+    ) -> Result<(), Box<dyn Error>>
+    where
+        O: tabled::settings::TableOption<
+                tabled::grid::records::vec_records::VecRecords<
+                    tabled::grid::records::vec_records::Text<String>,
+                >,
+                tabled::grid::config::ColoredConfig,
+                tabled::grid::dimension::CompleteDimension,
+            > + Clone,
+    {
+        let header = [
+            "bus name",
+            "statistic",
+            "base",
+            "new",
+            "delta",
+            "change",
+            "status",
+        ]
+        .map(String::from)
+        .to_vec();
+        let rows = self.delta_rows(markdown, all);
+        if !rows.is_empty() {
+            writeln!(write, "{}", generate_tabled(&header, &rows, style.clone()))?;
+        }
+        let prefix = if markdown { "- " } else { "" };
+        let header = ["bus name", "statistic", "bucket", "base", "new", "shift"]
+            .map(String::from)
+            .to_vec();
+        let rows = self.shift_rows();
+        if !rows.is_empty() {
+            writeln!(write, "Bucket distribution shifts:")?;
+            writeln!(write, "{}", generate_tabled(&header, &rows, style))?;
+        }
+        for bus in self.only_in_base.iter() {
+            writeln!(write, "{prefix}Bus {bus} is missing in the new run")?;
+        }
+        for bus in self.only_in_new.iter() {
+            writeln!(write, "{prefix}Bus {bus} is missing in the base run")?;
+        }
+        let changed = self
+            .buses
+            .iter()
+            .flat_map(|b| b.deltas.iter())
+            .filter(|d| d.status != Status::Unchanged)
+            .count();
+        writeln!(
+            write,
+            "{prefix}{changed} values changed, {} regressions beyond {}% tolerance",
+            self.regressions(),
+            self.tolerance_pct
+        )?;
+        Ok(())
+    }
+
+    fn write_csv(&self, write: &mut impl Write) -> Result<(), Box<dyn Error>> {
+        // Deltas and bucket shifts are written as two tables with different columns
+        let mut wtr = csv::WriterBuilder::new().flexible(true).from_writer(write);
+        wtr.write_record([
+            "bus name",
+            "statistic",
+            "base",
+            "new",
+            "delta",
+            "change [%]",
+            "status",
+        ])?;
+        for bus in self.buses.iter() {
+            for d in bus.deltas.iter() {
+                wtr.write_record([
+                    bus.bus.clone(),
+                    d.statistic.clone(),
+                    d.base.to_string(),
+                    d.new.to_string(),
+                    d.delta.to_string(),
+                    d.change_pct.map(|c| c.to_string()).unwrap_or_default(),
+                    d.status.as_str().to_owned(),
+                ])?;
+            }
+        }
+        if self.buses.iter().any(|b| !b.bucket_shifts.is_empty()) {
+            wtr.write_record([
+                "bus name",
+                "statistic",
+                "bucket",
+                "base",
+                "new",
+                "base share [%]",
+                "new share [%]",
+            ])?;
+            for bus in self.buses.iter() {
+                for s in bus.bucket_shifts.iter() {
+                    wtr.write_record([
+                        bus.bus.clone(),
+                        s.statistic.clone(),
+                        s.bucket.clone(),
+                        s.base_count.to_string(),
+                        s.new_count.to_string(),
+                        s.base_share_pct.to_string(),
+                        s.new_share_pct.to_string(),
+                    ])?;
+                }
+            }
+        }
+        wtr.flush()?;
+        Ok(())
+    }
+}
+
+/// Compares statistics saved in busperf format in `base` and `new` files and writes the
+/// comparison to `out`, see [Comparison::write].
+pub fn compare_files(
+    base: &str,
+    new: &str,
+    tolerance_pct: f64,
+    format: ReportFormat,
+    all: bool,
+    out: &mut impl Write,
+) -> Result<Comparison, Box<dyn Error>> {
+    let base = load_data(base).map_err(|e| format!("{base}: {e}"))?;
+    let new = load_data(new).map_err(|e| format!("{new}: {e}"))?;
+    let comparison = compare(&base.usages, &new.usages, tolerance_pct);
+    comparison.write(out, format, all)?;
+    Ok(comparison)
+}
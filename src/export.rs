@@ -0,0 +1,139 @@
+//! Export of individual transactions and periods, e.g. for analysis in pandas.
+
+use std::{error::Error, io::Write};
+
+use libbusperf::bus_usage::{BusData, BusUsage, Period};
+use serde::Serialize;
+
+use crate::show::load_data;
+
+/// Format of exported records.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExportFormat {
+    Csv,
+    /// One JSON object per line
+    JsonLines,
+}
+
+/// Kind of an exported record.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordKind {
+    /// Transaction of a multichannel bus
+    Transaction,
+    /// Burst on a single channel bus
+    Burst,
+    /// Delay between transactions on a single channel bus
+    Delay,
+}
+
+/// Single transaction or period of a bus. Times are in waveform time units.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct Record<'a> {
+    pub bus: &'a str,
+    pub phase: Option<&'a str>,
+    pub kind: RecordKind,
+    pub start: u64,
+    /// End of the period or time of response to the transaction.
+    pub end: u64,
+    /// Duration of the period in clock cycles.
+    pub duration: Option<i32>,
+    pub first_data: Option<u64>,
+    pub last_data: Option<u64>,
+    pub resp: Option<&'a str>,
+    pub id: Option<&'a str>,
+    /// Start of the next transaction.
+    pub next: Option<u64>,
+}
+
+fn period<'a>(bus: &'a BusData, kind: RecordKind, period: &Period) -> Record<'a> {
+    Record {
+        bus: bus.usage.get_name(),
+        phase: bus.phase.as_deref(),
+        kind,
+        start: period.start(),
+        end: period.end(),
+        duration: Some(period.duration()),
+        first_data: None,
+        last_data: None,
+        resp: None,
+        id: None,
+        next: None,
+    }
+}
+
+/// Returns records of all transactions of multichannel buses and all bursts and delays of single
+/// channel buses, in order of occurrence on each bus.
+pub fn records(usages: &[BusData]) -> Vec<Record<'_>> {
+    let mut records = vec![];
+    for bus in usages {
+        match &bus.usage {
+            BusUsage::SingleChannel(usage) => {
+                let mut periods = usage
+                    .burst_lengths()
+                    .iter()
+                    .map(|p| period(bus, RecordKind::Burst, p))
+                    .chain(
+                        usage
+                            .transaction_delays()
+                            .iter()
+                            .map(|p| period(bus, RecordKind::Delay, p)),
+                    )
+                    .collect::<Vec<_>>();
+                periods.sort_by_key(|r| r.start);
+                records.append(&mut periods);
+            }
+            BusUsage::MultiChannel(usage) => {
+                records.extend(usage.transactions().into_iter().map(|t| Record {
+                    bus: bus.usage.get_name(),
+                    phase: bus.phase.as_deref(),
+                    kind: RecordKind::Transaction,
+                    start: t.start,
+                    end: t.resp_time,
+                    duration: None,
+                    first_data: Some(t.first_data),
+                    last_data: Some(t.last_data),
+                    resp: Some(t.resp),
+                    id: t.id,
+                    next: Some(t.next),
+                }))
+            }
+        }
+    }
+    records
+}
+
+/// Writes records of `usages` in given format, see [records].
+pub fn write_records(
+    write: &mut impl Write,
+    usages: &[BusData],
+    format: ExportFormat,
+) -> Result<(), Box<dyn Error>> {
+    let records = records(usages);
+    match format {
+        ExportFormat::Csv => {
+            let mut wtr = csv::Writer::from_writer(write);
+            for record in records {
+                wtr.serialize(record)?;
+            }
+            wtr.flush()?;
+        }
+        ExportFormat::JsonLines => {
+            for record in records {
+                serde_json::to_writer(&mut *write, &record)?;
+                writeln!(write)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Exports records of statistics saved in busperf format in `file` to `out`.
+pub fn export_file(
+    file: &str,
+    format: ExportFormat,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn Error>> {
+    let data = load_data(file)?;
+    write_records(out, &data.usages, format)
+}
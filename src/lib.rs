@@ -1,16 +1,28 @@
 use crate::{
-    analyze::{SimulationData, analyzer::Analyzer},
-    show::OutputType,
+    analyze::{
+        ResultCache, SimulationData,
+        analyzer::{Analyzer, analyze_all},
+        follow_simulation_trace,
+    },
+    show::{OutputType, show_data},
 };
-use std::{error::Error, io::Write};
+use std::{error::Error, io::Write, time::Duration};
 
 pub mod analyze;
+pub mod check;
+pub mod compare;
+pub mod export;
+pub mod merge;
+pub mod root_cause;
+pub mod session;
 pub mod show;
 use libbusperf::bus_usage::BusData;
 
 /// Run visualization.
 ///
-/// If any analyzer has not yet been run it will be run. Then visualization of type `type_` will be run.
+/// If any analyzer has not yet been run it will be run, see [analyze_all], unless its results are
+/// found in the `cache`. Then visualization of type `type_` will be run.
+#[allow(clippy::too_many_arguments)]
 pub fn run_visualization(
     mut analyzers: Vec<Box<dyn Analyzer>>,
     type_: OutputType,
@@ -19,15 +31,43 @@ pub fn run_visualization(
     trace_path: String,
     verbose: bool,
     skipped_stats: &[String],
+    cache: Option<&ResultCache>,
 ) -> Result<(), Box<dyn Error>> {
-    use crate::show::show_data;
+    if let Some(cache) = cache {
+        let loaded = cache.load(&mut analyzers);
+        if verbose {
+            println!("Loaded results of {loaded} buses from cache");
+        }
+    }
+    let results = analyze_all(&mut analyzers, simulation_data, verbose);
+    if let Some(cache) = cache {
+        cache.store(&analyzers)?;
+    }
+    let usages = collect_bus_data(&analyzers, results);
 
-    let usages = analyzers
-        .iter_mut()
-        .filter_map(|a| {
-            if !a.finished_analysis()
-                && let Err(e) = a.analyze(simulation_data, verbose)
-            {
+    show_data(
+        usages,
+        trace_path,
+        None,
+        simulation_data.timescale().unwrap_or_default(),
+        type_,
+        out,
+        verbose,
+        skipped_stats,
+    )?;
+    Ok(())
+}
+
+// Returns results of finished analyses, printing errors of failed ones
+fn collect_bus_data(
+    analyzers: &[Box<dyn Analyzer>],
+    results: Vec<Result<(), String>>,
+) -> Vec<BusData> {
+    analyzers
+        .iter()
+        .zip(results)
+        .filter_map(|(a, result)| {
+            if let Err(e) = result {
                 use owo_colors::OwoColorize;
                 eprintln!(
                     "{} {} {}",
@@ -36,13 +76,110 @@ pub fn run_visualization(
                     e.bright_red()
                 );
             }
-            a.get_results().cloned().map(|usage| BusData {
-                usage,
-                signals: a.get_signals().into_iter().cloned().collect(),
-            })
+            a.bus_data()
         })
-        .collect();
+        .collect()
+}
 
-    show_data(usages, trace_path, None, type_, out, verbose, skipped_stats)?;
+/// Run visualization of a VCD trace that is still being written, see [follow_simulation_trace].
+///
+/// Text outputs are printed after every `refresh`, GUI is updated as data arrives, while other
+/// outputs are written once the whole trace is read.
+#[allow(clippy::too_many_arguments)]
+pub fn run_follow(
+    mut analyzers: Vec<Box<dyn Analyzer>>,
+    type_: OutputType,
+    out: &mut impl Write,
+    trace_path: String,
+    refresh: Duration,
+    idle_timeout: Duration,
+    verbose: bool,
+    skipped_stats: &[String],
+) -> Result<(), Box<dyn Error>> {
+    #[cfg(feature = "gui")]
+    if let OutputType::Rendered = type_ {
+        return follow_in_gui(analyzers, trace_path, refresh, idle_timeout, verbose);
+    }
+    let periodic = matches!(type_, OutputType::Pretty | OutputType::Csv | OutputType::Md);
+    // Saved results refer to the trace by its hash
+    let saved = !periodic && !matches!(type_, OutputType::Json | OutputType::Report);
+    if saved && !std::fs::metadata(&trace_path).is_ok_and(|m| m.is_file()) {
+        Err("saving results of a followed trace requires it to be a regular file")?
+    }
+    follow_simulation_trace(
+        &trace_path,
+        &mut analyzers,
+        refresh,
+        idle_timeout,
+        verbose,
+        |analyzers, results, simulation_data, finished| {
+            if !periodic && !finished {
+                return Ok(());
+            }
+            let timescale = simulation_data.timescale().unwrap_or_default();
+            if let OutputType::Pretty = type_ {
+                writeln!(
+                    out,
+                    "Trace analyzed up to {}",
+                    timescale.format_time(simulation_data.end_time())
+                )?;
+            }
+            show_data(
+                collect_bus_data(analyzers, results),
+                trace_path.clone(),
+                None,
+                timescale,
+                type_.clone(),
+                out,
+                verbose,
+                skipped_stats,
+            )
+        },
+    )
+}
+
+#[cfg(feature = "gui")]
+fn follow_in_gui(
+    mut analyzers: Vec<Box<dyn Analyzer>>,
+    trace_path: String,
+    refresh: Duration,
+    idle_timeout: Duration,
+    verbose: bool,
+) -> Result<(), Box<dyn Error>> {
+    let (sender, updates) = std::sync::mpsc::channel();
+    let follower = {
+        let trace_path = trace_path.clone();
+        std::thread::spawn(move || {
+            follow_simulation_trace(
+                &trace_path,
+                &mut analyzers,
+                refresh,
+                idle_timeout,
+                verbose,
+                |analyzers, results, simulation_data, _| {
+                    let timescale = simulation_data.timescale().unwrap_or_default();
+                    // The GUI may already be closed, in which case results are not needed
+                    let _ = sender.send((collect_bus_data(analyzers, results), timescale));
+                    Ok(())
+                },
+            )
+            .map_err(|e| e.to_string())
+        })
+    };
+    // GUI is started once there are first results to show
+    let (usages, timescale) = match updates.recv() {
+        Ok(update) => update,
+        Err(_) => {
+            follower.join().map_err(|_| "trace follower panicked")??;
+            return Err("trace ended before any results were available".into());
+        }
+    };
+    if usages.is_empty() {
+        Err("no bus was analyzed successfully")?
+    }
+    busperf_gui::run_egui(usages, trace_path, None, timescale, Some(updates))?;
+    if follower.is_finished() {
+        follower.join().map_err(|_| "trace follower panicked")??;
+    }
     Ok(())
 }
@@ -1,10 +1,17 @@
 use bpaf::{OptionParser, Parser, construct, long, positional, short};
-use busperf::show::OutputType;
+use busperf::export::ExportFormat;
+use busperf::show::{OutputType, ReportFormat};
 use cfg_if::cfg_if;
+use libbusperf::binning::Bins;
 use owo_colors::OwoColorize;
+use std::time::Duration;
 
 enum Args {
     Analyze(AnalyzeArgs),
+    Check(CheckArgs),
+    Compare(CompareArgs),
+    Export(ExportArgs),
+    Merge(MergeArgs),
     Show(ShowArgs),
 }
 
@@ -14,23 +21,35 @@ impl Args {
             .to_options()
             .descr("Analyze given trace")
             .command("analyze");
+        let check = CheckArgs::parse()
+            .to_options()
+            .descr("Check statistics of a trace against performance limits")
+            .command("check");
+        let compare = CompareArgs::parse()
+            .to_options()
+            .descr("Compare statistics from two files, e.g. of a baseline and a candidate run")
+            .command("compare");
+        let export = ExportArgs::parse()
+            .to_options()
+            .descr("Export individual transactions and periods from a file, e.g. for pandas")
+            .command("export");
+        let merge = MergeArgs::parse()
+            .to_options()
+            .descr("Merge statistics of the same design from many files, e.g. of a seed sweep")
+            .command("merge");
         let show = ShowArgs::parse()
             .to_options()
             .descr("Show statistics from a file")
             .command("show");
 
-        let parser: OptionParser<Args> = construct!([analyze, show]).to_options();
+        let parser: OptionParser<Args> =
+            construct!([analyze, check, compare, export, merge, show]).to_options();
         let mut args = parser.run();
 
-        // swap simulation trace and bus description when files are passed in wrong order
-        if let Args::Analyze(args) = &mut args {
-            let files = &mut args.files;
-            if files.simulation_trace.ends_with(".yaml")
-                && (files.bus_description.ends_with(".fst")
-                    || files.bus_description.ends_with(".vcd"))
-            {
-                std::mem::swap(&mut files.simulation_trace, &mut files.bus_description);
-            }
+        match &mut args {
+            Args::Analyze(args) => args.files.fix_order(),
+            Args::Check(args) => args.files.fix_order(),
+            Args::Compare(_) | Args::Export(_) | Args::Merge(_) | Args::Show(_) => (),
         }
         args
     }
@@ -48,6 +67,12 @@ impl OutputTypeParser {
         let md = long("md")
             .help("Format output as md table")
             .req_flag(OutputType::Md);
+        let json = long("json")
+            .help("Format output as json with all statistics")
+            .req_flag(OutputType::Json);
+        let report = long("html_report")
+            .help("Generate static HTML report with tables and charts")
+            .req_flag(OutputType::Report);
         let text = long("text")
             .help("Format output as table")
             .req_flag(OutputType::Pretty);
@@ -61,13 +86,13 @@ impl OutputTypeParser {
 
         cfg_if! {
             if #[cfg(all(feature = "gui", feature = "generate-html"))] {
-                construct!([gui, csv, md, text, data, html])
+                construct!([gui, csv, md, json, report, text, data, html])
             } else if #[cfg(feature = "gui")] {
-                construct!([gui, csv, md, text, data])
+                construct!([gui, csv, md, json, report, text, data])
             } else if #[cfg(feature = "generate-html")] {
-                construct!([csv, md, text, data, html])
+                construct!([csv, md, json, report, text, data, html])
             } else {
-                construct!([csv, md, text, data])
+                construct!([csv, md, json, report, text, data])
             }
         }
     }
@@ -94,60 +119,194 @@ impl ShowArgs {
     }
 }
 
+fn report_format() -> impl Parser<ReportFormat> {
+    let csv = long("csv")
+        .help("Format output as csv")
+        .req_flag(ReportFormat::Csv);
+    let md = long("md")
+        .help("Format output as md table")
+        .req_flag(ReportFormat::Md);
+    let text = long("text")
+        .help("Format output as table [default]")
+        .req_flag(ReportFormat::Text);
+    let json = long("json")
+        .help("Format output as json")
+        .req_flag(ReportFormat::Json);
+    construct!([csv, md, text, json]).fallback(ReportFormat::Text)
+}
+
+struct ExportArgs {
+    file: String,
+    output: Option<String>,
+    format: ExportFormat,
+}
+
+impl ExportArgs {
+    pub fn parse() -> impl Parser<Args> {
+        let csv = long("csv")
+            .help("Format output as csv [default]")
+            .req_flag(ExportFormat::Csv);
+        let jsonl = long("jsonl")
+            .help("Format output as JSON Lines")
+            .req_flag(ExportFormat::JsonLines);
+        let format = construct!([csv, jsonl]).fallback(ExportFormat::Csv);
+        let output = short('o')
+            .long("output")
+            .help("Output filename")
+            .argument("OUT")
+            .optional();
+        let file = positional("FILENAME").help("File to load statistics from");
+
+        let parser = construct!(ExportArgs {
+            format,
+            output,
+            file,
+        });
+        construct!(Args::Export(parser))
+    }
+}
+
+struct MergeArgs {
+    files: Vec<String>,
+    output: String,
+    format: ReportFormat,
+}
+
+impl MergeArgs {
+    pub fn parse() -> impl Parser<Args> {
+        let format = report_format();
+        let output = short('o')
+            .long("output")
+            .help("File to save merged statistics to")
+            .argument("OUT");
+        let files = positional("FILES")
+            .help("Files with statistics of the same buses")
+            .some("at least one file is required");
+
+        let parser = construct!(MergeArgs {
+            format,
+            output,
+            files,
+        });
+        construct!(Args::Merge(parser))
+    }
+}
+
+struct CompareArgs {
+    base: String,
+    new: String,
+    format: ReportFormat,
+    output: Option<String>,
+    tolerance: f64,
+    all: bool,
+}
+
+impl CompareArgs {
+    pub fn parse() -> impl Parser<Args> {
+        let format = report_format();
+        let output = short('o')
+            .long("output")
+            .help("Output filename")
+            .argument("OUT")
+            .optional();
+        let tolerance = short('t')
+            .long("tolerance")
+            .help("Change in percent above which a statistic is reported as changed [default: 5]")
+            .argument::<f64>("PERCENT")
+            .guard(|t| *t >= 0.0, "tolerance cannot be negative")
+            .fallback(5.0);
+        let all = long("all")
+            .help("List also values that did not change beyond the tolerance in text and md output")
+            .switch();
+        let base = positional("BASE").help("File with statistics of the baseline run");
+        let new = positional("NEW").help("File with statistics of the candidate run");
+
+        let parser = construct!(CompareArgs {
+            format,
+            output,
+            tolerance,
+            all,
+            base,
+            new,
+        });
+        construct!(Args::Compare(parser))
+    }
+}
+
 struct AnalyzeArgs {
     files: FileArgs,
-    max_burst_delay: u32,
+    params: AnalysisParams,
     verbose: bool,
     output: Option<String>,
     skipped_stats: Option<String>,
     output_type: OutputType,
-    window_length: u32,
-    x_rate: f32,
-    y_rate: f32,
-    plugins_path: String,
+    stream: bool,
+    cache: Option<String>,
+    follow: bool,
+    refresh: f64,
+    follow_timeout: f64,
+    bins: Bins,
 }
 
 struct FileArgs {
     simulation_trace: String,
-    bus_description: String,
+    bus_descriptions: Vec<String>,
 }
 
-impl AnalyzeArgs {
-    pub fn parse() -> impl Parser<Args> {
+impl FileArgs {
+    pub fn parse() -> impl Parser<FileArgs> {
         // We accept simulation trace as either options or positional arguments
         let simulation_trace = positional("TRACE")
-            .help("vcd/fst file with simulation trace")
+            .help(
+                "vcd/fst/ghw file with simulation trace, possibly gzip compressed, or - for stdin. Compressed traces and stdin are decompressed whole into memory, unless a vcd trace is read with --stream",
+            )
             .complete_shell(bpaf::ShellComp::File {
-                mask: Some("*.(fst|vcd)"),
+                mask: Some("*.(fst|vcd|ghw|gz)"),
             });
-        let bus_description = positional("BUS_CONFIG")
-            .help("yaml with description of buses")
+        let bus_descriptions = positional("BUS_CONFIG")
+            .help("yaml with description of buses or IP-XACT component xml, buses of several files are merged")
             .complete_shell(bpaf::ShellComp::File {
-                mask: Some("*.(yaml|yml)"),
-            });
-        let files = construct!(FileArgs {
+                mask: Some("*.(yaml|yml|xml)"),
+            })
+            .some("at least one bus description is required");
+        construct!(FileArgs {
             simulation_trace,
-            bus_description
-        });
+            bus_descriptions
+        })
+    }
+
+    // swap simulation trace and bus description when files are passed in wrong order
+    fn fix_order(&mut self) {
+        use busperf::analyze::is_simulation_trace;
+
+        if !is_simulation_trace(&self.simulation_trace)
+            && let Some(trace) = self
+                .bus_descriptions
+                .iter_mut()
+                .find(|f| is_simulation_trace(f))
+        {
+            std::mem::swap(&mut self.simulation_trace, trace);
+        }
+    }
+}
 
+// Parameters of the analysis shared by commands that analyze a trace
+struct AnalysisParams {
+    max_burst_delay: u32,
+    window_length: u32,
+    x_rate: f32,
+    y_rate: f32,
+    plugins_path: String,
+    ipxact_scope: Option<String>,
+}
+
+impl AnalysisParams {
+    pub fn parse() -> impl Parser<AnalysisParams> {
         let max_burst_delay = short('m')
             .long("max_burst_delay")
             .help("Max delay during a burst [default: 0]")
             .argument("BURST")
             .fallback(0);
-        let output = short('o')
-            .long("output")
-            .help("Output filename")
-            .argument("OUT")
-            .optional();
-
-        let skipped_stats = long("skip")
-            .help("Stats to skip separated by a comma.")
-            .argument::<String>("SKIPPED_STATS")
-            .optional();
-
-        let output_type = OutputTypeParser::parse();
-
         let window_length = short('w')
             .long("window")
             .help("Set size of the rolling window [default: 10000]")
@@ -163,29 +322,182 @@ impl AnalyzeArgs {
             .help("Set y_rate for bandwidth below y_rate [default: 0.00001]")
             .argument("Y_RATE")
             .fallback(0.00001);
-        let verbose = short('v').long("verbose").switch();
         let plugins_path = short('p')
             .long("plugins_path")
             .help("Path to python plugins [default: \"./plugins/python]\"")
             .argument("PATH")
             .fallback("./plugins/python".to_string());
+        let ipxact_scope = long("ipxact_scope")
+            .help("Scope of the IP-XACT component instance, separated by dots [default: component name]")
+            .argument("SCOPE")
+            .optional();
+        construct!(AnalysisParams {
+            max_burst_delay,
+            window_length,
+            x_rate,
+            y_rate,
+            plugins_path,
+            ipxact_scope,
+        })
+    }
+
+    fn ipxact_scope(&self) -> Option<Vec<String>> {
+        self.ipxact_scope
+            .as_ref()
+            .map(|s| s.split('.').map(|s| s.to_string()).collect())
+    }
+
+    // Session with the parameters, describing buses of every file: IP-XACT components of `.xml`
+    // files and yaml descriptions of the other ones
+    fn session(
+        &self,
+        bus_descriptions: &[String],
+        bins: Bins,
+        verbose: bool,
+    ) -> Result<busperf::session::Session, Box<dyn std::error::Error>> {
+        use busperf::session::{AnalysisConfig, Session};
+
+        let config = AnalysisConfig::default()
+            .max_burst_delay(self.max_burst_delay as i32)
+            .window_length(self.window_length)
+            .x_rate(self.x_rate)
+            .y_rate(self.y_rate)
+            .plugins_path(&self.plugins_path)
+            .bins(bins);
+        let scope = self.ipxact_scope();
+        let mut session = Session::new(config).verbose(verbose);
+        for description in bus_descriptions.iter() {
+            session = if description.ends_with(".xml") {
+                session.ipxact_file(description, scope.as_deref())?
+            } else {
+                session.bus_description_file(description)?
+            };
+        }
+        for skipped in session.skipped_interfaces() {
+            eprintln!("{} {skipped}, skipping", "[WARN]".yellow());
+        }
+        Ok(session)
+    }
+}
+
+impl AnalyzeArgs {
+    pub fn parse() -> impl Parser<Args> {
+        let files = FileArgs::parse();
+        let params = AnalysisParams::parse();
+        let output = short('o')
+            .long("output")
+            .help("Output filename")
+            .argument("OUT")
+            .optional();
+
+        let skipped_stats = long("skip")
+            .help("Stats to skip separated by a comma.")
+            .argument::<String>("SKIPPED_STATS")
+            .optional();
+
+        let output_type = OutputTypeParser::parse();
+        let verbose = short('v').long("verbose").switch();
+        let cache = long("cache")
+            .help("Directory in which results are cached, so buses with unchanged configuration are not analyzed again")
+            .argument("DIR")
+            .optional();
+        let stream = long("stream")
+            .help("Read only signals used by the analyzers from a vcd trace, keeping only their value changes in memory; fst and ghw traces are still loaded whole")
+            .switch();
+        let follow = long("follow")
+            .help("Analyze a vcd trace while it is being written, e.g. to a named pipe, updating results periodically. Each update analyzes the whole trace read so far again, so updates wait until the filtered trace grows by 10%")
+            .switch();
+        let refresh = long("refresh")
+            .help("Seconds between updates of results in follow mode [default: 1]")
+            .argument::<f64>("SECONDS")
+            .guard(|s| *s > 0.0, "refresh interval must be positive")
+            .fallback(1.0);
+        let follow_timeout = long("follow_timeout")
+            .help("Seconds without new data after which a followed trace file is considered complete [default: 60]")
+            .argument::<f64>("SECONDS")
+            .guard(|s| *s >= 0.0, "timeout cannot be negative")
+            .fallback(60.0);
+
+        let bins = long("bins")
+            .help("Binning of durations in histograms: log2, linear:WIDTH or edges:EDGE,EDGE,..., for a single statistic with STATISTIC=BINNING, e.g. delays=linear:10 [default: log2]")
+            .argument::<String>("BINNING")
+            .many()
+            .parse(|settings| {
+                let mut bins = Bins::default();
+                for setting in settings {
+                    bins.parse_setting(&setting)?;
+                }
+                Ok::<_, String>(bins)
+            });
 
         let parser = construct!(AnalyzeArgs {
             output_type,
             output,
             skipped_stats,
-            max_burst_delay,
-            window_length,
-            x_rate,
-            y_rate,
+            params,
             verbose,
-            plugins_path,
+            stream,
+            cache,
+            follow,
+            refresh,
+            follow_timeout,
+            bins,
             files,
         });
         construct!(Args::Analyze(parser))
     }
 }
 
+struct CheckArgs {
+    files: FileArgs,
+    params: AnalysisParams,
+    expect: String,
+    junit: Option<String>,
+    verbose: bool,
+}
+
+impl CheckArgs {
+    pub fn parse() -> impl Parser<Args> {
+        let files = FileArgs::parse();
+        let params = AnalysisParams::parse();
+        let expect = long("expect")
+            .help("yaml with a list of limits, e.g. `ram_rd.bandwidth >= 0.4`")
+            .argument("LIMITS")
+            .complete_shell(bpaf::ShellComp::File {
+                mask: Some("*.(yaml|yml)"),
+            });
+        let junit = long("junit")
+            .help("Write results as JUnit XML report to FILE")
+            .argument("FILE")
+            .optional();
+        let verbose = short('v').long("verbose").switch();
+
+        let parser = construct!(CheckArgs {
+            expect,
+            junit,
+            params,
+            verbose,
+            files,
+        });
+        construct!(Args::Check(parser))
+    }
+}
+
+fn run_check(args: CheckArgs) -> Result<bool, Box<dyn std::error::Error>> {
+    use busperf::check;
+
+    let session = args
+        .params
+        .session(&args.files.bus_descriptions, Bins::default(), args.verbose)?
+        .trace_file(&args.files.simulation_trace)?;
+    check::run_check(
+        session,
+        &args.expect,
+        args.junit.as_deref(),
+        &mut std::io::stdout(),
+    )
+}
+
 fn main() {
     let args = Args::parse();
     match args {
@@ -196,18 +508,15 @@ fn main() {
                 .map(|s| s.to_string())
                 .collect();
             use busperf::{
-                analyze::{load_bus_analyzers, load_simulation_trace},
-                run_visualization,
+                analyze::{ResultCache, load_simulation_trace, stream_simulation_trace},
+                run_follow, run_visualization,
             };
 
-            let analyzers = match load_bus_analyzers(
-                &args.files.bus_description,
-                args.max_burst_delay as i32,
-                args.window_length,
-                args.x_rate,
-                args.y_rate,
-                &args.plugins_path,
-            ) {
+            let analyzers = args
+                .params
+                .session(&args.files.bus_descriptions, args.bins, args.verbose)
+                .and_then(|session| session.analyzers());
+            let analyzers = match analyzers {
                 Ok(analyzers) => analyzers,
                 Err(e) => {
                     eprintln!(
@@ -219,15 +528,57 @@ fn main() {
                 }
             };
 
-            let mut data = load_simulation_trace(&args.files.simulation_trace, args.verbose)
-                .unwrap_or_else(|e| {
+            if args.follow && args.cache.is_some() {
+                eprintln!("Error: Results of a followed trace cannot be cached.");
+                std::process::exit(1);
+            }
+            // Cached and saved results refer to the trace by its hash
+            let saved = match args.output_type {
+                OutputType::Data => true,
+                #[cfg(feature = "generate-html")]
+                OutputType::Html => true,
+                _ => false,
+            };
+            if args.files.simulation_trace == "-" && (saved || args.cache.is_some()) {
+                eprintln!(
+                    "Error: Results of a trace read from stdin cannot be cached or saved, save the trace to a file instead."
+                );
+                std::process::exit(1);
+            }
+            let cache = args.cache.as_ref().map(|dir| {
+                ResultCache::new(dir, &args.files.simulation_trace).unwrap_or_else(|e| {
+                    eprintln!(
+                        "{} {}",
+                        "[ERROR] Invalid cache:".bright_red(),
+                        e.bright_red()
+                    );
+                    std::process::exit(1);
+                })
+            });
+            let data = if args.follow {
+                None
+            } else if args.stream {
+                Some(stream_simulation_trace(
+                    &args.files.simulation_trace,
+                    &analyzers,
+                    args.verbose,
+                ))
+            } else {
+                Some(load_simulation_trace(
+                    &args.files.simulation_trace,
+                    args.verbose,
+                ))
+            };
+            let mut data = data.map(|data| {
+                data.unwrap_or_else(|e| {
                     eprintln!(
                         "{} {}",
                         "[ERROR] Invalid simulation trace:".bright_red(),
                         e.bright_red()
                     );
                     std::process::exit(1);
-                });
+                })
+            });
             if let OutputType::Data = args.output_type {
                 args.output.as_ref().unwrap_or_else(|| {
                     eprintln!(
@@ -257,14 +608,101 @@ fn main() {
                     std::process::exit(1);
                 }),
             };
-            if let Err(e) = run_visualization(
-                analyzers,
-                args.output_type,
+            let result = if let Some(data) = &mut data {
+                run_visualization(
+                    analyzers,
+                    args.output_type,
+                    &mut out,
+                    data,
+                    args.files.simulation_trace,
+                    args.verbose,
+                    &skipped_stats,
+                    cache.as_ref(),
+                )
+            } else {
+                run_follow(
+                    analyzers,
+                    args.output_type,
+                    &mut out,
+                    args.files.simulation_trace,
+                    Duration::from_secs_f64(args.refresh),
+                    Duration::from_secs_f64(args.follow_timeout),
+                    args.verbose,
+                    &skipped_stats,
+                )
+            };
+            if let Err(e) = result {
+                eprintln!("{} {}", "[ERROR]".bright_red(), e.bright_red());
+                std::process::exit(1);
+            }
+        }
+        Args::Check(args) => match run_check(args) {
+            Ok(true) => (),
+            Ok(false) => std::process::exit(1),
+            Err(e) => {
+                eprintln!("{} {}", "[ERROR]".bright_red(), e.bright_red());
+                std::process::exit(2);
+            }
+        },
+        Args::Compare(args) => {
+            use busperf::compare::compare_files;
+
+            let mut out: Box<dyn std::io::Write> = match &args.output {
+                None => Box::new(std::io::stdout()),
+                Some(filename) => match std::fs::File::create(filename) {
+                    Ok(file) => Box::new(file),
+                    Err(e) => {
+                        eprintln!(
+                            "{} {}",
+                            "[ERROR] Failed to create output file:".bright_red(),
+                            e.bright_red()
+                        );
+                        std::process::exit(1);
+                    }
+                },
+            };
+            if let Err(e) = compare_files(
+                &args.base,
+                &args.new,
+                args.tolerance,
+                args.format,
+                args.all,
                 &mut out,
-                &mut data,
-                args.files.simulation_trace,
-                args.verbose,
-                &skipped_stats,
+            ) {
+                eprintln!("{} {}", "[ERROR]".bright_red(), e.bright_red());
+                std::process::exit(1);
+            }
+        }
+        Args::Export(args) => {
+            use busperf::export::export_file;
+
+            let mut out: Box<dyn std::io::Write> = match &args.output {
+                None => Box::new(std::io::stdout()),
+                Some(filename) => match std::fs::File::create(filename) {
+                    Ok(file) => Box::new(file),
+                    Err(e) => {
+                        eprintln!(
+                            "{} {}",
+                            "[ERROR] Failed to create output file:".bright_red(),
+                            e.bright_red()
+                        );
+                        std::process::exit(1);
+                    }
+                },
+            };
+            if let Err(e) = export_file(&args.file, args.format, &mut out) {
+                eprintln!("{} {}", "[ERROR]".bright_red(), e.bright_red());
+                std::process::exit(1);
+            }
+        }
+        Args::Merge(args) => {
+            use busperf::merge::merge_files;
+
+            if let Err(e) = merge_files(
+                &args.files,
+                &args.output,
+                args.format,
+                &mut std::io::stdout(),
             ) {
                 eprintln!("{} {}", "[ERROR]".bright_red(), e.bright_red());
                 std::process::exit(1);
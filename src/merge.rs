@@ -0,0 +1,209 @@
+//! Merging statistics of the same design from many simulations, e.g. of a seed sweep.
+
+use std::{error::Error, io::Write};
+
+use libbusperf::bus_usage::{BusData, BusUsage};
+use serde::Serialize;
+
+use crate::{
+    check::format_value,
+    compare::values,
+    show::{ReportFormat, SavedData, load_data, text_output::generate_tabled},
+};
+
+/// Spread of a value of a statistic across simulations.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct Spread {
+    pub statistic: String,
+    /// Number of simulations in which the value could be calculated.
+    pub runs: usize,
+    pub mean: f64,
+    /// Sample standard deviation.
+    pub std_dev: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl Spread {
+    fn new(statistic: String, values: &[f64]) -> Spread {
+        let runs = values.len();
+        let mean = values.iter().sum::<f64>() / runs as f64;
+        let std_dev = if runs > 1 {
+            (values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (runs - 1) as f64).sqrt()
+        } else {
+            0.0
+        };
+        Spread {
+            statistic,
+            runs,
+            mean,
+            std_dev,
+            min: values.iter().copied().fold(f64::INFINITY, f64::min),
+            max: values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+        }
+    }
+}
+
+/// Spread of statistics of a bus.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct BusSpread {
+    pub bus: String,
+    /// Number of simulations in which the bus was analyzed.
+    pub runs: usize,
+    pub statistics: Vec<Spread>,
+}
+
+fn spread(bus: String, usages: &[&BusUsage]) -> BusSpread {
+    let runs = usages.iter().map(|u| values(u)).collect::<Vec<_>>();
+    let statistics = runs[0]
+        .iter()
+        .map(|(statistic, _, _)| {
+            let values = runs
+                .iter()
+                .filter_map(|values| values.iter().find(|(s, _, _)| s == statistic))
+                .map(|(_, value, _)| *value)
+                // Values that could not be calculated, e.g. error rate without transactions
+                .filter(|value| !value.is_nan())
+                .collect::<Vec<_>>();
+            Spread::new(statistic.clone(), &values)
+        })
+        .filter(|s| s.runs > 0)
+        .collect();
+    BusSpread {
+        bus,
+        runs: usages.len(),
+        statistics,
+    }
+}
+
+/// Merges statistics of the same buses from many simulations.
+///
+/// Buses are matched by name and phase, see [BusUsage::merge]. Returns merged data, with trace of
+/// the first simulation, and spread of statistics across simulations.
+pub fn merge(runs: &[SavedData]) -> Result<(SavedData, Vec<BusSpread>), Box<dyn Error>> {
+    let first = runs.first().ok_or("nothing to merge")?;
+    if runs.iter().any(|r| r.timescale != first.timescale) {
+        Err("cannot merge data with different timescales")?
+    }
+    let mut names = vec![];
+    for usage in runs.iter().flat_map(|r| r.usages.iter()) {
+        let name = usage.display_name();
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    let mut usages = vec![];
+    let mut spreads = vec![];
+    for name in names {
+        let buses = runs
+            .iter()
+            .filter_map(|r| r.usages.iter().find(|u| u.display_name() == name))
+            .collect::<Vec<_>>();
+        let bus_usages = buses.iter().map(|b| &b.usage).collect::<Vec<_>>();
+        let usage = BusUsage::merge(&bus_usages)?;
+        let mut diagnostics = buses[0].diagnostics.clone();
+        for bus in buses[1..].iter() {
+            diagnostics.merge(&bus.diagnostics);
+        }
+        spreads.push(spread(name, &bus_usages));
+        usages.push(BusData {
+            usage,
+            signals: buses[0].signals.clone(),
+            phase: buses[0].phase.clone(),
+            diagnostics,
+            bins: buses[0].bins.clone(),
+            feeds: buses[0].feeds.clone(),
+            scope: buses[0].scope.clone(),
+        });
+    }
+    let merged = SavedData {
+        trace_path: first.trace_path.clone(),
+        hash: first.hash.clone(),
+        timescale: first.timescale,
+        usages,
+    };
+    Ok((merged, spreads))
+}
+
+/// Writes spread of statistics in given format.
+pub fn write_spread(
+    write: &mut impl Write,
+    spreads: &[BusSpread],
+    format: ReportFormat,
+) -> Result<(), Box<dyn Error>> {
+    let header = [
+        "bus name",
+        "runs",
+        "statistic",
+        "mean",
+        "std dev",
+        "min",
+        "max",
+    ];
+    // Values are rounded only in tables
+    let value: fn(f64) -> String = match format {
+        ReportFormat::Csv => |v| v.to_string(),
+        _ => format_value,
+    };
+    let rows = spreads
+        .iter()
+        .flat_map(|bus| {
+            bus.statistics.iter().map(|s| {
+                vec![
+                    bus.bus.clone(),
+                    s.runs.to_string(),
+                    s.statistic.clone(),
+                    value(s.mean),
+                    value(s.std_dev),
+                    value(s.min),
+                    value(s.max),
+                ]
+            })
+        })
+        .collect::<Vec<_>>();
+    let header = header.map(String::from).to_vec();
+    match format {
+        ReportFormat::Text => writeln!(
+            write,
+            "{}",
+            generate_tabled(&header, &rows, tabled::settings::Style::rounded())
+        )?,
+        ReportFormat::Md => writeln!(
+            write,
+            "{}",
+            generate_tabled(&header, &rows, tabled::settings::Style::markdown())
+        )?,
+        ReportFormat::Csv => {
+            let mut wtr = csv::Writer::from_writer(write);
+            wtr.write_record(header)?;
+            for row in rows {
+                wtr.write_record(row)?;
+            }
+            wtr.flush()?;
+        }
+        ReportFormat::Json => {
+            serde_json::to_writer_pretty(&mut *write, spreads)?;
+            writeln!(write)?;
+        }
+    }
+    Ok(())
+}
+
+/// Merges statistics saved in busperf format in `files`, saves them to `output` and writes spread
+/// of statistics across files to `out`.
+pub fn merge_files(
+    files: &[String],
+    output: &str,
+    format: ReportFormat,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn Error>> {
+    let runs = files
+        .iter()
+        .map(|f| load_data(f).map_err(|e| format!("{f}: {e}")))
+        .collect::<Result<Vec<_>, _>>()?;
+    let (merged, spreads) = merge(&runs)?;
+    let mut file =
+        std::fs::File::create(output).map_err(|e| format!("failed to create {output}: {e}"))?;
+    merged.save(&mut file)?;
+    write_spread(out, &spreads, format)
+}
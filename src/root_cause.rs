@@ -0,0 +1,206 @@
+//! Attribution of backpressure to buses further down declared chains of buses, see `feeds` in
+//! `docs/source/yaml.md`.
+//!
+//! Every backpressure cycle of a bus which feeds other buses is followed downstream: while the
+//! fed bus is in backpressure too, the search continues from it. The cycle is attributed to the
+//! first fed bus that was transferring data at the time (saturated), to the last bus in
+//! backpressure (stalled) or, if no fed bus was busy nor stalled, to the bus itself (unexplained).
+
+use std::{borrow::Cow, collections::BTreeMap};
+
+use libbusperf::{
+    CycleType,
+    bus_usage::{BusData, BusUsage, CycleRun, RealTime},
+};
+use serde::Serialize;
+
+/// Why the root cause held the upstream buses back.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum CauseKind {
+    /// The bus was transferring data, but not fast enough.
+    Saturated,
+    /// The bus was in backpressure itself, with no fed bus to blame.
+    Stalled,
+    /// None of the buses fed by the backpressured bus was busy or stalled.
+    Unexplained,
+}
+
+impl std::fmt::Display for CauseKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CauseKind::Saturated => write!(f, "saturated"),
+            CauseKind::Stalled => write!(f, "stalled"),
+            CauseKind::Unexplained => write!(f, "unexplained"),
+        }
+    }
+}
+
+/// Bus to which backpressure cycles of upstream buses are attributed.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct RootCause {
+    /// Name of the bus, with the phase if the analysis was split into phases.
+    pub bus: String,
+    pub kind: CauseKind,
+    /// Number of attributed backpressure cycles, summed over all upstream buses.
+    pub cycles: u64,
+    /// Percentage of all backpressure cycles of buses which feed other buses.
+    pub share: f64,
+    /// Numbers of attributed backpressure cycles of each upstream bus.
+    pub upstream: BTreeMap<String, u64>,
+}
+
+// Returns runs of states of the bus. A multichannel bus is busy while any transaction is in flight.
+fn timeline(data: &BusData) -> Cow<'_, [CycleRun]> {
+    match &data.usage {
+        BusUsage::SingleChannel(usage) => Cow::Borrowed(usage.runs()),
+        BusUsage::MultiChannel(usage) => {
+            let mut transactions = usage
+                .transactions()
+                .iter()
+                .map(|t| (t.start, t.resp_time.max(t.start + 1)))
+                .collect::<Vec<_>>();
+            transactions.sort();
+            let mut runs: Vec<CycleRun> = vec![];
+            for (start, end) in transactions {
+                match runs.last_mut() {
+                    Some(last) if start <= last.end => last.end = last.end.max(end),
+                    _ => runs.push(CycleRun {
+                        state: CycleType::Busy,
+                        start,
+                        end,
+                    }),
+                }
+            }
+            Cow::Owned(runs)
+        }
+    }
+}
+
+fn state_at(runs: &[CycleRun], time: RealTime) -> Option<CycleType> {
+    let i = runs.partition_point(|r| r.start <= time);
+    runs[..i].last().filter(|r| time < r.end).map(|r| r.state)
+}
+
+// Times between `start` and `end` at which state of the bus changes
+fn changes(runs: &[CycleRun], start: RealTime, end: RealTime) -> impl Iterator<Item = RealTime> {
+    let first = runs.partition_point(|r| r.end <= start);
+    runs[first..]
+        .iter()
+        .take_while(move |r| r.start < end)
+        .flat_map(|r| [r.start, r.end])
+        .filter(move |t| start < *t && *t < end)
+}
+
+struct Chains<'a> {
+    runs: Vec<Cow<'a, [CycleRun]>>,
+    feeds: Vec<Vec<usize>>,
+}
+
+impl Chains<'_> {
+    // Attributes backpressure of bus `path.last()` between `start` and `end`, adding durations
+    // for each root cause to `found`
+    fn attribute(
+        &self,
+        path: &mut Vec<usize>,
+        start: RealTime,
+        end: RealTime,
+        found: &mut BTreeMap<(usize, CauseKind), RealTime>,
+    ) {
+        let bus = *path.last().expect("path starts at the upstream bus");
+        let mut times = vec![start, end];
+        for &fed in self.feeds[bus].iter() {
+            times.extend(changes(&self.runs[fed], start, end));
+        }
+        times.sort();
+        times.dedup();
+        for span in times.windows(2) {
+            let (start, end) = (span[0], span[1]);
+            let state = |fed: &usize| state_at(&self.runs[*fed], start);
+            let fed = |s| {
+                self.feeds[bus]
+                    .iter()
+                    .find(|f| state(f) == Some(s))
+                    .copied()
+            };
+            if let Some(stalled) = fed(CycleType::Backpressure) {
+                if self.feeds[stalled].is_empty() || path.contains(&stalled) {
+                    *found.entry((stalled, CauseKind::Stalled)).or_default() += end - start;
+                } else {
+                    path.push(stalled);
+                    self.attribute(path, start, end, found);
+                    path.pop();
+                }
+            } else if let Some(busy) = fed(CycleType::Busy) {
+                *found.entry((busy, CauseKind::Saturated)).or_default() += end - start;
+            } else {
+                let kind = match path.len() {
+                    1 => CauseKind::Unexplained,
+                    _ => CauseKind::Stalled,
+                };
+                *found.entry((bus, kind)).or_default() += end - start;
+            }
+        }
+    }
+}
+
+/// Returns root causes of backpressure of buses which feed other buses, from the one with the most
+/// attributed cycles. Only buses of the same phase are chained.
+pub fn root_causes(usages: &[&BusData]) -> Vec<RootCause> {
+    let chains = Chains {
+        runs: usages.iter().map(|u| timeline(u)).collect(),
+        feeds: usages
+            .iter()
+            .map(|u| {
+                u.feeds
+                    .iter()
+                    .filter_map(|name| {
+                        usages
+                            .iter()
+                            .position(|d| d.usage.get_name() == name && d.phase == u.phase)
+                    })
+                    .collect()
+            })
+            .collect(),
+    };
+    let mut causes: BTreeMap<(usize, CauseKind), BTreeMap<String, u64>> = BTreeMap::new();
+    for (i, u) in usages.iter().enumerate() {
+        // Multichannel buses cannot feed other buses, as their backpressure is not known
+        let BusUsage::SingleChannel(usage) = &u.usage else {
+            continue;
+        };
+        if chains.feeds[i].is_empty() {
+            continue;
+        }
+        let mut found = BTreeMap::new();
+        for run in chains.runs[i].iter() {
+            if run.state == CycleType::Backpressure {
+                chains.attribute(&mut vec![i], run.start, run.end, &mut found);
+            }
+        }
+        for (cause, time) in found {
+            *causes
+                .entry(cause)
+                .or_default()
+                .entry(u.usage.get_name().to_owned())
+                .or_default() += time / usage.clock_period().max(1);
+        }
+    }
+    let total: u64 = causes.values().flat_map(|u| u.values()).sum();
+    let mut causes = causes
+        .into_iter()
+        .map(|((bus, kind), upstream)| {
+            let cycles = upstream.values().sum();
+            RootCause {
+                bus: usages[bus].display_name(),
+                kind,
+                cycles,
+                share: cycles as f64 * 100.0 / total as f64,
+                upstream,
+            }
+        })
+        .filter(|c| c.cycles > 0)
+        .collect::<Vec<_>>();
+    causes.sort_by_key(|c| std::cmp::Reverse(c.cycles));
+    causes
+}
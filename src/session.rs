@@ -0,0 +1,227 @@
+//! Builder style API for running analyses from other Rust programs.
+//!
+//! ```no_run
+//! use busperf::session::{AnalysisConfig, Session};
+//!
+//! let results = Session::new(AnalysisConfig::default().max_burst_delay(1))
+//!     .bus_description_file("buses.yaml")?
+//!     .trace_file("trace.vcd")?
+//!     .analyze()?;
+//! for bus in &results.buses {
+//!     println!("{}: {:?}", bus.display_name(), bus.usage);
+//! }
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::{error::Error, path::Path};
+
+use libbusperf::{CyclesNum, Timescale, binning::Bins, bus_usage::BusData};
+use yaml_rust2::Yaml;
+
+use crate::analyze::{
+    Descriptions, SimulationData,
+    analyzer::{Analyzer, analyze_all},
+    load_simulation_trace,
+};
+
+/// Parameters of the analysis used for buses which do not set them in their description.
+#[derive(Clone, Debug)]
+pub struct AnalysisConfig {
+    max_burst_delay: CyclesNum,
+    window_length: u32,
+    x_rate: f32,
+    y_rate: f32,
+    plugins_path: String,
+    bins: Bins,
+}
+
+impl Default for AnalysisConfig {
+    /// Same defaults as in `busperf analyze`.
+    fn default() -> Self {
+        AnalysisConfig {
+            max_burst_delay: 0,
+            window_length: 10000,
+            x_rate: 0.0001,
+            y_rate: 0.00001,
+            plugins_path: "./plugins/python".to_owned(),
+            bins: Bins::default(),
+        }
+    }
+}
+
+impl AnalysisConfig {
+    /// Max delay during a burst in clock cycles.
+    pub fn max_burst_delay(mut self, max_burst_delay: CyclesNum) -> Self {
+        self.max_burst_delay = max_burst_delay;
+        self
+    }
+
+    /// Size of the rolling window in which bandwidth is averaged.
+    pub fn window_length(mut self, window_length: u32) -> Self {
+        self.window_length = window_length;
+        self
+    }
+
+    /// Bandwidth above which time is counted in `Bandwidth above x rate`.
+    pub fn x_rate(mut self, x_rate: f32) -> Self {
+        self.x_rate = x_rate;
+        self
+    }
+
+    /// Bandwidth below which time is counted in `Bandwidth below y rate`.
+    pub fn y_rate(mut self, y_rate: f32) -> Self {
+        self.y_rate = y_rate;
+        self
+    }
+
+    /// Directory with Python plugins.
+    pub fn plugins_path(mut self, plugins_path: impl Into<String>) -> Self {
+        self.plugins_path = plugins_path.into();
+        self
+    }
+
+    /// Binning of durations in histograms of statistics, used if not set in the description.
+    pub fn bins(mut self, bins: Bins) -> Self {
+        self.bins = bins;
+        self
+    }
+}
+
+/// Bus whose analysis failed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FailedBus {
+    pub name: String,
+    pub phase: Option<String>,
+    pub error: String,
+}
+
+/// Results of [Session::analyze].
+pub struct AnalysisResults {
+    /// Results of successfully analyzed buses, in order of their descriptions.
+    pub buses: Vec<BusData>,
+    pub failed: Vec<FailedBus>,
+    pub timescale: Timescale,
+}
+
+/// Analysis of buses described in yaml in a single simulation trace.
+///
+/// Several descriptions can be added, they are merged in the same way as yaml files passed to
+/// `busperf analyze`.
+pub struct Session {
+    config: AnalysisConfig,
+    descriptions: Descriptions,
+    trace: Option<SimulationData>,
+    verbose: bool,
+}
+
+impl Session {
+    pub fn new(config: AnalysisConfig) -> Self {
+        Session {
+            config,
+            descriptions: Descriptions::default(),
+            trace: None,
+            verbose: false,
+        }
+    }
+
+    /// Prints how long each step of the analysis took.
+    pub fn verbose(mut self, verbose: bool) -> Self {
+        self.verbose = verbose;
+        self
+    }
+
+    /// Adds buses described in a yaml file, together with files it includes.
+    pub fn bus_description_file(mut self, path: impl AsRef<Path>) -> Result<Self, Box<dyn Error>> {
+        self.descriptions.load_file(path.as_ref())?;
+        Ok(self)
+    }
+
+    /// Adds buses described in a yaml document. Included files are relative to the working
+    /// directory.
+    pub fn bus_description_str(mut self, yaml: &str) -> Result<Self, Box<dyn Error>> {
+        self.descriptions.load_str(yaml, Path::new("<string>"))?;
+        Ok(self)
+    }
+
+    /// Adds buses described in a parsed yaml document.
+    pub fn bus_description_yaml(mut self, yaml: Yaml) -> Result<Self, Box<dyn Error>> {
+        self.descriptions.load_yaml(yaml, Path::new("<yaml>"))?;
+        Ok(self)
+    }
+
+    /// Adds AMBA bus interfaces of an IP-XACT component, see
+    /// [load_ipxact_analyzers](crate::analyze::load_ipxact_analyzers).
+    pub fn ipxact_file(
+        mut self,
+        path: impl AsRef<Path>,
+        scope: Option<&[String]>,
+    ) -> Result<Self, Box<dyn Error>> {
+        self.descriptions.load_ipxact(path.as_ref(), scope)?;
+        Ok(self)
+    }
+
+    /// Returns messages about bus interfaces of IP-XACT components that are not supported and
+    /// were skipped.
+    pub fn skipped_interfaces(&self) -> &[String] {
+        self.descriptions.skipped()
+    }
+
+    /// Loads trace to analyze from a file, see [load_simulation_trace].
+    pub fn trace_file(mut self, path: impl AsRef<Path>) -> Result<Self, Box<dyn Error>> {
+        let path = path
+            .as_ref()
+            .to_str()
+            .ok_or("trace path is not valid UTF-8")?;
+        self.trace = Some(load_simulation_trace(path, self.verbose)?);
+        Ok(self)
+    }
+
+    /// Sets already loaded trace to analyze, e.g. with [SimulationData::from_wellen].
+    pub fn trace(mut self, trace: SimulationData) -> Self {
+        self.trace = Some(trace);
+        self
+    }
+
+    /// Creates analyzers of all described buses.
+    pub fn analyzers(&self) -> Result<Vec<Box<dyn Analyzer>>, Box<dyn Error>> {
+        let config = &self.config;
+        let mut analyzers = self.descriptions.build_analyzers(
+            config.max_burst_delay,
+            config.window_length,
+            config.x_rate,
+            config.y_rate,
+            &config.plugins_path,
+        )?;
+        for analyzer in analyzers.iter_mut() {
+            analyzer.set_default_bins(&config.bins);
+        }
+        Ok(analyzers)
+    }
+
+    /// Analyzes all described buses in the trace.
+    ///
+    /// Returns an error if descriptions are invalid or no trace was set. Buses whose analysis
+    /// failed are listed in [AnalysisResults::failed].
+    pub fn analyze(&mut self) -> Result<AnalysisResults, Box<dyn Error>> {
+        let mut analyzers = self.analyzers()?;
+        let trace = self.trace.as_mut().ok_or("no simulation trace was set")?;
+        let results = analyze_all(&mut analyzers, trace, self.verbose);
+        let mut buses = vec![];
+        let mut failed = vec![];
+        for (analyzer, result) in analyzers.iter().zip(results) {
+            match (result, analyzer.bus_data()) {
+                (Ok(()), Some(data)) => buses.push(data),
+                (result, _) => failed.push(FailedBus {
+                    name: analyzer.name().to_owned(),
+                    phase: analyzer.phase().map(|p| p.to_owned()),
+                    error: result.err().unwrap_or_default(),
+                }),
+            }
+        }
+        Ok(AnalysisResults {
+            buses,
+            failed,
+            timescale: trace.timescale().unwrap_or_default(),
+        })
+    }
+}
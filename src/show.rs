@@ -5,9 +5,11 @@ use std::io::Read;
 use std::{cell::Cell, io::Write};
 
 use libbusperf::bus_usage::BusData;
-use libbusperf::calculate_file_hash;
+use libbusperf::{Timescale, calculate_file_hash};
 
-mod text_output;
+mod html_report;
+mod json_output;
+pub(crate) mod text_output;
 
 /// Type of visualization of data.
 #[derive(Clone)]
@@ -16,6 +18,10 @@ pub enum OutputType {
     Pretty,
     Csv,
     Md,
+    /// All statistics as JSON
+    Json,
+    /// Static HTML report with tables and SVG charts
+    Report,
     /// GUI
     #[cfg(feature = "gui")]
     Rendered,
@@ -26,16 +32,27 @@ pub enum OutputType {
     Html,
 }
 
+/// Format of reports derived from statistics, such as comparisons.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReportFormat {
+    Text,
+    Csv,
+    Md,
+    Json,
+}
+
 pub struct WaveformFile {
     pub path: String,
     pub hash: Hash,
     pub checked: Cell<bool>,
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn show_data(
     usages: Vec<BusData>,
     trace_path: String,
     _hash: Option<String>,
+    timescale: Timescale,
     type_: OutputType,
     out: &mut impl Write,
     verbose: bool,
@@ -43,25 +60,69 @@ pub fn show_data(
 ) -> Result<(), Box<dyn Error>> {
     match type_ {
         OutputType::Pretty => {
-            let usages = usages.iter().map(|u| &u.usage).collect::<Vec<_>>();
-            text_output::print_statistics(out, &usages, verbose, skipped_stats)
+            let usages = usages.iter().collect::<Vec<_>>();
+            text_output::print_statistics(out, &usages, timescale, verbose, skipped_stats)
         }
         OutputType::Csv => {
-            let usages = usages.iter().map(|u| &u.usage).collect::<Vec<_>>();
-            text_output::generate_csv(out, &usages, verbose, skipped_stats)
+            let usages = usages.iter().collect::<Vec<_>>();
+            text_output::generate_csv(out, &usages, timescale, verbose, skipped_stats)?;
+            // Diagnostics do not fit in the table
+            text_output::print_diagnostics(&mut std::io::stderr(), &usages, timescale, "")
         }
         OutputType::Md => {
-            let usages = usages.iter().map(|u| &u.usage).collect::<Vec<_>>();
-            text_output::generate_md_table(out, &usages, verbose, skipped_stats)
+            let usages = usages.iter().collect::<Vec<_>>();
+            text_output::generate_md_table(out, &usages, timescale, verbose, skipped_stats)
         }
-        #[cfg(feature = "gui")]
-        OutputType::Rendered => {
-            use busperf_gui::egui_visualization::TimescaleUnit;
-            busperf_gui::run_egui(usages, trace_path, _hash, TimescaleUnit::new(-9))
+        OutputType::Json => {
+            let usages = usages.iter().collect::<Vec<_>>();
+            json_output::generate_json(out, &usages, &trace_path, timescale, verbose, skipped_stats)
+        }
+        OutputType::Report => {
+            let usages = usages.iter().collect::<Vec<_>>();
+            html_report::generate_html_report(out, &usages, &trace_path, timescale, skipped_stats)
         }
-        OutputType::Data => save_data(usages, trace_path, out),
+        #[cfg(feature = "gui")]
+        OutputType::Rendered => busperf_gui::run_egui(usages, trace_path, _hash, timescale, None),
+        OutputType::Data => save_data(usages, trace_path, timescale, out),
         #[cfg(feature = "generate-html")]
-        OutputType::Html => generate_html(usages, trace_path, out),
+        OutputType::Html => generate_html(usages, trace_path, timescale, out),
+    }
+}
+
+/// Contents of a file saved in busperf format.
+pub struct SavedData {
+    pub trace_path: String,
+    /// Hash of the trace file.
+    pub hash: String,
+    pub timescale: Timescale,
+    pub usages: Vec<BusData>,
+}
+
+/// Loads statistics saved in busperf format.
+pub fn load_data(filename: &str) -> Result<SavedData, Box<dyn Error>> {
+    let data = std::fs::read(filename).map_err(|e| format!("Failed to load file {e}"))?;
+    let mut decoder = flate2::read::GzDecoder::new(&*data);
+    let mut buf = Vec::new();
+    decoder.read_to_end(&mut buf).map_err(|_| "Invalid file")?;
+    let (trace_path, hash, timescale, usages) = libbusperf::saved::decode(&buf)?;
+    Ok(SavedData {
+        trace_path,
+        hash,
+        timescale,
+        usages,
+    })
+}
+
+impl SavedData {
+    /// Writes the data in busperf format.
+    pub fn save(&self, out: &mut impl Write) -> Result<(), Box<dyn Error>> {
+        let data =
+            libbusperf::saved::encode(&self.trace_path, &self.hash, &self.timescale, &self.usages)?;
+        let mut encoder = flate2::write::GzEncoder::new(out, Compression::default());
+        encoder
+            .write_all(&data)
+            .map_err(|e| format!("Write to file failed {e}"))?;
+        Ok(())
     }
 }
 
@@ -70,18 +131,12 @@ pub fn visualization_from_file(
     output_type: OutputType,
     verbose: bool,
 ) -> Result<(), Box<dyn Error>> {
-    let data = std::fs::read(filename).map_err(|e| format!("Failed to load file {e}"))?;
-    let mut decoder = flate2::read::GzDecoder::new(&*data);
-    let mut buf = Vec::new();
-    decoder.read_to_end(&mut buf).map_err(|_| "Invalid file")?;
-    let config = bincode::config::standard();
-    let (data, _): ((String, String, Vec<BusData>), _) =
-        bincode::decode_from_slice(&buf, config).map_err(|_| "Invalid file data")?;
-    let (waveform_path, hash, usages) = data;
+    let data = load_data(filename)?;
     show_data(
-        usages,
-        waveform_path,
-        Some(hash),
+        data.usages,
+        data.trace_path,
+        Some(data.hash),
+        data.timescale,
         output_type,
         &mut std::io::stdout(),
         verbose,
@@ -94,38 +149,40 @@ pub fn visualization_from_file(
 fn prepare_data(
     usages: Vec<BusData>,
     trace: String,
+    timescale: Timescale,
     out: &mut impl Write,
 ) -> Result<(), Box<dyn Error>> {
     let hash = calculate_file_hash(&trace)
         .map_err(|e| format!("[ERROR] failed to calculate trace hash: {e}"))?;
-    let data = (trace, hash.to_string(), usages);
-    let config = bincode::config::standard();
-    let data = bincode::encode_to_vec(data, config).map_err(|_| "Serialization failed")?;
-    let mut encoder = flate2::write::GzEncoder::new(out, Compression::default());
-    encoder
-        .write_all(&data)
-        .map_err(|e| format!("Write to file failed {e}"))?;
-    Ok(())
+    SavedData {
+        trace_path: trace,
+        hash: hash.to_string(),
+        timescale,
+        usages,
+    }
+    .save(out)
 }
 
 fn save_data(
     usages: Vec<BusData>,
     trace: String,
+    timescale: Timescale,
     out: &mut impl Write,
 ) -> Result<(), Box<dyn Error>> {
-    prepare_data(usages, trace, out)
+    prepare_data(usages, trace, timescale, out)
 }
 
 #[cfg(feature = "generate-html")]
 fn generate_html(
     usages: Vec<BusData>,
     trace: String,
+    timescale: Timescale,
     out: &mut impl Write,
 ) -> Result<(), Box<dyn Error>> {
     use base64::prelude::*;
 
     let mut busperf_data = Vec::new();
-    prepare_data(usages, trace, &mut busperf_data)?;
+    prepare_data(usages, trace, timescale, &mut busperf_data)?;
 
     let busperf_data = BASE64_STANDARD.encode(busperf_data);
 
@@ -0,0 +1,524 @@
+//! Static HTML report with tables and inline SVG charts, viewable without JavaScript.
+
+use std::{collections::BTreeSet, error::Error, fmt::Write as _, io::Write};
+
+use libbusperf::{
+    Timescale,
+    binning::Binning,
+    bus_usage::{BucketsStatistic, BusData, PercentageStatistic, Statistic, TimelineStatistic},
+};
+
+use super::{
+    json_output::split_unit,
+    text_output::{get_root_causes, summary_cells},
+};
+
+const WIDTH: f64 = 640.0;
+const HEIGHT: f64 = 280.0;
+// Space for axis labels
+const MARGIN: f64 = 50.0;
+
+const STYLE: &str = "body{font-family:sans-serif;margin:2em;color:#222}\
+table{border-collapse:collapse;margin:0.5em 0}\
+td,th{border:1px solid #ccc;padding:0.2em 0.6em;text-align:right}\
+th{background:#eee}td:first-child,th:first-child{text-align:left}\
+section{border-top:2px solid #888;margin-top:2em}\
+.charts{display:flex;flex-wrap:wrap;gap:1em}\
+svg text{font-size:11px}.warn{color:#a60}.error{color:#c00}";
+
+// Same colors as in the GUI
+fn color(i: usize) -> String {
+    let golden_ratio = (5.0_f64.sqrt() - 1.0) / 2.0;
+    format!(
+        "hsl({:.0},85%,40%)",
+        (i as f64 * golden_ratio).fract() * 360.0
+    )
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn legend(html: &mut String, entries: &[(usize, &str)], y: f64) -> std::fmt::Result {
+    let mut x = MARGIN;
+    for (i, name) in entries {
+        write!(
+            html,
+            r#"<rect x="{x}" y="{}" width="10" height="10" fill="{}"/><text x="{}" y="{y}">{}</text>"#,
+            y - 9.0,
+            color(*i),
+            x + 14.0,
+            escape(name)
+        )?;
+        x += 24.0 + 7.0 * name.len() as f64;
+    }
+    Ok(())
+}
+
+// Draws axes with grid lines and values on the y axis from 0 to `max`
+fn axes(html: &mut String, max: f64, x_label: &str, y_label: &str) -> std::fmt::Result {
+    let (bottom, right) = (HEIGHT - MARGIN, WIDTH - 10.0);
+    for i in 0..=4 {
+        let y = bottom - (bottom - 20.0) * i as f64 / 4.0;
+        write!(
+            html,
+            r##"<line x1="{MARGIN}" y1="{y}" x2="{right}" y2="{y}" stroke="#ddd"/><text x="{}" y="{}" text-anchor="end">{}</text>"##,
+            MARGIN - 4.0,
+            y + 4.0,
+            format_number(max * i as f64 / 4.0)
+        )?;
+    }
+    write!(
+        html,
+        r##"<line x1="{MARGIN}" y1="{bottom}" x2="{right}" y2="{bottom}" stroke="#222"/><line x1="{MARGIN}" y1="20" x2="{MARGIN}" y2="{bottom}" stroke="#222"/><text x="{}" y="{}" text-anchor="middle">{}</text><text x="12" y="{}" text-anchor="middle" transform="rotate(-90 12 {})">{}</text>"##,
+        (MARGIN + right) / 2.0,
+        HEIGHT - 18.0,
+        escape(x_label),
+        bottom / 2.0,
+        bottom / 2.0,
+        escape(y_label)
+    )
+}
+
+fn format_number(value: f64) -> String {
+    let value = format!("{value:.4}");
+    value.trim_end_matches('0').trim_end_matches('.').to_owned()
+}
+
+/// Bar chart of buckets of bucket statistics with the same binning, grouped by bucket.
+fn buckets_chart(html: &mut String, statistics: &[(usize, &BucketsStatistic)]) -> std::fmt::Result {
+    let buckets = statistics
+        .iter()
+        .map(|(_, s)| s.get_buckets())
+        .collect::<Vec<_>>();
+    let all = buckets
+        .iter()
+        .flat_map(|b| b.keys().copied())
+        .collect::<BTreeSet<_>>();
+    if all.is_empty() {
+        return Ok(());
+    }
+    let max = buckets
+        .iter()
+        .flat_map(|b| b.values().copied())
+        .max()
+        .unwrap_or(1) as f64;
+    write!(
+        html,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{WIDTH}" height="{HEIGHT}">"#
+    )?;
+    axes(html, max, "Value [clock cycles]", "Number of occurrences")?;
+    let group = (WIDTH - 10.0 - MARGIN) / all.len() as f64;
+    let bar = group * 0.8 / statistics.len() as f64;
+    let bottom = HEIGHT - MARGIN;
+    for (g, bucket) in all.iter().enumerate() {
+        let x = MARGIN + g as f64 * group;
+        for (i, ((stat_id, statistic), counts)) in statistics.iter().zip(&buckets).enumerate() {
+            if let Some(&count) = counts.get(bucket) {
+                let h = (bottom - 20.0) * count as f64 / max;
+                write!(
+                    html,
+                    r#"<rect x="{:.1}" y="{:.1}" width="{bar:.1}" height="{h:.1}" fill="{}"><title>{}: {} x{count}</title></rect>"#,
+                    x + group * 0.1 + i as f64 * bar,
+                    bottom - h,
+                    color(*stat_id),
+                    escape(statistic.name),
+                    statistic.bucket_label(*bucket)
+                )?;
+            }
+        }
+        write!(
+            html,
+            r#"<text x="{:.1}" y="{}" text-anchor="middle">{}</text>"#,
+            x + group / 2.0,
+            bottom + 14.0,
+            statistics[0].1.bucket_label(*bucket)
+        )?;
+    }
+    let entries = statistics
+        .iter()
+        .map(|(i, s)| (*i, s.name))
+        .collect::<Vec<_>>();
+    legend(html, &entries, 14.0)?;
+    write!(html, "</svg>")
+}
+
+/// Pie chart of a percentage statistic.
+fn pie_chart(html: &mut String, statistic: &PercentageStatistic) -> std::fmt::Result {
+    let sum: f64 = statistic.data_labels.iter().map(|(v, _)| *v as f64).sum();
+    if sum <= 0.0 {
+        return Ok(());
+    }
+    let (cx, cy, r) = (HEIGHT / 2.0, HEIGHT / 2.0, HEIGHT / 2.0 - 20.0);
+    write!(
+        html,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{WIDTH}" height="{HEIGHT}">"#
+    )?;
+    let mut angle = 0.0f64;
+    let mut legend_y = 30.0;
+    for (i, (value, label)) in statistic.data_labels.iter().enumerate() {
+        if *value <= 0.0 {
+            continue;
+        }
+        let fraction = *value as f64 / sum;
+        let title = format!("{label}: {value} ({:.2}%)", fraction * 100.0);
+        if fraction >= 1.0 {
+            write!(
+                html,
+                r#"<circle cx="{cx}" cy="{cy}" r="{r}" fill="{}"><title>{title}</title></circle>"#,
+                color(i)
+            )?;
+        } else {
+            let end = angle + fraction * std::f64::consts::TAU;
+            let point = |a: f64| (cx + r * a.sin(), cy - r * a.cos());
+            let (x1, y1) = point(angle);
+            let (x2, y2) = point(end);
+            write!(
+                html,
+                r#"<path d="M{cx},{cy} L{x1:.2},{y1:.2} A{r},{r} 0 {},1 {x2:.2},{y2:.2} Z" fill="{}"><title>{title}</title></path>"#,
+                u8::from(fraction > 0.5),
+                color(i)
+            )?;
+            angle = end;
+        }
+        write!(
+            html,
+            r#"<rect x="{}" y="{}" width="10" height="10" fill="{}"/><text x="{}" y="{legend_y}">{}</text>"#,
+            HEIGHT + 10.0,
+            legend_y - 9.0,
+            color(i),
+            HEIGHT + 24.0,
+            escape(&title)
+        )?;
+        legend_y += 18.0;
+    }
+    write!(html, "</svg>")
+}
+
+// Unit of plotted values, which for thresholds of bandwidth of multichannel buses is the unit of
+// bandwidth rather than of the percentage of time they are exceeded
+fn plotted_unit(statistic: &TimelineStatistic) -> Option<&'static str> {
+    match statistic.name {
+        "Bandwidth above x rate [%]" | "Bandwidth below y rate [%]" => Some("t/clk"),
+        name => split_unit(name).1,
+    }
+}
+
+/// Line chart of timeline statistics in the same unit with their markers.
+fn timeline_chart(
+    html: &mut String,
+    statistics: &[(usize, &TimelineStatistic)],
+    unit: Option<&str>,
+    timescale: Timescale,
+) -> std::fmt::Result {
+    let points = statistics
+        .iter()
+        .flat_map(|(_, s)| s.values.iter())
+        .collect::<Vec<_>>();
+    if points.is_empty() {
+        return Ok(());
+    }
+    let start = points.iter().map(|[x, _]| *x).fold(f64::INFINITY, f64::min);
+    let end = points
+        .iter()
+        .map(|[x, _]| *x)
+        .fold(f64::NEG_INFINITY, f64::max);
+    let max = points.iter().map(|[_, y]| *y).fold(0.0, f64::max);
+    let max = if max > 0.0 { max } else { 1.0 };
+    let span = if end > start { end - start } else { 1.0 };
+    let bottom = HEIGHT - MARGIN;
+    let right = WIDTH - 10.0;
+    let x = |t: f64| MARGIN + (right - MARGIN) * (t - start) / span;
+    let y = |v: f64| bottom - (bottom - 20.0) * v / max;
+    write!(
+        html,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{WIDTH}" height="{HEIGHT}">"#
+    )?;
+    let y_label = match unit {
+        Some(unit) => format!("Value [{unit}]"),
+        None => "Value".to_owned(),
+    };
+    axes(html, max, "Time", &y_label)?;
+    for t in [start, end] {
+        write!(
+            html,
+            r#"<text x="{:.1}" y="{}" text-anchor="middle">{}</text>"#,
+            x(t),
+            bottom + 14.0,
+            timescale.format_time(t as u64)
+        )?;
+    }
+    for (stat_id, statistic) in statistics {
+        for marker in statistic
+            .vertical_lines
+            .iter()
+            .filter(|m| (start..=end).contains(*m))
+        {
+            write!(
+                html,
+                r#"<line x1="{0:.1}" y1="20" x2="{0:.1}" y2="{bottom}" stroke="{1}" stroke-dasharray="4" opacity="0.5"/>"#,
+                x(*marker),
+                color(*stat_id)
+            )?;
+        }
+        let line = statistic
+            .values
+            .iter()
+            .map(|[t, v]| format!("{:.1},{:.1}", x(*t), y(*v)))
+            .collect::<Vec<_>>()
+            .join(" ");
+        write!(
+            html,
+            r#"<polyline points="{line}" fill="none" stroke="{}" stroke-width="1.5"><title>{}</title></polyline>"#,
+            color(*stat_id),
+            escape(statistic.name)
+        )?;
+    }
+    let entries = statistics
+        .iter()
+        .filter(|(_, s)| !s.values.is_empty())
+        .map(|(i, s)| (*i, s.name))
+        .collect::<Vec<_>>();
+    legend(html, &entries, 14.0)?;
+    write!(html, "</svg>")
+}
+
+fn table(html: &mut String, header: &[&str], rows: &[Vec<String>]) -> std::fmt::Result {
+    write!(html, "<table><tr>")?;
+    for h in header {
+        write!(html, "<th>{}</th>", escape(h))?;
+    }
+    write!(html, "</tr>")?;
+    for row in rows {
+        write!(html, "<tr>")?;
+        for cell in row {
+            write!(html, "<td>{}</td>", escape(cell))?;
+        }
+        write!(html, "</tr>")?;
+    }
+    writeln!(html, "</table>")
+}
+
+/// Table and chart of buckets of statistics with the same binning.
+fn distributions(html: &mut String, buckets: &[(usize, &BucketsStatistic)]) -> std::fmt::Result {
+    let all = buckets
+        .iter()
+        .flat_map(|(_, s)| s.get_buckets().into_keys())
+        .collect::<BTreeSet<_>>();
+    let counts = buckets
+        .iter()
+        .map(|(_, s)| s.get_buckets())
+        .collect::<Vec<_>>();
+    let rows = all
+        .iter()
+        .map(|b| {
+            let mut row = vec![buckets[0].1.bucket_label(*b)];
+            row.extend(
+                counts
+                    .iter()
+                    .map(|c| c.get(b).map(|n| n.to_string()).unwrap_or_default()),
+            );
+            row
+        })
+        .collect::<Vec<_>>();
+    let mut header = vec!["Clock cycles"];
+    header.extend(buckets.iter().map(|(_, s)| s.name));
+    if rows.is_empty() {
+        writeln!(html, "<p>No transaction on this bus</p>")?;
+    } else {
+        table(html, &header, &rows)?;
+        buckets_chart(html, buckets)?;
+    }
+    Ok(())
+}
+
+fn bus_section(
+    html: &mut String,
+    bus: &BusData,
+    timescale: Timescale,
+    skipped_stats: &[String],
+) -> std::fmt::Result {
+    writeln!(html, "<section><h2>{}</h2>", escape(&bus.display_name()))?;
+    let statistics = bus.get_statistics(skipped_stats);
+    let mut percentages = vec![];
+    let mut buckets = vec![];
+    let mut timelines = vec![];
+    for (i, statistic) in statistics.iter().enumerate() {
+        match statistic {
+            Statistic::Percentage(s) => percentages.push(s),
+            Statistic::Bucket(s) => buckets.push((i, s)),
+            Statistic::Timeline(s) => timelines.push((i, s)),
+        }
+    }
+
+    for statistic in percentages.iter() {
+        writeln!(html, "<h3>{}</h3>", escape(statistic.name))?;
+        writeln!(html, "<p>{}</p>", escape(statistic.description))?;
+        let sum: f32 = statistic.data_labels.iter().map(|(v, _)| v).sum();
+        let rows = statistic
+            .data_labels
+            .iter()
+            .map(|(v, l)| {
+                vec![
+                    l.to_string(),
+                    v.to_string(),
+                    if sum > 0.0 {
+                        format!("{:.2}", v / sum * 100.0)
+                    } else {
+                        String::new()
+                    },
+                ]
+            })
+            .collect::<Vec<_>>();
+        writeln!(html, "<div class=\"charts\"><div>")?;
+        table(html, &["State", "Clock cycles", "%"], &rows)?;
+        writeln!(html, "</div>")?;
+        pie_chart(html, statistic)?;
+        writeln!(html, "</div>")?;
+    }
+
+    if !timelines.is_empty() {
+        writeln!(html, "<h3>Timeline</h3>")?;
+        let rows = timelines
+            .iter()
+            .map(|(_, s)| {
+                vec![
+                    s.name.to_owned(),
+                    s.display.clone(),
+                    s.description.to_owned(),
+                ]
+            })
+            .collect::<Vec<_>>();
+        table(html, &["Statistic", "Value", "Description"], &rows)?;
+        // Statistics in different units, e.g. percentages and transactions per clock cycle,
+        // would not be readable on a common axis
+        let mut units = vec![];
+        for (_, s) in timelines.iter() {
+            let unit = plotted_unit(s);
+            if !units.contains(&unit) {
+                units.push(unit);
+            }
+        }
+        writeln!(html, "<div class=\"charts\">")?;
+        for unit in units {
+            let group = timelines
+                .iter()
+                .filter(|(_, s)| plotted_unit(s) == unit)
+                .copied()
+                .collect::<Vec<_>>();
+            timeline_chart(html, &group, unit, timescale)?;
+        }
+        writeln!(html, "</div>")?;
+    }
+
+    if !buckets.is_empty() {
+        writeln!(html, "<h3>Distributions</h3>")?;
+        // Buckets of statistics with different binning cannot be shown together
+        let mut binnings: Vec<&Binning> = vec![];
+        for (_, s) in buckets.iter() {
+            if !binnings.contains(&&s.binning) {
+                binnings.push(&s.binning);
+            }
+        }
+        for binning in binnings {
+            let group = buckets
+                .iter()
+                .filter(|(_, s)| s.binning == *binning)
+                .copied()
+                .collect::<Vec<_>>();
+            distributions(html, &group)?;
+        }
+        let summaries = buckets
+            .iter()
+            .map(|(_, s)| {
+                let mut row = vec![s.name.to_owned()];
+                row.extend(summary_cells(s));
+                row.push(s.description.to_owned());
+                row
+            })
+            .collect::<Vec<_>>();
+        table(
+            html,
+            &[
+                "Statistic",
+                "Count",
+                "Min",
+                "p50",
+                "p90",
+                "p99",
+                "Max",
+                "Mean",
+                "Std dev",
+                "Description",
+            ],
+            &summaries,
+        )?;
+    }
+
+    if !bus.diagnostics.is_empty() {
+        writeln!(html, "<h3>Diagnostics</h3><ul>")?;
+        for d in bus.diagnostics.iter() {
+            let class = match d.severity {
+                libbusperf::diagnostics::Severity::Warning => "warn",
+                libbusperf::diagnostics::Severity::Error => "error",
+            };
+            writeln!(
+                html,
+                "<li class=\"{class}\">[{}] {} ({} occurrences, first at {})</li>",
+                d.severity,
+                escape(&d.message),
+                d.count,
+                timescale.format_time(d.time)
+            )?;
+        }
+        writeln!(html, "</ul>")?;
+    }
+    writeln!(html, "</section>")
+}
+
+/// Writes a self-contained HTML report with statistics of all buses.
+pub fn generate_html_report(
+    write: &mut impl Write,
+    usages: &[&BusData],
+    trace_path: &str,
+    timescale: Timescale,
+    skipped_stats: &[String],
+) -> Result<(), Box<dyn Error>> {
+    let mut html = String::new();
+    writeln!(
+        html,
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>busperf: {0}</title><style>{STYLE}</style></head><body>\n<h1>busperf report</h1><p>Trace: {0}</p>",
+        escape(trace_path)
+    )?;
+    if usages.len() > 1 {
+        writeln!(html, "<ul>")?;
+        for (i, u) in usages.iter().enumerate() {
+            writeln!(
+                html,
+                "<li><a href=\"#bus{i}\">{}</a></li>",
+                escape(&u.display_name())
+            )?;
+        }
+        writeln!(html, "</ul>")?;
+    }
+    let (header, rows) = get_root_causes(usages);
+    if !rows.is_empty() {
+        writeln!(
+            html,
+            "<section><h2>Backpressure root causes</h2><p>Backpressure cycles of buses which feed other buses, attributed to the bus that held them back.</p>"
+        )?;
+        let header = header.iter().map(|h| h.as_str()).collect::<Vec<_>>();
+        table(&mut html, &header, &rows)?;
+        writeln!(html, "</section>")?;
+    }
+    for (i, u) in usages.iter().enumerate() {
+        writeln!(html, "<a id=\"bus{i}\"></a>")?;
+        bus_section(&mut html, u, timescale, skipped_stats)?;
+    }
+    writeln!(html, "</body></html>")?;
+    write.write_all(html.as_bytes())?;
+    Ok(())
+}
@@ -0,0 +1,239 @@
+//! Output of all statistics as JSON, the schema is described in `docs/source/output.md`.
+
+use std::{collections::BTreeMap, error::Error, io::Write};
+
+use libbusperf::{
+    CyclesNum, Timescale,
+    bus_usage::{BusData, BusUsage, Statistic},
+    diagnostics::Severity,
+};
+use serde::Serialize;
+
+use crate::root_cause::{RootCause, root_causes};
+
+/// Version of the schema, increased on incompatible changes.
+const FORMAT_VERSION: u32 = 1;
+
+#[derive(Serialize)]
+struct Report<'a> {
+    format_version: u32,
+    trace: &'a str,
+    timescale: JsonTimescale,
+    buses: Vec<Bus<'a>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    root_causes: Vec<RootCause>,
+}
+
+#[derive(Serialize)]
+struct JsonTimescale {
+    factor: u32,
+    exponent: i8,
+}
+
+#[derive(Serialize)]
+struct Bus<'a> {
+    name: &'a str,
+    phase: Option<&'a str>,
+    kind: &'static str,
+    signals: Vec<String>,
+    statistics: Vec<JsonStatistic>,
+    diagnostics: Vec<JsonDiagnostic<'a>>,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum JsonStatistic {
+    Percentage {
+        name: &'static str,
+        description: &'static str,
+        unit: &'static str,
+        values: BTreeMap<&'static str, f32>,
+    },
+    Buckets {
+        name: &'static str,
+        description: &'static str,
+        unit: &'static str,
+        clock_period: u64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        summary: Option<Summary>,
+        buckets: Vec<Bucket>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        periods: Option<Vec<JsonPeriod>>,
+    },
+    Timeline {
+        name: &'static str,
+        description: &'static str,
+        unit: Option<&'static str>,
+        value: f64,
+        display: String,
+        series: Vec<[f64; 2]>,
+        markers: Vec<f64>,
+    },
+}
+
+#[derive(Serialize)]
+struct Summary {
+    count: usize,
+    min: i32,
+    max: i32,
+    mean: f64,
+    std_dev: f64,
+    p50: i32,
+    p90: i32,
+    p99: i32,
+}
+
+#[derive(Serialize)]
+struct Bucket {
+    bucket: i32,
+    range: String,
+    min: Option<i64>,
+    max: Option<i64>,
+    count: usize,
+}
+
+#[derive(Serialize)]
+struct JsonPeriod {
+    start: u64,
+    end: u64,
+    duration: i32,
+}
+
+#[derive(Serialize)]
+struct JsonDiagnostic<'a> {
+    severity: &'static str,
+    kind: &'a str,
+    message: &'a str,
+    time: u64,
+    count: u32,
+}
+
+// Splits e.g. `Bandwidth [t/clk]` into the name and the unit
+pub(crate) fn split_unit(name: &'static str) -> (&'static str, Option<&'static str>) {
+    match name.rsplit_once(" [") {
+        Some((name, unit)) => (name, unit.strip_suffix(']')),
+        None => (name, None),
+    }
+}
+
+fn statistic(statistic: &Statistic, verbose: bool) -> JsonStatistic {
+    match statistic {
+        Statistic::Percentage(s) => JsonStatistic::Percentage {
+            name: s.name,
+            description: s.description,
+            unit: "clock cycles",
+            values: s.data_labels.iter().map(|(v, l)| (*l, *v)).collect(),
+        },
+        Statistic::Bucket(s) => {
+            let buckets: BTreeMap<_, _> = s.get_buckets().into_iter().collect();
+            JsonStatistic::Buckets {
+                name: s.name,
+                description: s.description,
+                unit: "clock cycles",
+                clock_period: s.clk_to_time,
+                summary: s.summary().map(|s| Summary {
+                    count: s.count,
+                    min: s.min,
+                    max: s.max,
+                    mean: s.mean,
+                    std_dev: s.std_dev,
+                    p50: s.p50,
+                    p90: s.p90,
+                    p99: s.p99,
+                }),
+                buckets: buckets
+                    .into_iter()
+                    .map(|(bucket, count)| {
+                        let (min, max) = s.bucket_bounds(bucket);
+                        // Open-ended bins are bounded by the range of durations
+                        Bucket {
+                            bucket,
+                            range: s.bucket_label(bucket),
+                            min: (min > CyclesNum::MIN as i64).then_some(min),
+                            max: (max < CyclesNum::MAX as i64).then_some(max),
+                            count,
+                        }
+                    })
+                    .collect(),
+                periods: verbose.then(|| {
+                    s.data
+                        .iter()
+                        .map(|p| JsonPeriod {
+                            start: p.start(),
+                            end: p.end(),
+                            duration: p.duration(),
+                        })
+                        .collect()
+                }),
+            }
+        }
+        Statistic::Timeline(s) => {
+            let (name, unit) = split_unit(s.name);
+            JsonStatistic::Timeline {
+                name,
+                description: s.description,
+                unit,
+                value: s.value,
+                display: s.display.clone(),
+                series: s.values.clone(),
+                markers: s.vertical_lines.clone(),
+            }
+        }
+    }
+}
+
+/// Writes all statistics of `usages` as a JSON document. Periods of bucket statistics are listed
+/// only if `verbose` is set.
+pub fn generate_json(
+    write: &mut impl Write,
+    usages: &[&BusData],
+    trace_path: &str,
+    timescale: Timescale,
+    verbose: bool,
+    skipped_stats: &[String],
+) -> Result<(), Box<dyn Error>> {
+    let buses = usages
+        .iter()
+        .map(|u| Bus {
+            name: u.usage.get_name(),
+            phase: u.phase.as_deref(),
+            kind: match u.usage {
+                BusUsage::SingleChannel(_) => "single_channel",
+                BusUsage::MultiChannel(_) => "multi_channel",
+            },
+            signals: u.signals.iter().map(|s| s.to_string()).collect(),
+            statistics: u
+                .get_statistics(skipped_stats)
+                .iter()
+                .map(|s| statistic(s, verbose))
+                .collect(),
+            diagnostics: u
+                .diagnostics
+                .iter()
+                .map(|d| JsonDiagnostic {
+                    severity: match d.severity {
+                        Severity::Warning => "warning",
+                        Severity::Error => "error",
+                    },
+                    kind: &d.kind,
+                    message: &d.message,
+                    time: d.time,
+                    count: d.count,
+                })
+                .collect(),
+        })
+        .collect();
+    let report = Report {
+        format_version: FORMAT_VERSION,
+        trace: trace_path,
+        timescale: JsonTimescale {
+            factor: timescale.factor,
+            exponent: timescale.exponent,
+        },
+        buses,
+        root_causes: root_causes(usages),
+    };
+    serde_json::to_writer_pretty(&mut *write, &report)?;
+    writeln!(write)?;
+    Ok(())
+}
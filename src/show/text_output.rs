@@ -1,8 +1,17 @@
 use std::{collections::BTreeMap, error::Error, io::Write};
 
-use libbusperf::bus_usage::{BusUsage, Statistic};
+use libbusperf::{
+    Timescale,
+    bus_usage::{BucketsStatistic, BusData, BusUsage, Statistic},
+};
 
-fn generate_tabled<O>(header: &Vec<String>, data: &Vec<Vec<String>>, style: O) -> tabled::Table
+use crate::root_cause::root_causes;
+
+pub(crate) fn generate_tabled<O>(
+    header: &Vec<String>,
+    data: &Vec<Vec<String>>,
+    style: O,
+) -> tabled::Table
 where
     O: tabled::settings::TableOption<
             tabled::grid::records::vec_records::VecRecords<
@@ -22,11 +31,19 @@ where
     t
 }
 
-fn get_header(usages: &[&BusUsage], skipped_stats: &[String]) -> Vec<String> {
+// Phase column is shown only if the analysis was split into phases
+fn has_phases(usages: &[&BusData]) -> bool {
+    usages.iter().any(|u| u.phase.is_some())
+}
+
+fn get_header(usages: &[&BusData], skipped_stats: &[String]) -> Vec<String> {
     if usages.is_empty() {
         return vec![];
     }
     let mut header = vec![String::from("bus name")];
+    if has_phases(usages) {
+        header.push(String::from("phase"));
+    }
     let stats = usages
         .iter()
         .map(|u| u.get_statistics(skipped_stats))
@@ -47,11 +64,20 @@ fn get_header(usages: &[&BusUsage], skipped_stats: &[String]) -> Vec<String> {
     header
 }
 
-fn get_data(usages: &[&BusUsage], verbose: bool, skipped_stats: &[String]) -> Vec<Vec<String>> {
+fn get_data(
+    usages: &[&BusData],
+    timescale: Timescale,
+    verbose: bool,
+    skipped_stats: &[String],
+) -> Vec<Vec<String>> {
+    let phases = has_phases(usages);
     usages
         .iter()
         .map(|u| {
-            let mut v = vec![u.get_name().to_owned()];
+            let mut v = vec![u.usage.get_name().to_owned()];
+            if phases {
+                v.push(u.phase.clone().unwrap_or_default());
+            }
             for s in u.get_statistics(skipped_stats).iter() {
                 match s {
                     Statistic::Percentage(percentage_statistic) => {
@@ -61,7 +87,19 @@ fn get_data(usages: &[&BusUsage], verbose: bool, skipped_stats: &[String]) -> Ve
                     }
                     Statistic::Bucket(buckets_statistic) => {
                         if verbose {
-                            v.push(format!("{:?}", buckets_statistic.data));
+                            let periods = buckets_statistic
+                                .data
+                                .iter()
+                                .map(|p| {
+                                    format!(
+                                        "{}-{}: {}",
+                                        timescale.format_time(p.start()),
+                                        timescale.format_time(p.end()),
+                                        p.duration()
+                                    )
+                                })
+                                .collect::<Vec<_>>();
+                            v.push(format!("[{}]", periods.join(", ")));
                         } else {
                             let buckets: BTreeMap<_, _> =
                                 buckets_statistic.get_buckets().into_iter().collect();
@@ -69,24 +107,11 @@ fn get_data(usages: &[&BusUsage], verbose: bool, skipped_stats: &[String]) -> Ve
                                 .iter()
                                 .filter_map(|(&i, v)| {
                                     if *v > 0 {
-                                        Some(if i < 2 {
-                                            format!("{} x{}", i, *v)
-                                        } else if i >= 41 {
-                                            format!("2^{}+ x{}", i, *v)
-                                        } else if i >= 21 {
-                                            let i = i as u32 - 20;
-                                            format!("{}-{}M x{}", 1 << (i - 1), 1 << i, *v)
-                                        } else if i >= 11 {
-                                            let i = i as u32 - 10;
-                                            format!("{}-{}k x{}", 1 << (i - 1), 1 << i, *v)
-                                        } else {
-                                            format!(
-                                                "{}-{} x{}",
-                                                1 << (i as u64 - 1),
-                                                (1 << i as u64) - 1,
-                                                *v
-                                            )
-                                        })
+                                        Some(format!(
+                                            "{} x{}",
+                                            buckets_statistic.bucket_label(i),
+                                            *v
+                                        ))
                                     } else {
                                         None
                                     }
@@ -110,9 +135,174 @@ fn get_data(usages: &[&BusUsage], verbose: bool, skipped_stats: &[String]) -> Ve
         .collect::<Vec<_>>()
 }
 
+/// Count, min, p50, p90, p99, max, mean and std dev of durations of a bucket statistic.
+pub(crate) fn summary_cells(statistic: &BucketsStatistic) -> Vec<String> {
+    match statistic.summary() {
+        Some(summary) => vec![
+            summary.count.to_string(),
+            summary.min.to_string(),
+            summary.p50.to_string(),
+            summary.p90.to_string(),
+            summary.p99.to_string(),
+            summary.max.to_string(),
+            format!("{:.2}", summary.mean),
+            format!("{:.2}", summary.std_dev),
+        ],
+        None => {
+            let mut cells = vec![String::from("0")];
+            cells.extend(std::iter::repeat_n(String::new(), 7));
+            cells
+        }
+    }
+}
+
+// Exact statistics of durations of every bucket statistic, a row for each bus and statistic
+fn get_summary(usages: &[&BusData], skipped_stats: &[String]) -> (Vec<String>, Vec<Vec<String>>) {
+    let phases = has_phases(usages);
+    let mut header = vec![String::from("bus name")];
+    if phases {
+        header.push(String::from("phase"));
+    }
+    header.extend(
+        [
+            "statistic",
+            "count",
+            "min",
+            "p50",
+            "p90",
+            "p99",
+            "max",
+            "mean",
+            "std dev",
+        ]
+        .map(String::from),
+    );
+    let mut rows = vec![];
+    for u in usages {
+        for s in u.get_statistics(skipped_stats) {
+            let Statistic::Bucket(s) = s else {
+                continue;
+            };
+            let mut row = vec![u.usage.get_name().to_owned()];
+            if phases {
+                row.push(u.phase.clone().unwrap_or_default());
+            }
+            row.push(s.name.to_owned());
+            row.extend(summary_cells(&s));
+            rows.push(row);
+        }
+    }
+    (header, rows)
+}
+
+// Statistics of every bus side by side in each phase, a row for each bus and statistic and a
+// column for each phase, empty if the analysis was not split into phases
+fn get_phase_comparison(
+    usages: &[&BusData],
+    skipped_stats: &[String],
+) -> (Vec<String>, Vec<Vec<String>>) {
+    if !has_phases(usages) {
+        return (vec![], vec![]);
+    }
+    let mut phases: Vec<String> = vec![];
+    for u in usages {
+        let phase = u.phase.clone().unwrap_or_default();
+        if !phases.contains(&phase) {
+            phases.push(phase);
+        }
+    }
+    // Values of a bus and statistic in each phase, in order of first appearance
+    let mut rows: Vec<(String, String, Vec<String>)> = vec![];
+    for u in usages {
+        let column = phases
+            .iter()
+            .position(|p| *p == u.phase.clone().unwrap_or_default())
+            .unwrap();
+        let mut values = vec![];
+        for s in u.get_statistics(skipped_stats) {
+            match s {
+                Statistic::Percentage(s) => values.extend(
+                    s.data_labels
+                        .iter()
+                        .map(|(v, l)| ((*l).to_owned(), v.to_string())),
+                ),
+                Statistic::Bucket(s) => values.push((
+                    format!("{} mean", s.name),
+                    s.summary()
+                        .map(|s| format!("{:.2}", s.mean))
+                        .unwrap_or_default(),
+                )),
+                Statistic::Timeline(s) => values.push((s.name.to_owned(), s.display)),
+            }
+        }
+        let name = u.usage.get_name();
+        for (statistic, value) in values {
+            let row = match rows
+                .iter()
+                .position(|(b, s, _)| b == name && *s == statistic)
+            {
+                Some(row) => row,
+                None => {
+                    rows.push((
+                        name.to_owned(),
+                        statistic,
+                        vec![String::new(); phases.len()],
+                    ));
+                    rows.len() - 1
+                }
+            };
+            rows[row].2[column] = value;
+        }
+    }
+    let mut header = vec![String::from("bus name"), String::from("statistic")];
+    header.extend(phases);
+    let rows = rows
+        .into_iter()
+        .map(|(bus, statistic, values)| {
+            let mut row = vec![bus, statistic];
+            row.extend(values);
+            row
+        })
+        .collect();
+    (header, rows)
+}
+
+// Root causes of backpressure on chains of buses, empty if no bus feeds another one
+pub(crate) fn get_root_causes(usages: &[&BusData]) -> (Vec<String>, Vec<Vec<String>>) {
+    let header = [
+        "root cause",
+        "kind",
+        "backpressure cycles",
+        "share [%]",
+        "upstream buses",
+    ]
+    .map(String::from)
+    .to_vec();
+    let rows = root_causes(usages)
+        .into_iter()
+        .map(|c| {
+            let upstream = c
+                .upstream
+                .iter()
+                .map(|(bus, cycles)| format!("{bus} x{cycles}"))
+                .collect::<Vec<_>>()
+                .join("; ");
+            vec![
+                c.bus,
+                c.kind.to_string(),
+                c.cycles.to_string(),
+                format!("{:.2}", c.share),
+                upstream,
+            ]
+        })
+        .collect();
+    (header, rows)
+}
+
 fn print_statistics_internal<O>(
     write: &mut impl Write,
-    usages: &[&BusUsage],
+    usages: &[&BusData],
+    timescale: Timescale,
     verbose: bool,
     style: O,
     skipped_stats: &[String],
@@ -128,72 +318,123 @@ where
 {
     let single_usages = usages
         .iter()
-        .filter_map(|&u| match u {
+        .filter_map(|&u| match u.usage {
             BusUsage::SingleChannel(_) => Some(u),
             _ => None,
         })
         .collect::<Vec<_>>();
     if !single_usages.is_empty() {
         let header = get_header(&single_usages, skipped_stats);
-        let data = get_data(&single_usages, verbose, skipped_stats);
+        let data = get_data(&single_usages, timescale, verbose, skipped_stats);
         writeln!(write, "{}", generate_tabled(&header, &data, style.clone()))?;
     }
 
     let multi_usage: Vec<_> = usages
         .iter()
-        .filter_map(|&u| match u {
+        .filter_map(|&u| match u.usage {
             BusUsage::MultiChannel(_) => Some(u),
             _ => None,
         })
         .collect();
     if !multi_usage.is_empty() {
         let header = get_header(&multi_usage, skipped_stats);
-        let data = get_data(&multi_usage, verbose, skipped_stats);
+        let data = get_data(&multi_usage, timescale, verbose, skipped_stats);
+        writeln!(write, "{}", generate_tabled(&header, &data, style.clone()))?;
+    }
+
+    let (header, data) = get_summary(usages, skipped_stats);
+    if !data.is_empty() {
+        writeln!(write, "{}", generate_tabled(&header, &data, style.clone()))?;
+    }
+
+    let (header, data) = get_phase_comparison(usages, skipped_stats);
+    if !data.is_empty() {
+        writeln!(write, "{}", generate_tabled(&header, &data, style.clone()))?;
+    }
+
+    let (header, data) = get_root_causes(usages);
+    if !data.is_empty() {
         writeln!(write, "{}", generate_tabled(&header, &data, style))?;
     }
     Ok(())
 }
 
+/// Writes a line for every diagnostic of the buses, each starting with `prefix`.
+pub fn print_diagnostics(
+    write: &mut impl Write,
+    usages: &[&BusData],
+    timescale: Timescale,
+    prefix: &str,
+) -> Result<(), Box<dyn Error>> {
+    for u in usages {
+        for d in u.diagnostics.iter() {
+            let times = if d.count > 1 {
+                format!(", {} times", d.count)
+            } else {
+                String::new()
+            };
+            writeln!(
+                write,
+                "{prefix}[{}] {}: {} ({}, at {}{times})",
+                d.severity,
+                u.display_name(),
+                d.message,
+                d.kind,
+                timescale.format_time(d.time),
+            )?;
+        }
+    }
+    Ok(())
+}
+
 pub fn print_statistics(
     write: &mut impl Write,
-    usages: &[&BusUsage],
+    usages: &[&BusData],
+    timescale: Timescale,
     verbose: bool,
     skipped_stats: &[String],
 ) -> Result<(), Box<dyn Error>> {
     print_statistics_internal(
         write,
         usages,
+        timescale,
         verbose,
         tabled::settings::Style::rounded(),
         skipped_stats,
-    )
+    )?;
+    print_diagnostics(write, usages, timescale, "")
 }
 
 pub fn generate_md_table(
     write: &mut impl Write,
-    usages: &[&BusUsage],
+    usages: &[&BusData],
+    timescale: Timescale,
     verbose: bool,
     skipped_stats: &[String],
 ) -> Result<(), Box<dyn Error>> {
     print_statistics_internal(
         write,
         usages,
+        timescale,
         verbose,
         tabled::settings::Style::markdown(),
         skipped_stats,
-    )
+    )?;
+    print_diagnostics(write, usages, timescale, "- ")
 }
 
 pub fn generate_csv(
     write: &mut impl Write,
-    usages: &[&BusUsage],
+    usages: &[&BusData],
+    timescale: Timescale,
     verbose: bool,
     skipped_stats: &[String],
 ) -> Result<(), Box<dyn Error>> {
-    let mut wtr = csv::Writer::from_writer(write);
+    // Tables have different numbers of columns
+    let mut wtr = csv::WriterBuilder::new().flexible(true).from_writer(write);
     let single_usages: Vec<_> = usages
         .iter()
-        .filter_map(|&u| match u {
+        .filter_map(|&u| match u.usage {
             BusUsage::SingleChannel(_) => Some(u),
             _ => None,
         })
@@ -201,14 +442,14 @@ pub fn generate_csv(
     if !single_usages.is_empty() {
         let header = get_header(&single_usages, skipped_stats);
         wtr.write_record(header)?;
-        let data = get_data(&single_usages, verbose, skipped_stats);
+        let data = get_data(&single_usages, timescale, verbose, skipped_stats);
         for d in data {
             wtr.write_record(d)?;
         }
     }
     let multi_usage: Vec<_> = usages
         .iter()
-        .filter_map(|&u| match u {
+        .filter_map(|&u| match u.usage {
             BusUsage::MultiChannel(_) => Some(u),
             _ => None,
         })
@@ -216,11 +457,23 @@ pub fn generate_csv(
     if !multi_usage.is_empty() {
         let header = get_header(&multi_usage, skipped_stats);
         wtr.write_record(header)?;
-        let data = get_data(&multi_usage, verbose, skipped_stats);
+        let data = get_data(&multi_usage, timescale, verbose, skipped_stats);
         for d in data {
             wtr.write_record(d)?;
         }
     }
+    for (header, data) in [
+        get_summary(usages, skipped_stats),
+        get_phase_comparison(usages, skipped_stats),
+        get_root_causes(usages),
+    ] {
+        if !data.is_empty() {
+            wtr.write_record(header)?;
+            for d in data {
+                wtr.write_record(d)?;
+            }
+        }
+    }
     wtr.flush()?;
     Ok(())
 }
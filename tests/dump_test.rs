@@ -1,5 +1,7 @@
 use busperf::analyze::*;
-use libbusperf::bus_usage::{self, BusUsage, Period, SingleChannelBusUsage};
+use libbusperf::Timescale;
+use libbusperf::bus_usage::{self, BusUsage, Period, SingleChannelBusUsage, Statistic};
+use libbusperf::diagnostics::Severity;
 
 // helper function to check if analyzer returns expected result
 fn test(trace: &str, yaml: &str, max_burst_delay: i32, correct: &[BusUsage]) {
@@ -27,7 +29,7 @@ fn test_basic(trace: &str, yaml: &str, num: usize) {
     let mut descs = load_bus_analyzers(yaml, 0, 10000, 0.0001, 0.00001, "plugins/python").unwrap();
     for desc in descs.iter_mut() {
         desc.analyze(&mut data, false).unwrap();
-        assert!(matches!(desc.get_results(), Some(_)))
+        assert!(desc.get_results().is_some())
     }
     assert!(descs.len() == num)
 }
@@ -59,6 +61,9 @@ fn dump_rst_high() {
         0,
         bus_usage::CurrentlyCalculating::Delay,
         2000,
+        10000,
+        window(0, 30, 15, 0, 15),
+        None,
     ));
     let correct_b = BusUsage::SingleChannel(SingleChannelBusUsage::literal(
         "b_",
@@ -73,6 +78,9 @@ fn dump_rst_high() {
         0,
         bus_usage::CurrentlyCalculating::Delay,
         2000,
+        10000,
+        window(0, 30, 0, 0, 15),
+        None,
     ));
     test(
         "tests/test_dumps/dump.vcd",
@@ -112,6 +120,26 @@ fn nested_scopes() {
     );
 }
 
+// results keep scopes of modules in which buses are described, used to group buses in the GUI
+#[test]
+fn bus_scopes() {
+    use busperf::session::{AnalysisConfig, Session};
+
+    let results = Session::new(AnalysisConfig::default())
+        .bus_description_file("tests/test_dumps/nested_scopes.yaml")
+        .unwrap()
+        .trace_file("tests/test_dumps/nested_scopes.vcd")
+        .unwrap()
+        .analyze()
+        .unwrap();
+    let scopes = results
+        .buses
+        .iter()
+        .map(|b| b.scope.join("."))
+        .collect::<Vec<_>>();
+    assert_eq!(scopes, ["top.tb.$rootio", "top.tb.some_module"]);
+}
+
 #[test]
 fn common_clk_rst_ifs() {
     test_basic(
@@ -137,6 +165,9 @@ fn basic_max_burst_delay() {
         2,
         bus_usage::CurrentlyCalculating::Burst,
         2,
+        10000,
+        window(0, 22, 9, 5, 2),
+        None,
     ));
     test(
         "tests/test_dumps/test.vcd",
@@ -146,6 +177,101 @@ fn basic_max_burst_delay() {
     );
 }
 
+// test max_burst_delay set for the bus in yaml
+#[test]
+fn yaml_max_burst_delay() {
+    let correct = BusUsage::SingleChannel(SingleChannelBusUsage::literal(
+        "test",
+        9,
+        5,
+        3,
+        0,
+        3,
+        2,
+        vec![Period::literal(0, 2, 2), Period::literal(24, 34, 6)],
+        vec![Period::literal(4, 22, 10), Period::literal(36, 42, 4)],
+        2,
+        bus_usage::CurrentlyCalculating::Burst,
+        2,
+        10000,
+        window(0, 22, 9, 5, 2),
+        None,
+    ));
+    test(
+        "tests/test_dumps/test.vcd",
+        "tests/test_dumps/test_max_burst_delay.yaml",
+        0,
+        &[correct],
+    );
+}
+
+// utilization, backpressure and throughput of a single channel bus in rolling windows
+#[test]
+fn single_channel_timeline() {
+    let mut data = load_simulation_trace("tests/test_dumps/test.vcd", false).unwrap();
+    let mut analyzers = load_bus_analyzers(
+        "tests/test_dumps/test.yaml",
+        0,
+        8,
+        0.0001,
+        0.00001,
+        "plugins/python",
+    )
+    .unwrap();
+    analyzers[0].analyze(&mut data, false).unwrap();
+    let usage = analyzers[0].get_results().unwrap();
+    let timelines = usage
+        .get_statistics(&[])
+        .into_iter()
+        .filter_map(|s| match s {
+            Statistic::Timeline(s) => Some(s),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let [utilization, backpressure, throughput] = &timelines[..] else {
+        panic!("expected three timelines");
+    };
+    assert_eq!(
+        (utilization.value, backpressure.value, throughput.value),
+        (70.0, 25.0, 0.45)
+    );
+    assert_eq!(backpressure.values.len(), 6);
+    // windows of 8 cycles, starting every 4 cycles
+    assert_eq!(backpressure.values[3], [24.0, 50.0]);
+    assert_eq!(throughput.values[1], [8.0, 0.625]);
+    assert_eq!(utilization.values[5], [40.0, 100.0]);
+}
+
+// test window length and rates set for each bus in yaml
+#[test]
+fn yaml_analysis_params() {
+    let mut data = load_simulation_trace("tests/test_dumps/axi.vcd", false).unwrap();
+    let mut overridden = load_bus_analyzers(
+        "tests/test_dumps/axi_analysis_params.yaml",
+        0,
+        10000,
+        0.0001,
+        0.00001,
+        "plugins/python",
+    )
+    .unwrap();
+    for (i, (desc, window_length)) in overridden.iter_mut().zip([1000, 500]).enumerate() {
+        desc.analyze(&mut data, false).unwrap();
+        let mut from_args = load_bus_analyzers(
+            "tests/taxi_descriptions/axi_ram.yaml",
+            0,
+            window_length,
+            0.1,
+            0.01,
+            "plugins/python",
+        )
+        .unwrap()
+        .remove(i);
+        from_args.analyze(&mut data, false).unwrap();
+        assert_eq!(desc.get_results(), from_args.get_results());
+    }
+}
+
 // test for credit/valid bus
 #[test]
 fn credit_valid() {
@@ -172,6 +298,9 @@ fn credit_valid() {
         0,
         bus_usage::CurrentlyCalculating::Burst,
         2,
+        10000,
+        window(1, 21, 14, 0, 1),
+        None,
     ));
     test(
         "tests/test_dumps/credit_valid.vcd",
@@ -181,6 +310,51 @@ fn credit_valid() {
     );
 }
 
+// test that repeated problems are reported as a single counted diagnostic
+#[test]
+fn diagnostics() {
+    let mut data = load_simulation_trace("tests/test_dumps/credit_valid.vcd", false).unwrap();
+    let mut analyzers = load_bus_analyzers(
+        "tests/test_dumps/credit_valid.yaml",
+        0,
+        10000,
+        0.0001,
+        0.00001,
+        "plugins/python",
+    )
+    .unwrap();
+    analyzers[0].analyze(&mut data, false).unwrap();
+    let diagnostics = analyzers[0].diagnostics().iter().collect::<Vec<_>>();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].severity, Severity::Warning);
+    assert_eq!(diagnostics[0].kind, "valid_without_credit");
+    // Time of the clock edge at which the cycle is recorded
+    assert_eq!(diagnostics[0].time, 29);
+    assert_eq!(diagnostics[0].count, 5);
+
+    // Occurrences with different details are counted together, keeping the earliest one
+    let mut diagnostics = libbusperf::diagnostics::Diagnostics::default();
+    for time in (0..1000).rev() {
+        diagnostics.warn("unknown_state", format!("valid: {time}"), time);
+    }
+    diagnostics.error("unknown_state", "valid: x", 5);
+    let diagnostics = diagnostics.iter().collect::<Vec<_>>();
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(
+        (diagnostics[0].message.as_str(), diagnostics[0].time),
+        ("valid: 0", 0)
+    );
+    assert_eq!(diagnostics[0].count, 1000);
+
+    let mut data = load_simulation_trace("tests/test_dumps/dump.vcd", false).unwrap();
+    let mut analyzers =
+        load_bus_analyzers("tests/test_dumps/dump.yaml", 0, 10000, 0.0001, 0.00001, "").unwrap();
+    for analyzer in analyzers.iter_mut() {
+        analyzer.analyze(&mut data, false).unwrap();
+        assert!(analyzer.diagnostics().is_empty());
+    }
+}
+
 // test for ahb bus
 #[test]
 fn ahb() {
@@ -209,6 +383,9 @@ fn ahb() {
         0,
         bus_usage::CurrentlyCalculating::Burst,
         2,
+        10000,
+        window(1, 21, 9, 5, 1),
+        None,
     ));
     test(
         "tests/test_dumps/ahb.vcd",
@@ -245,6 +422,9 @@ fn apb() {
         0,
         bus_usage::CurrentlyCalculating::Delay,
         2,
+        10000,
+        window(0, 22, 11, 5, 2),
+        None,
     ));
     test(
         "tests/test_dumps/apb.vcd",
@@ -286,6 +466,274 @@ fn default_analyzer_intervals() {
     );
 }
 
+// intervals with units are converted using timescale of the trace
+#[test]
+fn intervals_with_units() {
+    let mut data = load_simulation_trace("tests/test_dumps/test.vcd", false).unwrap();
+    let timescale = data.timescale().unwrap();
+    assert_eq!(timescale, Timescale::new(1, -12));
+    assert_eq!(timescale.format_time(12_500_000), "12.5us");
+    let results = [
+        "tests/test_dumps/ready_valid_intervals.yaml",
+        "tests/test_dumps/ready_valid_intervals_units.yaml",
+    ]
+    .map(|yaml| {
+        let mut descs =
+            load_bus_analyzers(yaml, 0, 10000, 0.0001, 0.00001, "plugins/python").unwrap();
+        descs[0].analyze(&mut data, false).unwrap();
+        descs[0].get_results().cloned().unwrap()
+    });
+    assert_eq!(results[0], results[1]);
+}
+
+// intervals defined by trace events give the same results as equivalent fixed intervals
+#[test]
+fn intervals_from_events() {
+    let mut data = load_simulation_trace("tests/test_dumps/test.vcd", false).unwrap();
+    let results = [
+        "tests/test_dumps/intervals_events.yaml",
+        "tests/test_dumps/intervals_fixed.yaml",
+    ]
+    .map(|yaml| {
+        let mut descs =
+            load_bus_analyzers(yaml, 0, 10000, 0.0001, 0.00001, "plugins/python").unwrap();
+        descs
+            .iter_mut()
+            .map(|desc| {
+                desc.analyze(&mut data, false).unwrap();
+                desc.get_results().cloned().unwrap()
+            })
+            .collect::<Vec<_>>()
+    });
+    assert_eq!(results[0], results[1]);
+}
+
+// every bus is analyzed separately in each phase
+#[test]
+fn phases() {
+    let mut data = load_simulation_trace("tests/test_dumps/test.vcd", false).unwrap();
+    let mut descs = load_bus_analyzers(
+        "tests/test_dumps/phases.yaml",
+        0,
+        10000,
+        0.0001,
+        0.00001,
+        "plugins/python",
+    )
+    .unwrap();
+    let mut fixed = load_bus_analyzers(
+        "tests/test_dumps/intervals_fixed.yaml",
+        0,
+        10000,
+        0.0001,
+        0.00001,
+        "plugins/python",
+    )
+    .unwrap();
+    assert_eq!(descs.len(), 2);
+    assert_eq!(descs[0].phase(), Some("valid"));
+    assert_eq!(descs[1].phase(), Some("late"));
+    for desc in descs.iter_mut().chain(fixed.iter_mut()) {
+        desc.analyze(&mut data, false).unwrap();
+    }
+    assert_eq!(descs[0].get_results(), fixed[0].get_results());
+    assert_ne!(descs[1].get_results(), fixed[0].get_results());
+
+    // statistics of phases are compared side by side, a column for each phase
+    use busperf::session::{AnalysisConfig, Session};
+    use busperf::show::{OutputType, show_data};
+    let results = Session::new(AnalysisConfig::default())
+        .bus_description_file("tests/test_dumps/phases.yaml")
+        .unwrap()
+        .trace_file("tests/test_dumps/test.vcd")
+        .unwrap()
+        .analyze()
+        .unwrap();
+    let mut out = vec![];
+    show_data(
+        results.buses,
+        "tests/test_dumps/test.vcd".into(),
+        None,
+        results.timescale,
+        OutputType::Md,
+        &mut out,
+        false,
+        &[],
+    )
+    .unwrap();
+    let md = String::from_utf8(out).unwrap();
+    assert!(md.contains("| bus name | statistic               | valid  | late   |"));
+    assert!(md.contains("| while    | Busy                    | 8      | 4      |"));
+    assert!(md.contains("| while    | Throughput [t/clk]      | 0.5714 | 0.3333 |"));
+}
+
+// streamed trace gives the same results as a fully loaded one
+#[test]
+fn streaming() {
+    for (trace, yaml) in [
+        ("tests/test_dumps/dump.vcd", "tests/test_dumps/dump.yaml"),
+        ("tests/test_dumps/test.vcd", "tests/test_dumps/phases.yaml"),
+        (
+            "tests/test_dumps/credit_valid.vcd",
+            "tests/test_dumps/credit_valid.yaml",
+        ),
+        (
+            "tests/test_dumps/axi.vcd",
+            "tests/taxi_descriptions/axi_ram.yaml",
+        ),
+    ] {
+        let results = [false, true].map(|stream| {
+            let mut descs =
+                load_bus_analyzers(yaml, 0, 10000, 0.0001, 0.00001, "plugins/python").unwrap();
+            let mut data = if stream {
+                stream_simulation_trace(trace, &descs, false).unwrap()
+            } else {
+                load_simulation_trace(trace, false).unwrap()
+            };
+            descs
+                .iter_mut()
+                .map(|desc| {
+                    desc.analyze(&mut data, false).unwrap();
+                    desc.get_results().cloned().unwrap()
+                })
+                .collect::<Vec<_>>()
+        });
+        assert_eq!(results[0], results[1], "{trace} {yaml}");
+    }
+}
+
+// gzip compressed trace is detected by its content
+#[test]
+fn compressed_trace() {
+    assert!(is_simulation_trace("tests/test_dumps/dump.vcd.gz"));
+    assert!(is_simulation_trace("tests/test_dumps/axi.vcd"));
+    assert!(!is_simulation_trace("tests/test_dumps/dump.yaml"));
+    assert!(!is_simulation_trace("tests/test_dumps/axi_ipxact.xml"));
+    test(
+        "tests/test_dumps/dump.vcd.gz",
+        "tests/test_dumps/dump.yaml",
+        0,
+        &[correct_dump_a(), correct_dump_b()],
+    );
+    let mut descs = load_bus_analyzers(
+        "tests/test_dumps/dump.yaml",
+        0,
+        10000,
+        0.0001,
+        0.00001,
+        "plugins/python",
+    )
+    .unwrap();
+    let mut data = stream_simulation_trace("tests/test_dumps/dump.vcd.gz", &descs, false).unwrap();
+    for (desc, correct) in descs.iter_mut().zip([correct_dump_a(), correct_dump_b()]) {
+        desc.analyze(&mut data, false).unwrap();
+        assert_eq!(desc.get_results(), Some(&correct));
+    }
+}
+
+// analyzers run on separate threads give the same results in the same order as run one by one
+#[test]
+fn parallel() {
+    for (trace, yaml) in [
+        (
+            "tests/test_dumps/axi.vcd",
+            "tests/taxi_descriptions/axi_ram.yaml",
+        ),
+        (
+            "tests/test_dumps/dump.vcd",
+            "tests/test_dumps/python_dump.yaml",
+        ),
+        ("tests/test_dumps/test.vcd", "tests/test_dumps/phases.yaml"),
+    ] {
+        let mut data = load_simulation_trace(trace, false).unwrap();
+        let mut sequential =
+            load_bus_analyzers(yaml, 0, 10000, 0.0001, 0.00001, "plugins/python").unwrap();
+        let mut parallel =
+            load_bus_analyzers(yaml, 0, 10000, 0.0001, 0.00001, "plugins/python").unwrap();
+        for desc in sequential.iter_mut() {
+            desc.analyze(&mut data, false).unwrap();
+        }
+        for result in analyzer::analyze_all(&mut parallel, &mut data, false) {
+            result.unwrap();
+        }
+        for (sequential, parallel) in sequential.iter().zip(&parallel) {
+            assert_eq!(sequential.get_results(), parallel.get_results());
+        }
+    }
+}
+
+// cached results are reused only for buses with unchanged configuration
+#[test]
+fn result_cache() {
+    let trace = "tests/test_dumps/dump.vcd";
+    let dir = std::env::temp_dir().join("busperf_result_cache_test");
+    let _ = std::fs::remove_dir_all(&dir);
+    let cache = ResultCache::new(dir.to_str().unwrap(), trace).unwrap();
+    let mut data = load_simulation_trace(trace, false).unwrap();
+    let load =
+        |yaml| load_bus_analyzers(yaml, 0, 10000, 0.0001, 0.00001, "plugins/python").unwrap();
+
+    let mut analyzed = load("tests/test_dumps/dump.yaml");
+    assert_eq!(cache.load(&mut analyzed), 0);
+    for desc in analyzed.iter_mut() {
+        desc.analyze(&mut data, false).unwrap();
+    }
+    cache.store(&analyzed).unwrap();
+
+    let mut cached = load("tests/test_dumps/dump.yaml");
+    assert_eq!(cache.load(&mut cached), 2);
+    for (analyzed, cached) in analyzed.iter().zip(&cached) {
+        assert!(cached.finished_analysis());
+        assert_eq!(analyzed.get_results(), cached.get_results());
+    }
+    // only reset of a_ differs
+    let mut changed = load("tests/test_dumps/dump_rst_high.yaml");
+    assert_eq!(cache.load(&mut changed), 1);
+    assert!(!changed[0].finished_analysis());
+    let mut other_params =
+        load_bus_analyzers("tests/test_dumps/dump.yaml", 1, 10000, 0.0001, 0.00001, "").unwrap();
+    assert_eq!(cache.load(&mut other_params), 0);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+// cached results of buses handled by Python plugins are not reused once the plugin changes
+#[test]
+fn result_cache_plugins() {
+    let trace = "tests/test_dumps/dump.vcd";
+    let dir = std::env::temp_dir().join("busperf_result_cache_plugins_test");
+    let _ = std::fs::remove_dir_all(&dir);
+    let plugins = dir.join("plugins");
+    std::fs::create_dir_all(&plugins).unwrap();
+    let plugin = plugins.join("PythonReadyValid.py");
+    std::fs::copy("plugins/python/PythonReadyValid.py", &plugin).unwrap();
+    let cache = ResultCache::new(dir.join("cache").to_str().unwrap(), trace).unwrap();
+    let mut data = load_simulation_trace(trace, false).unwrap();
+    let load = || {
+        load_bus_analyzers(
+            "tests/test_dumps/python_dump.yaml",
+            0,
+            10000,
+            0.0001,
+            0.00001,
+            plugins.to_str().unwrap(),
+        )
+        .unwrap()
+    };
+
+    let mut analyzed = load();
+    for desc in analyzed.iter_mut() {
+        desc.analyze(&mut data, false).unwrap();
+    }
+    cache.store(&analyzed).unwrap();
+    assert_eq!(cache.load(&mut load()), 2);
+
+    let mut code = std::fs::read_to_string(&plugin).unwrap();
+    code.push_str("\n# changed\n");
+    std::fs::write(&plugin, code).unwrap();
+    assert_eq!(cache.load(&mut load()), 0);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
 // test multichannel axi analyzer
 #[test]
 fn axi_test() {
@@ -319,13 +767,170 @@ fn custom_plugin_path() {
     .unwrap();
     for desc in descs.iter_mut() {
         desc.analyze(&mut data, false).unwrap();
-        assert!(matches!(desc.get_results(), Some(_)))
+        assert!(desc.get_results().is_some())
     }
     assert!(descs.len() == 1)
 }
 
+// test yaml including another yaml file
+#[test]
+fn include() {
+    test(
+        "tests/test_dumps/dump.vcd",
+        "tests/test_dumps/dump_include.yaml",
+        0,
+        &[correct_dump_a(), correct_dump_b()],
+    );
+}
+
+// test merging of several yaml files
+#[test]
+fn merged_files() {
+    let mut data = load_simulation_trace("tests/test_dumps/dump.vcd", false).unwrap();
+    let mut descs = load_merged_bus_analyzers(
+        &[
+            "tests/test_dumps/include/dump_a.yaml",
+            "tests/test_dumps/include/dump_b.yaml",
+        ],
+        0,
+        10000,
+        0.0001,
+        0.00001,
+        "plugins/python",
+    )
+    .unwrap();
+    for (desc, correct) in descs.iter_mut().zip([correct_dump_a(), correct_dump_b()]) {
+        desc.analyze(&mut data, false).unwrap();
+        assert_eq!(desc.get_results(), Some(&correct));
+    }
+    assert_eq!(descs.len(), 2);
+}
+
+// test that a bus defined in two files is rejected
+#[test]
+fn merged_files_duplicate() {
+    let result = load_merged_bus_analyzers(
+        &[
+            "tests/test_dumps/dump.yaml",
+            "tests/test_dumps/include/dump_a.yaml",
+        ],
+        0,
+        10000,
+        0.0001,
+        0.00001,
+        "plugins/python",
+    );
+    assert!(result.is_err_and(|e| e.to_string().contains("bus a_ is defined in both")));
+}
+
+// test IP-XACT import of an AXI4-Stream interface
+#[test]
+fn ipxact_stream() {
+    let mut data = load_simulation_trace("tests/test_dumps/test.vcd", false).unwrap();
+    let mut descs = load_ipxact_analyzers(
+        "tests/test_dumps/test_ipxact.xml",
+        None,
+        0,
+        10000,
+        0.0001,
+        0.00001,
+    )
+    .unwrap();
+    assert_eq!(descs.len(), 1);
+    descs[0].analyze(&mut data, false).unwrap();
+    assert_eq!(descs[0].get_results(), Some(&correct_test()));
+}
+
+// test IP-XACT import of an AXI4 interface with separate clock and reset interfaces
+#[test]
+fn ipxact_axi() {
+    let mut data = load_simulation_trace("tests/test_dumps/axi.vcd", false).unwrap();
+    let scope = ["test_taxi_axi_ram".to_string(), "uut".to_string()];
+    let mut descs = load_ipxact_analyzers(
+        "tests/test_dumps/axi_ipxact.xml",
+        Some(&scope),
+        0,
+        10000,
+        0.0001,
+        0.00001,
+    )
+    .unwrap();
+    assert_eq!(descs.len(), 2);
+    for desc in descs.iter_mut() {
+        desc.analyze(&mut data, false).unwrap();
+        assert!(desc.get_results().is_some())
+    }
+}
+
+// IP-XACT components can be described together with yaml descriptions
+#[test]
+fn ipxact_with_yaml() {
+    use busperf::session::{AnalysisConfig, Session};
+
+    let results = Session::new(AnalysisConfig::default())
+        .ipxact_file("tests/test_dumps/test_ipxact.xml", None)
+        .unwrap()
+        .bus_description_file("tests/test_dumps/phases.yaml")
+        .unwrap()
+        .trace_file("tests/test_dumps/test.vcd")
+        .unwrap()
+        .analyze()
+        .unwrap();
+    let names = results
+        .buses
+        .iter()
+        .map(|b| b.display_name())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        [
+            "test [valid]",
+            "test [late]",
+            "while [valid]",
+            "while [late]"
+        ]
+    );
+
+    // Unsupported bus interfaces are returned instead of being analyzed
+    let xml = std::fs::read_to_string("tests/test_dumps/test_ipxact.xml").unwrap();
+    let path = std::env::temp_dir().join("busperf_unsupported_ipxact.xml");
+    let irq = r#"<spirit:busInterfaces>
+    <spirit:busInterface>
+      <spirit:name>irq</spirit:name>
+      <spirit:busType spirit:name="interrupt"/>
+    </spirit:busInterface>"#;
+    std::fs::write(&path, xml.replacen("<spirit:busInterfaces>", irq, 1)).unwrap();
+    let session = Session::new(AnalysisConfig::default())
+        .ipxact_file(&path, None)
+        .unwrap();
+    assert_eq!(session.analyzers().unwrap().len(), 1);
+    assert_eq!(session.skipped_interfaces().len(), 1);
+    assert!(
+        session.skipped_interfaces()[0]
+            .ends_with("bus interface irq of type interrupt is not supported")
+    );
+    std::fs::remove_file(path).unwrap();
+}
+
 // functions returning correct usages for tests
 
+// counts of cycles in a window of a single channel bus
+fn window(
+    start: u64,
+    cycles: i32,
+    busy: i32,
+    backpressure: i32,
+    reset: i32,
+) -> Vec<bus_usage::CycleCounts> {
+    vec![bus_usage::CycleCounts {
+        start,
+        cycles,
+        busy,
+        backpressure,
+        reset,
+    }]
+}
+
 fn correct_test() -> BusUsage {
     BusUsage::SingleChannel(SingleChannelBusUsage::literal(
         "test",
@@ -352,6 +957,9 @@ fn correct_test() -> BusUsage {
         0,
         bus_usage::CurrentlyCalculating::Burst,
         2,
+        10000,
+        window(0, 22, 9, 5, 2),
+        None,
     ))
 }
 
@@ -369,6 +977,9 @@ fn correct_dump_a() -> BusUsage {
         0,
         bus_usage::CurrentlyCalculating::Delay,
         2000,
+        10000,
+        window(0, 30, 0, 0, 15),
+        None,
     ))
 }
 
@@ -386,5 +997,705 @@ fn correct_dump_b() -> BusUsage {
         0,
         bus_usage::CurrentlyCalculating::Delay,
         2000,
+        10000,
+        window(0, 30, 0, 0, 15),
+        None,
     ))
 }
+
+// trace followed while it is being written gives the same final results as a complete one
+#[test]
+fn follow() {
+    use std::{io::Write, time::Duration};
+
+    let trace = "tests/test_dumps/dump.vcd";
+    let yaml = "tests/test_dumps/dump.yaml";
+    let path = std::env::temp_dir().join("busperf_follow_test.vcd");
+    let content = std::fs::read(trace).unwrap();
+    let mut file = std::fs::File::create(&path).unwrap();
+    let writer = std::thread::spawn(move || {
+        for chunk in content.chunks(content.len() / 4 + 1) {
+            file.write_all(chunk).unwrap();
+            std::thread::sleep(Duration::from_millis(100));
+        }
+    });
+
+    let mut descs = load_bus_analyzers(yaml, 0, 10000, 0.0001, 0.00001, "plugins/python").unwrap();
+    let mut updates = 0;
+    let mut followed = vec![];
+    follow_simulation_trace(
+        path.to_str().unwrap(),
+        &mut descs,
+        Duration::from_millis(20),
+        Duration::from_millis(500),
+        false,
+        |descs, results, _, finished| {
+            updates += 1;
+            // Beginning of the trace may be too short to analyze
+            if finished {
+                assert!(results.iter().all(|r| r.is_ok()));
+                followed = descs
+                    .iter()
+                    .map(|d| d.get_results().cloned().unwrap())
+                    .collect();
+            }
+            Ok(())
+        },
+    )
+    .unwrap();
+    writer.join().unwrap();
+    std::fs::remove_file(path).unwrap();
+    assert!(updates > 1);
+    assert_eq!(followed, [correct_dump_a(), correct_dump_b()]);
+}
+
+// session gives the same results for descriptions and traces from every kind of source
+#[test]
+fn session() {
+    use busperf::session::{AnalysisConfig, Session};
+    use wellen::{LoadOptions, viewers};
+
+    let trace = "tests/test_dumps/dump.vcd";
+    let yaml = std::fs::read_to_string("tests/test_dumps/dump.yaml").unwrap();
+    let wellen_trace = || {
+        let header = viewers::read_header_from_file(trace, &LoadOptions::default()).unwrap();
+        let body = viewers::read_body(header.body, &header.hierarchy, None).unwrap();
+        SimulationData::from_wellen(header.hierarchy, body)
+    };
+    let sessions = [
+        Session::new(AnalysisConfig::default())
+            .bus_description_file("tests/test_dumps/dump.yaml")
+            .unwrap()
+            .trace_file(trace)
+            .unwrap(),
+        Session::new(AnalysisConfig::default())
+            .bus_description_str(&yaml)
+            .unwrap()
+            .trace(wellen_trace()),
+        Session::new(AnalysisConfig::default())
+            .bus_description_yaml(yaml_rust2::YamlLoader::load_from_str(&yaml).unwrap()[0].clone())
+            .unwrap()
+            .trace_file(trace)
+            .unwrap(),
+    ];
+    for mut session in sessions {
+        let results = session.analyze().unwrap();
+        assert!(results.failed.is_empty());
+        let usages: Vec<_> = results.buses.into_iter().map(|b| b.usage).collect();
+        assert_eq!(usages, [correct_dump_a(), correct_dump_b()]);
+    }
+
+    let results = Session::new(AnalysisConfig::default().max_burst_delay(1))
+        .bus_description_file("tests/test_dumps/test.yaml")
+        .unwrap()
+        .trace_file("tests/test_dumps/test.vcd")
+        .unwrap()
+        .analyze()
+        .unwrap();
+    let mut data = load_simulation_trace("tests/test_dumps/test.vcd", false).unwrap();
+    let mut descs =
+        load_bus_analyzers("tests/test_dumps/test.yaml", 1, 10000, 0.0001, 0.00001, "").unwrap();
+    descs[0].analyze(&mut data, false).unwrap();
+    assert_eq!(results.buses.len(), 1);
+    assert_eq!(Some(&results.buses[0].usage), descs[0].get_results());
+
+    let mut missing_trace = Session::new(AnalysisConfig::default())
+        .bus_description_str(&yaml)
+        .unwrap();
+    assert!(missing_trace.analyze().is_err());
+    assert!(
+        Session::new(AnalysisConfig::default())
+            .bus_description_str("interfaces: [")
+            .is_err()
+    );
+}
+
+// check of performance limits reports passed, violated and invalid limits
+#[test]
+fn check_limits() {
+    use busperf::check::{self, Limit, Outcome};
+    use busperf::session::{AnalysisConfig, Session};
+
+    let results = Session::new(AnalysisConfig::default())
+        .bus_description_file("tests/taxi_descriptions/axi_ram.yaml")
+        .unwrap()
+        .trace_file("tests/test_dumps/axi.vcd")
+        .unwrap()
+        .analyze()
+        .unwrap();
+    let limits = check::load_limits("tests/test_dumps/axi_limits.yaml").unwrap();
+    let outcomes = check::check_limits(&limits, &results);
+    assert_eq!(outcomes.len(), 4);
+    assert!(outcomes.iter().all(|(_, o)| o.passed()));
+
+    let limits = [
+        "ram_wr.p99(cmd_to_completion) < 6",
+        "ram_wr.count(cmd_to_completion)>=158",
+        "ram_x.bandwidth > 0",
+        "ram_rd.cmd_to_completion < 3",
+    ]
+    .map(|l| l.parse::<Limit>().unwrap());
+    let outcomes = check::check_limits(&limits, &results);
+    assert_eq!(outcomes[0].1, Outcome::Failed(6.0));
+    assert_eq!(outcomes[1].1, Outcome::Passed(158.0));
+    assert!(matches!(outcomes[2].1, Outcome::Error(_)));
+    assert!(matches!(outcomes[3].1, Outcome::Error(_)));
+
+    let mut junit = vec![];
+    check::write_junit(&mut junit, &outcomes).unwrap();
+    let junit = String::from_utf8(junit).unwrap();
+    assert!(junit.contains(r#"tests="4" failures="1" errors="2""#));
+    assert!(junit.contains(r#"name="ram_wr.p99(cmd_to_completion) &lt; 6""#));
+
+    for invalid in [
+        "ram_rd.bandwidth",
+        "bandwidth > 1",
+        "ram_rd.p101(x) < 1",
+        "a.b < c",
+    ] {
+        assert!(invalid.parse::<Limit>().is_err(), "{invalid}");
+    }
+}
+
+// comparison of two runs reports changes in the direction of improvements or regressions
+#[test]
+fn compare() {
+    use busperf::compare::{Status, compare};
+    use busperf::session::{AnalysisConfig, Session};
+    use busperf::show::ReportFormat;
+
+    let analyze = |yaml| {
+        Session::new(AnalysisConfig::default())
+            .bus_description_file(yaml)
+            .unwrap()
+            .trace_file("tests/test_dumps/dump.vcd")
+            .unwrap()
+            .analyze()
+            .unwrap()
+            .buses
+    };
+    let base = analyze("tests/test_dumps/dump.yaml");
+    let new = analyze("tests/test_dumps/dump_rst_high.yaml");
+
+    let comparison = compare(&base, &new, 5.0);
+    assert_eq!(comparison.buses.len(), 2);
+    assert!(comparison.only_in_base.is_empty() && comparison.only_in_new.is_empty());
+    let status = |bus: usize, statistic: &str| {
+        comparison.buses[bus]
+            .deltas
+            .iter()
+            .find(|d| d.statistic == statistic)
+            .unwrap()
+            .status
+    };
+    assert_eq!(status(0, "Busy"), Status::Improvement);
+    assert_eq!(status(0, "Transaction delays (p99)"), Status::Improvement);
+    assert_eq!(status(0, "Burst lengths (max)"), Status::Changed);
+    assert_eq!(status(0, "Reset"), Status::Unchanged);
+    assert!(
+        comparison.buses[1]
+            .deltas
+            .iter()
+            .all(|d| d.status == Status::Unchanged)
+    );
+    assert_eq!(comparison.buses[0].bucket_shifts.len(), 3);
+    assert_eq!(comparison.regressions(), 0);
+
+    let comparison = compare(&new, &base[..1], 5.0);
+    assert_eq!(comparison.regressions(), 7);
+    assert_eq!(comparison.only_in_base, ["b_"]);
+
+    let mut json = vec![];
+    comparison
+        .write(&mut json, ReportFormat::Json, false)
+        .unwrap();
+    let json = String::from_utf8(json).unwrap();
+    assert!(json.contains(r#""status": "regression""#));
+}
+
+// merging runs sums cycles, keeps all periods and reports spread of statistics
+#[test]
+fn merge() {
+    use busperf::merge::merge;
+    use busperf::session::{AnalysisConfig, Session};
+    use busperf::show::SavedData;
+
+    let analyze = |trace: &str, yaml: &str| {
+        let results = Session::new(AnalysisConfig::default())
+            .bus_description_file(yaml)
+            .unwrap()
+            .trace_file(trace)
+            .unwrap()
+            .analyze()
+            .unwrap();
+        SavedData {
+            trace_path: trace.to_owned(),
+            hash: String::new(),
+            timescale: results.timescale,
+            usages: results.buses,
+        }
+    };
+    let runs = [
+        analyze("tests/test_dumps/dump.vcd", "tests/test_dumps/dump.yaml"),
+        analyze(
+            "tests/test_dumps/dump.vcd",
+            "tests/test_dumps/dump_rst_high.yaml",
+        ),
+    ];
+    let (merged, spreads) = merge(&runs).unwrap();
+    assert_eq!(merged.trace_path, "tests/test_dumps/dump.vcd");
+    assert_eq!(merged.usages.len(), 2);
+    let BusUsage::SingleChannel(a) = &merged.usages[0].usage else {
+        panic!("expected single channel bus");
+    };
+    let a = a.get_statistics();
+    let Statistic::Percentage(cycles) = &a[0] else {
+        panic!("expected cycles");
+    };
+    assert_eq!(
+        cycles
+            .data_labels
+            .iter()
+            .map(|(v, _)| *v)
+            .collect::<Vec<_>>(),
+        [15.0, 0.0, 15.0, 0.0, 0.0, 30.0]
+    );
+    let Statistic::Bucket(delays) = &a[1] else {
+        panic!("expected transaction delays");
+    };
+    assert_eq!(delays.data.len(), 2);
+
+    let busy = spreads[0]
+        .statistics
+        .iter()
+        .find(|s| s.statistic == "Busy")
+        .unwrap();
+    assert_eq!(
+        (busy.runs, busy.mean, busy.min, busy.max),
+        (2, 7.5, 0.0, 15.0)
+    );
+    assert!((busy.std_dev - 10.6066).abs() < 1e-4);
+
+    // Merging a multi channel bus with itself keeps averaged statistics
+    let axi = analyze(
+        "tests/test_dumps/axi.vcd",
+        "tests/taxi_descriptions/axi_ram.yaml",
+    );
+    let (merged, _) = merge(&[
+        analyze(
+            "tests/test_dumps/axi.vcd",
+            "tests/taxi_descriptions/axi_ram.yaml",
+        ),
+        analyze(
+            "tests/test_dumps/axi.vcd",
+            "tests/taxi_descriptions/axi_ram.yaml",
+        ),
+    ])
+    .unwrap();
+    for (merged, single) in merged.usages.iter().zip(axi.usages.iter()) {
+        let merged = merged.usage.get_statistics(&[]);
+        let single = single.usage.get_statistics(&[]);
+        for (merged, single) in merged.iter().zip(single.iter()) {
+            match (merged, single) {
+                (Statistic::Timeline(m), Statistic::Timeline(s)) => {
+                    assert!((m.value - s.value).abs() < 1e-6, "{}", m.name);
+                    assert_eq!(m.values, s.values);
+                }
+                (Statistic::Bucket(m), Statistic::Bucket(s)) => {
+                    assert_eq!(m.data.len(), s.data.len() * 2)
+                }
+                _ => panic!("unexpected statistic"),
+            }
+        }
+    }
+}
+
+// a run in which an axi bus was not analyzed at all does not make merged averages invalid
+#[test]
+fn merge_idle_axi() {
+    use busperf::session::{AnalysisConfig, Session};
+    use libbusperf::bus_usage::MultiChannelBusUsage;
+
+    let results = Session::new(AnalysisConfig::default())
+        .bus_description_file("tests/taxi_descriptions/axi_ram.yaml")
+        .unwrap()
+        .trace_file("tests/test_dumps/axi.vcd")
+        .unwrap()
+        .analyze()
+        .unwrap();
+    let active = &results.buses[0].usage;
+    let BusUsage::MultiChannel(usage) = active else {
+        panic!("expected multi channel bus");
+    };
+    let mut idle = MultiChannelBusUsage::new("ram_rd", 10000, 10, 0.0001, 0.00001);
+    idle.end(0, vec![]);
+    assert!(idle.get_statistics(&[]).iter().any(|s| match s {
+        Statistic::Timeline(t) => t.value.is_nan(),
+        _ => false,
+    }));
+    let idle = BusUsage::MultiChannel(idle);
+
+    for usages in [[active, &idle], [&idle, active]] {
+        let merged = BusUsage::merge(&usages).unwrap();
+        let BusUsage::MultiChannel(merged) = &merged else {
+            panic!("expected multi channel bus");
+        };
+        assert_eq!(merged.transactions().len(), usage.transactions().len());
+        for (merged, single) in merged
+            .get_statistics(&[])
+            .iter()
+            .zip(usage.get_statistics(&[]).iter())
+        {
+            if let (Statistic::Timeline(m), Statistic::Timeline(s)) = (merged, single) {
+                assert!((m.value - s.value).abs() < 1e-6, "{}", m.name);
+                assert_eq!(m.vertical_lines, s.vertical_lines);
+            }
+        }
+    }
+}
+
+// json output contains every statistic with bucket bounds and periods in verbose mode
+#[test]
+fn json_output() {
+    use busperf::session::{AnalysisConfig, Session};
+    use busperf::show::{OutputType, show_data};
+
+    let results = Session::new(AnalysisConfig::default())
+        .bus_description_file("tests/test_dumps/test.yaml")
+        .unwrap()
+        .trace_file("tests/test_dumps/test.vcd")
+        .unwrap()
+        .analyze()
+        .unwrap();
+    let mut out = vec![];
+    show_data(
+        results.buses,
+        "tests/test_dumps/test.vcd".into(),
+        None,
+        results.timescale,
+        OutputType::Json,
+        &mut out,
+        true,
+        &[],
+    )
+    .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    assert_eq!(json["format_version"], 1);
+    let bus = &json["buses"][0];
+    assert_eq!(bus["name"], "test");
+    assert_eq!(bus["kind"], "single_channel");
+    let statistics = bus["statistics"].as_array().unwrap();
+    assert_eq!(statistics.len(), 6);
+    assert_eq!(statistics[0]["type"], "percentage");
+    assert_eq!(statistics[0]["values"]["Busy"], 9.0);
+    let delays = &statistics[1];
+    assert_eq!(delays["type"], "buckets");
+    assert_eq!(
+        delays["buckets"][2],
+        serde_json::json!({"bucket": 3, "range": "4-7", "min": 4, "max": 7, "count": 1})
+    );
+    assert_eq!(delays["periods"].as_array().unwrap().len(), 5);
+}
+
+// backpressure of a bus is attributed to the bus it feeds, if that one was busy or stalled
+#[test]
+fn root_causes() {
+    use busperf::root_cause::{CauseKind, root_causes};
+    use busperf::session::{AnalysisConfig, Session};
+
+    let results = Session::new(AnalysisConfig::default())
+        .bus_description_file("tests/test_dumps/pipeline.yaml")
+        .unwrap()
+        .trace_file("tests/test_dumps/pipeline.vcd")
+        .unwrap()
+        .analyze()
+        .unwrap();
+    let buses = results.buses.iter().collect::<Vec<_>>();
+    assert_eq!(buses[0].feeds, ["dst"]);
+    // States are recorded only for buses in chains
+    for bus in buses.iter() {
+        let BusUsage::SingleChannel(usage) = &bus.usage else {
+            panic!("expected single channel bus");
+        };
+        assert!(!usage.runs().is_empty());
+    }
+    let causes = root_causes(&buses)
+        .into_iter()
+        .map(|c| (c.bus, c.kind, c.cycles, c.upstream["src"]))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        causes,
+        [
+            ("dst".to_owned(), CauseKind::Saturated, 3, 3),
+            ("dst".to_owned(), CauseKind::Stalled, 2, 2),
+            ("src".to_owned(), CauseKind::Unexplained, 1, 1),
+        ]
+    );
+
+    let invalid = Session::new(AnalysisConfig::default())
+        .bus_description_str(
+            &std::fs::read_to_string("tests/test_dumps/pipeline.yaml")
+                .unwrap()
+                .replace("feeds: \"dst\"", "feeds: [\"dst\", \"sink\"]"),
+        )
+        .unwrap()
+        .analyzers();
+    assert!(invalid.is_err());
+    // Backpressure of multichannel buses is not known
+    let multichannel = Session::new(AnalysisConfig::default())
+        .bus_description_str(
+            &std::fs::read_to_string("tests/taxi_descriptions/axi_ram.yaml")
+                .unwrap()
+                .replacen(
+                    "custom_analyzer: \"AXIRdAnalyzer\"",
+                    "custom_analyzer: \"AXIRdAnalyzer\"\n    feeds: \"ram_wr\"",
+                    1,
+                ),
+        )
+        .unwrap()
+        .analyzers();
+    assert!(multichannel.is_err());
+}
+
+// files saved before the format had a version are loaded with the default timescale
+#[test]
+fn saved_format() {
+    use busperf::session::{AnalysisConfig, Session};
+    use busperf::show::{SavedData, load_data};
+
+    for (saved, trace, yaml) in [
+        (
+            "tests/test_dumps/legacy_test.busperf",
+            "tests/test_dumps/test.vcd",
+            "tests/test_dumps/test.yaml",
+        ),
+        (
+            "tests/test_dumps/legacy_axi.busperf",
+            "tests/test_dumps/axi.vcd",
+            "tests/taxi_descriptions/axi_ram.yaml",
+        ),
+    ] {
+        let legacy = load_data(saved).unwrap();
+        assert_eq!(legacy.trace_path, trace);
+        assert_eq!(legacy.timescale, Timescale::default());
+        let results = Session::new(AnalysisConfig::default())
+            .bus_description_file(yaml)
+            .unwrap()
+            .trace_file(trace)
+            .unwrap()
+            .analyze()
+            .unwrap();
+        assert_eq!(legacy.usages.len(), results.buses.len());
+        for (legacy, bus) in legacy.usages.iter().zip(results.buses.iter()) {
+            let displays = |b: &bus_usage::BusData| {
+                b.get_statistics(&[])
+                    .iter()
+                    .map(|s| s.display())
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(displays(legacy), displays(bus));
+            // Scope of the module is not saved, but shared by all signals of the bus
+            assert!(!legacy.scope.is_empty());
+            assert_eq!(legacy.scope, bus.scope);
+            // Transactions were not saved, so merged ones would not match their periods
+            if let BusUsage::MultiChannel(usage) = &legacy.usage {
+                assert!(usage.transactions().is_empty());
+                let merged = BusUsage::merge(&[&bus.usage, &legacy.usage]).unwrap();
+                let BusUsage::MultiChannel(merged) = merged else {
+                    panic!("expected multi channel bus");
+                };
+                assert!(merged.transactions().is_empty());
+            }
+        }
+
+        // Saved again in the current format
+        let path = std::env::temp_dir().join("busperf_saved_format.busperf");
+        let path = path.to_str().unwrap();
+        let mut file = std::fs::File::create(path).unwrap();
+        legacy.save(&mut file).unwrap();
+        drop(file);
+        let SavedData {
+            usages, timescale, ..
+        } = load_data(path).unwrap();
+        assert_eq!(timescale, Timescale::default());
+        assert_eq!(usages.len(), results.buses.len());
+    }
+}
+
+// exported records contain every transaction of multichannel buses and periods of single channel
+#[test]
+fn export_records() {
+    use busperf::export::{ExportFormat, RecordKind, records, write_records};
+    use busperf::session::{AnalysisConfig, Session};
+
+    let analyze = |trace: &str, yaml: &str| {
+        Session::new(AnalysisConfig::default())
+            .bus_description_file(yaml)
+            .unwrap()
+            .trace_file(trace)
+            .unwrap()
+            .analyze()
+            .unwrap()
+            .buses
+    };
+    let axi = analyze(
+        "tests/test_dumps/axi.vcd",
+        "tests/taxi_descriptions/axi_ram.yaml",
+    );
+    let transactions = records(&axi);
+    let BusUsage::MultiChannel(rd) = &axi[0].usage else {
+        panic!("expected multichannel bus");
+    };
+    assert_eq!(
+        transactions.iter().filter(|r| r.bus == "ram_rd").count(),
+        rd.transactions().len()
+    );
+    let first = &transactions[0];
+    assert_eq!(first.kind, RecordKind::Transaction);
+    assert_eq!(
+        (first.start, first.first_data, first.end, first.next),
+        (170000, Some(190000), 200000, Some(320000))
+    );
+    assert_eq!((first.resp, first.id), (Some("00"), Some("00000000")));
+
+    let single = analyze("tests/test_dumps/test.vcd", "tests/test_dumps/test.yaml");
+    let periods = records(&single);
+    assert_eq!(periods.len(), 10);
+    assert_eq!(periods[1].kind, RecordKind::Burst);
+    assert_eq!(
+        (periods[1].start, periods[1].end, periods[1].duration),
+        (4, 10, Some(4))
+    );
+
+    let mut out = vec![];
+    write_records(&mut out, &single, ExportFormat::JsonLines).unwrap();
+    let lines = String::from_utf8(out).unwrap();
+    assert_eq!(lines.lines().count(), 10);
+    let line: serde_json::Value = serde_json::from_str(lines.lines().next().unwrap()).unwrap();
+    assert_eq!(line["kind"], "delay");
+}
+
+// html report is self-contained and has charts of every kind of statistic
+#[test]
+fn html_report() {
+    use busperf::session::{AnalysisConfig, Session};
+    use busperf::show::{OutputType, show_data};
+
+    let results = Session::new(AnalysisConfig::default())
+        .bus_description_file("tests/taxi_descriptions/axi_ram.yaml")
+        .unwrap()
+        .trace_file("tests/test_dumps/axi.vcd")
+        .unwrap()
+        .analyze()
+        .unwrap();
+    let mut out = vec![];
+    show_data(
+        results.buses,
+        "tests/test_dumps/axi.vcd".into(),
+        None,
+        results.timescale,
+        OutputType::Report,
+        &mut out,
+        false,
+        &[],
+    )
+    .unwrap();
+    let html = String::from_utf8(out).unwrap();
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(!html.contains("<script"));
+    assert!(html.contains("<h2>ram_rd</h2>") && html.contains("<h2>ram_wr</h2>"));
+    // timeline and buckets chart for each bus
+    assert_eq!(html.matches("<svg").count(), 4);
+    assert!(html.contains("<polyline"));
+    assert!(html.contains("<td>Bandwidth [t/clk]</td><td>0.0773</td>"));
+    assert!(html.contains(">Value [t/clk]</text>") && !html.contains(">Value [%]</text>"));
+
+    // percentages and throughput of single channel buses are drawn on separate axes
+    let results = Session::new(AnalysisConfig::default())
+        .bus_description_file("tests/test_dumps/test.yaml")
+        .unwrap()
+        .trace_file("tests/test_dumps/test.vcd")
+        .unwrap()
+        .analyze()
+        .unwrap();
+    let mut out = vec![];
+    show_data(
+        results.buses,
+        "tests/test_dumps/test.vcd".into(),
+        None,
+        results.timescale,
+        OutputType::Report,
+        &mut out,
+        false,
+        &[],
+    )
+    .unwrap();
+    let html = String::from_utf8(out).unwrap();
+    assert_eq!(html.matches(">Value [%]</text>").count(), 1);
+    assert_eq!(html.matches(">Value [t/clk]</text>").count(), 1);
+}
+
+// exact percentiles, mean and standard deviation of durations of periods
+#[test]
+fn duration_summary() {
+    use libbusperf::bus_usage::BucketsStatistic;
+
+    let periods = [3, 1, 6, 2, 1]
+        .map(|d| Period::literal(0, d as u64, d))
+        .to_vec();
+    let statistic = BucketsStatistic::new("Transaction delays", &periods, 1, "Red", "");
+    let summary = statistic.summary().unwrap();
+    assert_eq!(
+        (summary.count, summary.min, summary.max, summary.mean),
+        (5, 1, 6, 2.6)
+    );
+    assert_eq!((summary.p50, summary.p90, summary.p99), (2, 6, 6));
+    assert!((summary.std_dev - 1.8547).abs() < 1e-4);
+    assert_eq!(statistic.percentile(20.0), Some(1));
+    assert_eq!(statistic.percentile(60.0), Some(2));
+
+    let empty = vec![];
+    let statistic = BucketsStatistic::new("Burst lengths", &empty, 1, "Blue", "");
+    assert_eq!(statistic.summary(), None);
+    assert_eq!(statistic.mean(), None);
+}
+
+// histograms are binned as set in yaml, falling back to binning from the config
+#[test]
+fn binning() {
+    use busperf::session::{AnalysisConfig, Session};
+    use libbusperf::binning::{Binning, Bins};
+
+    let mut bins = Bins::default();
+    bins.parse_setting("linear:2").unwrap();
+    bins.parse_setting("Transaction delays=log2").unwrap();
+    let buses = Session::new(AnalysisConfig::default().bins(bins))
+        .bus_description_file("tests/test_dumps/test_bins.yaml")
+        .unwrap()
+        .trace_file("tests/test_dumps/test.vcd")
+        .unwrap()
+        .analyze()
+        .unwrap()
+        .buses;
+    let statistics = buses[0].get_statistics(&[]);
+    let [_, Statistic::Bucket(delays), Statistic::Bucket(bursts), ..] = &statistics[..] else {
+        panic!("expected delays and burst lengths");
+    };
+    assert_eq!(delays.binning, Binning::Edges(vec![2, 4]));
+    assert_eq!(bursts.binning, Binning::Linear(2));
+    let bucket = |s: &bus_usage::BucketsStatistic, duration| {
+        let bucket = s.binning.bin(duration);
+        (
+            s.bucket_label(bucket),
+            s.get_buckets().get(&bucket).copied(),
+        )
+    };
+    assert_eq!(bucket(delays, 1), ("<2".to_owned(), Some(1)));
+    assert_eq!(bucket(delays, 3), ("2-3".to_owned(), Some(3)));
+    assert_eq!(bucket(delays, 6), ("4+".to_owned(), Some(1)));
+    assert_eq!(bucket(bursts, 1), ("0-1".to_owned(), Some(3)));
+    assert_eq!(bucket(bursts, 5), ("4-5".to_owned(), Some(1)));
+
+    assert!("edges:3,1".parse::<Binning>().is_err());
+    assert!("linear:0".parse::<Binning>().is_err());
+}